@@ -0,0 +1,69 @@
+//! A busy world for measuring how the simulation scales, shared by the benchmark and its
+//! smoke test
+
+use base::{
+    plane::{Destination, Plane},
+    world::{DirectionCardinal, DirectionGrid, PlaneKind, World},
+};
+use rand::{rngs::StdRng, seq::IndexedRandom, Rng, SeedableRng};
+
+/// Width and height of the busy world
+pub const SIZE: usize = 200;
+/// Planes start this far from the walls, so they can fly for a while without leaving the map
+pub const MARGIN: usize = 50;
+
+/// A large world with `planes` planes flying around, the same for the same `seed`
+pub fn busy_world(seed: u64, planes: usize) -> World {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut world = World::new(SIZE, SIZE);
+    for (id, wall) in [
+        DirectionGrid::Up,
+        DirectionGrid::Down,
+        DirectionGrid::Left,
+        DirectionGrid::Right,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let inward = DirectionCardinal::from(wall).opposite();
+        world
+            .place_exit(wall, inward, SIZE / 2, id as u8)
+            .expect("exit is on the wall");
+    }
+    world
+        .place_route_in_line([0, SIZE / 2], [SIZE - 1, SIZE / 2])
+        .expect("route is in the world");
+
+    for i in 0..planes {
+        let id = char::from_u32(0x100 + i as u32).expect("enough ids");
+        let kind = if rng.random_bool(0.5) {
+            PlaneKind::Jet
+        } else {
+            PlaneKind::Small
+        };
+        let mut plane = Plane::new(
+            [
+                rng.random_range(MARGIN..SIZE - MARGIN),
+                rng.random_range(MARGIN..SIZE - MARGIN),
+            ]
+            .into(),
+            *DirectionCardinal::ALL.choose(&mut rng).expect("not empty"),
+            kind,
+            id,
+            Destination::Exit(rng.random_range(0..4)),
+        );
+        plane.height = rng.random_range(1..=9);
+        plane.target_height = plane.height;
+        plane.spec.fuel = usize::MAX / 2;
+        world.insert_plane(plane);
+    }
+    world
+}
+
+/// Simulate `ticks` ticks, fewer than [`MARGIN`] so no plane reaches a wall
+pub fn run(world: &mut World, ticks: usize) {
+    assert!(ticks < MARGIN, "planes would reach the walls");
+    for _ in 0..ticks {
+        world.tick_planes();
+    }
+}
@@ -0,0 +1,23 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+mod load;
+
+const SEED: u64 = 1337;
+const TICKS: usize = 10;
+
+fn tick_planes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tick_planes");
+    for planes in [10, 100, 1000] {
+        group.bench_with_input(BenchmarkId::from_parameter(planes), &planes, |b, planes| {
+            b.iter_batched(
+                || load::busy_world(SEED, *planes),
+                |mut world| load::run(&mut world, TICKS),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, tick_planes);
+criterion_main!(benches);
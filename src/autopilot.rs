@@ -0,0 +1,144 @@
+//! A simple controller that flies every plane straight to its destination
+//!
+//! The autopilot does not plan ahead or keep planes apart, it only turns each plane towards
+//! its destination while avoiding walls, obstacles and the wrong exits on the next move. That is
+//! good enough for the demo on the start screen.
+
+use tracing::debug;
+
+use crate::{
+    command::Command,
+    plane::{Destination, Plane, MIN_FLIGHT_HEIGHT},
+    world::{DirectionCardinal, Pos, World, WorldTile},
+};
+
+/// The commands that point every plane at its destination
+///
+/// Planes that already fly the right way at the right height get no command.
+pub fn commands(world: &World) -> Vec<Command> {
+    let mut commands: Vec<Command> = world
+        .planes()
+        .filter_map(|plane| vector(world, plane))
+        .collect();
+    commands.sort_by_key(|command| command.plane());
+    commands
+}
+
+/// Give every plane the command from [`commands`], returns how many were carried out
+pub fn fly(world: &mut World) -> usize {
+    let mut carried_out = 0;
+    for command in commands(world) {
+        match command.apply(world) {
+            Ok(()) => carried_out += 1,
+            Err(e) => debug!(plane = %command.plane(), "autopilot command failed: {e}"),
+        }
+    }
+    carried_out
+}
+
+/// The tile the plane has to reach and the heading and height it flies from there on
+///
+/// Planes land by flying onto the airport from the tile before it, in the direction of the
+/// runway.
+fn waypoint(world: &World, plane: &Plane) -> Option<(Pos, Option<(DirectionCardinal, u8)>)> {
+    let target = world.destination_pos(plane.destination)?;
+    if let Destination::Airport(_) = plane.destination {
+        let Some(WorldTile::Airport(runway, _)) = world.tile(target) else {
+            return None;
+        };
+        let runway: DirectionCardinal = runway.into();
+        let approach = target.step(runway.opposite())?;
+        return Some((approach, Some((runway, 0))));
+    }
+    Some((target, None))
+}
+
+fn vector(world: &World, plane: &Plane) -> Option<Command> {
+    let target = world.destination_pos(plane.destination)?;
+    if plane.pos == target {
+        return None;
+    }
+    let (waypoint, final_leg) = waypoint(world, plane)?;
+    let (heading, height) = match final_leg {
+        Some(final_leg) if plane.pos == waypoint => final_leg,
+        _ => {
+            let height = match plane.destination {
+                Destination::Airport(_) => MIN_FLIGHT_HEIGHT,
+                Destination::Exit(_) => plane.spec.exit_height.max(MIN_FLIGHT_HEIGHT),
+            };
+            (best_heading(world, plane, waypoint)?, height)
+        }
+    };
+    if heading == plane.direction && height == plane.target_height && !plane.holding {
+        return None;
+    }
+    Some(Command::Vector {
+        plane: plane.id,
+        heading,
+        height,
+    })
+}
+
+/// The safe heading that brings the plane closest to `waypoint`, keeping the current heading
+/// if it is as good as any other
+fn best_heading(world: &World, plane: &Plane, waypoint: Pos) -> Option<DirectionCardinal> {
+    let mut headings = world.movement_mode().headings();
+    headings.sort_by_key(|heading| *heading != plane.direction);
+    headings
+        .into_iter()
+        .filter_map(|heading| {
+            let next = plane.pos.step(heading)?;
+            safe(world, plane, next).then_some((heading, distance(next, waypoint)))
+        })
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(heading, _)| heading)
+}
+
+/// The plane can fly to `next` without crashing or leaving through the wrong exit
+fn safe(world: &World, plane: &Plane, next: Pos) -> bool {
+    let Some(tile) = world.tile(next) else {
+        return false;
+    };
+    let on_edge =
+        next.x == 0 || next.y == 0 || next.x + 1 == world.width() || next.y + 1 == world.height();
+    tile != WorldTile::Obstacle
+        && (!on_edge || world.destination_pos(plane.destination) == Some(next))
+}
+
+/// Moves needed from `a` to `b` if the plane could fly in all eight directions
+fn distance(a: Pos, b: Pos) -> usize {
+    a.x.abs_diff(b.x).max(a.y.abs_diff(b.y))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::world::{DirectionGrid, PlaneKind, State};
+
+    #[test]
+    fn test_autopilot_flies_plane_to_its_exit() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 12, 0)
+            .unwrap()
+            .place_tile(WorldTile::Obstacle, [7, 8])
+            .unwrap();
+        world.insert_plane(Plane::new(
+            [5, 10].into(),
+            DirectionCardinal::West,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        ));
+
+        for _ in 0..100 {
+            if world.planes().count() == 0 {
+                break;
+            }
+            fly(&mut world);
+            assert!(matches!(world.tick_planes(), State::Ongoing));
+        }
+        assert_eq!(world.stats().exits, 1);
+        assert!(commands(&world).is_empty());
+    }
+}
@@ -0,0 +1,969 @@
+use std::{fmt::Display, str::FromStr};
+
+use tracing::info;
+
+use crate::{
+    error::Error,
+    level::{Level, Spawn},
+    plane::{Destination, Order, MAX_HEIGHT, MIN_FLIGHT_HEIGHT},
+    world::{DirectionCardinal, PlaneKind, World, WorldTile},
+};
+
+/// Something the player tells the planes to do, typed in below the map
+///
+/// Every command starts with the id of the plane it is about. Spaces are ignored, so `a=c` and
+/// `a = c` are the same command.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// `a = c`: plane `plane` gets the destination of plane `from`
+    CopyDestination { plane: char, from: char },
+    /// `a <> c`: planes `plane` and `other` trade destinations
+    SwapDestinations { plane: char, other: char },
+    /// `a ^ c` or `a v c`: plane `plane` flies one level above or below plane `other`
+    ///
+    /// The new height is kept between [`MIN_FLIGHT_HEIGHT`] and [`MAX_HEIGHT`].
+    AltitudeRelative {
+        plane: char,
+        other: char,
+        above: bool,
+    },
+    /// `a OK`: plane `plane` may leave or land when it reaches its destination
+    Clear { plane: char },
+    /// `a v NE 9`: plane `plane` turns to `heading` and climbs or descends to `height`
+    ///
+    /// Both are checked before either is carried out, so a bad heading or height changes
+    /// nothing. With [safety checks](World::set_safety_checks) a heading that crashes the
+    /// plane right away is bad too. A height below [`MIN_FLIGHT_HEIGHT`] is only good if the
+    /// plane [touches down](World::touchdown_pos) on an airport.
+    Vector {
+        plane: char,
+        heading: DirectionCardinal,
+        height: u8,
+    },
+    /// `a via b0 b1`: plane `plane` flies over the beacons of `route` in order, then on to its
+    /// destination, see [`World::fly_via`]
+    Via { plane: char, route: BeaconRoute },
+    /// `a land`: plane `plane` lands at the airport it is flying over, see
+    /// [`World::land_here`]
+    Land { plane: char },
+}
+
+/// A [`Command::Via`] leads over at most this many beacons
+pub const MAX_ROUTE_BEACONS: usize = 8;
+
+/// The ids of the beacons of a [`Command::Via`], in the order the plane flies over them
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BeaconRoute {
+    beacons: [u8; MAX_ROUTE_BEACONS],
+    len: usize,
+}
+
+impl BeaconRoute {
+    /// A route over `beacons`, at most [`MAX_ROUTE_BEACONS`] of them
+    pub fn new(beacons: &[u8]) -> Result<Self, Error> {
+        if beacons.len() > MAX_ROUTE_BEACONS {
+            return Err(Error::RouteTooLong(beacons.len(), MAX_ROUTE_BEACONS));
+        }
+        let mut route = Self {
+            beacons: [0; MAX_ROUTE_BEACONS],
+            len: beacons.len(),
+        };
+        route.beacons[..beacons.len()].copy_from_slice(beacons);
+        Ok(route)
+    }
+
+    pub fn beacons(&self) -> &[u8] {
+        &self.beacons[..self.len]
+    }
+}
+
+/// One command of every kind, in the order of the help screen
+pub const EXAMPLES: [Command; 7] = [
+    Command::Vector {
+        plane: 'a',
+        heading: DirectionCardinal::NorthEast,
+        height: 5,
+    },
+    Command::AltitudeRelative {
+        plane: 'a',
+        other: 'c',
+        above: true,
+    },
+    Command::CopyDestination {
+        plane: 'a',
+        from: 'c',
+    },
+    Command::SwapDestinations {
+        plane: 'a',
+        other: 'c',
+    },
+    Command::Clear { plane: 'a' },
+    Command::Via {
+        plane: 'a',
+        route: BeaconRoute {
+            beacons: [0, 1, 0, 0, 0, 0, 0, 0],
+            len: 2,
+        },
+    },
+    Command::Land { plane: 'a' },
+];
+
+/// `+ 0 jet a1`: a plane comes in through exit `exit`, only in practice mode
+///
+/// The kind and the destination may be left out, the plane is small then and flies to a random
+/// destination. Destinations are `e` or `a` followed by the id of an exit or an airport.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SpawnRequest {
+    pub exit: u8,
+    pub kind: PlaneKind,
+    pub destination: Option<Destination>,
+}
+
+/// The [`SpawnRequest`] on the help screen
+const SPAWN_EXAMPLE: SpawnRequest = SpawnRequest {
+    exit: 0,
+    kind: PlaneKind::Jet,
+    destination: Some(Destination::Airport(1)),
+};
+
+/// The lines of the help screen about commands, an [example](EXAMPLES) of each command and
+/// what it does
+pub fn help() -> Vec<String> {
+    let examples: Vec<String> = EXAMPLES.iter().map(Command::to_string).collect();
+    let spawn = SPAWN_EXAMPLE.to_string();
+    let width = examples
+        .iter()
+        .chain([&spawn])
+        .map(|e| e.len())
+        .max()
+        .unwrap_or_default();
+    EXAMPLES
+        .iter()
+        .zip(examples)
+        .map(|(command, example)| format!("{example:<width$}  {}", command.description()))
+        .chain([format!(
+            "{spawn:<width$}  practice mode: a jet to airport 1 comes in through exit 0"
+        )])
+        .collect()
+}
+
+impl SpawnRequest {
+    /// Let the plane into `level`, which has to be in [practice mode](crate::level::SpawnPolicy::Manual)
+    pub fn apply(self, level: &mut Level) -> Result<Spawn, Error> {
+        let spawn = level.spawn_manual(self.exit, self.kind, self.destination)?;
+        info!(plane = %spawn.plane, exit = spawn.exit, "plane let in by hand");
+        Ok(spawn)
+    }
+}
+
+impl FromStr for SpawnRequest {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || Error::BadCommand(s.to_string());
+        let rest = s.trim_start().strip_prefix('+').ok_or_else(bad)?;
+        let mut words = rest.split_whitespace();
+        let exit = words.next().ok_or_else(bad)?.parse().map_err(|_| bad())?;
+        let mut request = Self {
+            exit,
+            kind: PlaneKind::Small,
+            destination: None,
+        };
+        for word in words {
+            let word = word.to_ascii_lowercase();
+            let id = || word[1..].parse().map_err(|_| bad());
+            match word.as_str() {
+                "small" => request.kind = PlaneKind::Small,
+                "jet" => request.kind = PlaneKind::Jet,
+                _ if word.starts_with('e') => request.destination = Some(Destination::Exit(id()?)),
+                _ if word.starts_with('a') => {
+                    request.destination = Some(Destination::Airport(id()?))
+                }
+                _ => return Err(bad()),
+            }
+        }
+        Ok(request)
+    }
+}
+
+impl Display for SpawnRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "+ {} {}", self.exit, self.kind)?;
+        match self.destination {
+            Some(Destination::Exit(id)) => write!(f, " e{id}"),
+            Some(Destination::Airport(id)) => write!(f, " a{id}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Command {
+    /// What the command does in a few words, with `a` for the plane and `c` for the other plane
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::CopyDestination { .. } => "a flies to where c is going",
+            Self::SwapDestinations { .. } => "a and c trade destinations",
+            Self::AltitudeRelative { above: true, .. } => {
+                "a flies one level above c, `v` for one level below"
+            }
+            Self::AltitudeRelative { above: false, .. } => {
+                "a flies one level below c, `^` for one level above"
+            }
+            Self::Clear { .. } => "a may leave or land when it reaches its destination",
+            Self::Vector { .. } => "a turns to a heading and climbs or descends to a height",
+            Self::Via { .. } => "a flies over beacon 0, then beacon 1, then to its destination",
+            Self::Land { .. } => "a lands at the airport it is flying over",
+        }
+    }
+
+    /// The plane the command changes
+    pub fn plane(self) -> char {
+        match self {
+            Self::CopyDestination { plane, .. }
+            | Self::SwapDestinations { plane, .. }
+            | Self::AltitudeRelative { plane, .. }
+            | Self::Clear { plane }
+            | Self::Vector { plane, .. }
+            | Self::Via { plane, .. }
+            | Self::Land { plane } => plane,
+        }
+    }
+
+    /// Carry out the command for `controller`, who may only command their own planes
+    ///
+    /// Other planes can still be referred to, for example to copy their destination. Swapping
+    /// destinations changes both planes, so both have to belong to `controller`.
+    pub fn apply_as(self, world: &mut World, controller: u8) -> Result<(), Error> {
+        let mut changed = vec![self.plane()];
+        if let Self::SwapDestinations { other, .. } = self {
+            changed.push(other);
+        }
+        for plane in changed {
+            let plane = world.resolve_plane_id(plane)?;
+            let owner = world
+                .plane(plane)
+                .ok_or(Error::NoPlaneForID(plane))?
+                .controller;
+            if owner != controller {
+                return Err(Error::NotYourPlane(plane, controller));
+            }
+        }
+        self.apply(world)
+    }
+
+    /// Carry out the command in `world`, no matter which controller the plane belongs to
+    pub fn apply(self, world: &mut World) -> Result<(), Error> {
+        match self {
+            Self::CopyDestination { plane, from } => {
+                let from = world.resolve_plane_id(from)?;
+                let plane = world.resolve_plane_id(plane)?;
+                let destination = world
+                    .plane(from)
+                    .ok_or(Error::NoPlaneForID(from))?
+                    .destination;
+                world
+                    .plane_mut(plane)
+                    .ok_or(Error::NoPlaneForID(plane))?
+                    .destination = destination;
+                info!(plane = %plane, from = %from, %destination, "destination copied");
+            }
+            Self::SwapDestinations { plane, other } => {
+                let plane = world.resolve_plane_id(plane)?;
+                let other = world.resolve_plane_id(other)?;
+                let a = world
+                    .plane(plane)
+                    .ok_or(Error::NoPlaneForID(plane))?
+                    .destination;
+                let b = world
+                    .plane(other)
+                    .ok_or(Error::NoPlaneForID(other))?
+                    .destination;
+                world
+                    .plane_mut(plane)
+                    .ok_or(Error::NoPlaneForID(plane))?
+                    .destination = b;
+                world
+                    .plane_mut(other)
+                    .ok_or(Error::NoPlaneForID(other))?
+                    .destination = a;
+                info!(plane = %plane, other = %other, "destinations swapped");
+            }
+            Self::AltitudeRelative {
+                plane,
+                other,
+                above,
+            } => {
+                let other = world.resolve_plane_id(other)?;
+                let plane = world.resolve_plane_id(plane)?;
+                let reference = world.plane(other).ok_or(Error::NoPlaneForID(other))?.height;
+                let height = if above {
+                    reference.saturating_add(1)
+                } else {
+                    reference.saturating_sub(1)
+                }
+                .clamp(MIN_FLIGHT_HEIGHT, MAX_HEIGHT);
+                world
+                    .plane_mut(plane)
+                    .ok_or(Error::NoPlaneForID(plane))?
+                    .set_target_height(height)?;
+                info!(plane = %plane, other = %other, height, "altitude set relative to other plane");
+            }
+            Self::Clear { plane } => {
+                let plane = world.resolve_plane_id(plane)?;
+                world
+                    .plane_mut(plane)
+                    .ok_or(Error::NoPlaneForID(plane))?
+                    .cleared = true;
+                info!(plane = %plane, "plane cleared");
+            }
+            Self::Vector {
+                plane,
+                heading,
+                height,
+            } => {
+                let plane = world.resolve_plane_id(plane)?;
+                if !world.movement_mode().allows(heading) {
+                    return Err(Error::HeadingNotAllowed(heading));
+                }
+                if height > MAX_HEIGHT {
+                    return Err(Error::HeightTooHigh(height, MAX_HEIGHT));
+                }
+                if height < MIN_FLIGHT_HEIGHT
+                    && !matches!(
+                        world
+                            .touchdown_pos(plane, heading)
+                            .and_then(|pos| world.tile(pos)),
+                        Some(WorldTile::Airport(..))
+                    )
+                {
+                    return Err(Error::NotOverAirport(plane));
+                }
+                world.check_heading_safe(plane, heading)?;
+                let p = world.plane_mut(plane).ok_or(Error::NoPlaneForID(plane))?;
+                p.apply(Order::Heading(heading))?;
+                p.apply(Order::Height(height))?;
+                info!(plane = %plane, %heading, height, "plane vectored");
+            }
+            Self::Via { plane, route } => {
+                let plane = world.resolve_plane_id(plane)?;
+                world.fly_via(plane, route.beacons())?;
+                info!(plane = %plane, beacons = ?route.beacons(), "plane routed via beacons");
+            }
+            Self::Land { plane } => {
+                let plane = world.resolve_plane_id(plane)?;
+                let airport = world.land_here(plane)?;
+                info!(plane = %plane, airport, "plane lands at the airport below");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The operators that can follow the plane id, and whether another plane id comes after them
+const VERBS: [(&str, bool); 6] = [
+    ("=", true),
+    ("<>", true),
+    ("^", true),
+    ("v", true),
+    ("OK", false),
+    ("land", false),
+];
+
+/// Ways to complete a partially typed command, the most likely first
+///
+/// Every suggestion is a full input that replaces what was typed. Nothing is suggested for
+/// ids of planes that don't exist and for inputs that can't become a command anymore.
+pub fn suggestions(world: &World, input: &str) -> Vec<String> {
+    let mut ids: Vec<char> = world.planes().map(|p| p.id).collect();
+    ids.sort();
+    let tokens: Vec<char> = input.chars().filter(|c| !c.is_whitespace()).collect();
+
+    let Some((first, rest)) = tokens.split_first() else {
+        return ids.iter().map(|id| id.to_string()).collect();
+    };
+    let Ok(plane) = world.resolve_plane_id(*first) else {
+        return Vec::new();
+    };
+    let rest: String = rest.iter().collect::<String>().to_ascii_lowercase();
+    let mut out = Vec::new();
+    for (verb, takes_plane) in VERBS {
+        if takes_plane {
+            if verb.starts_with(rest.as_str()) {
+                out.extend(
+                    ids.iter()
+                        .filter(|id| **id != plane)
+                        .map(|other| format!("{first} {verb} {other}")),
+                );
+            }
+        } else if rest.len() < verb.len() && verb.to_ascii_lowercase().starts_with(&rest) {
+            out.push(format!("{first} {verb}"));
+        }
+    }
+    out
+}
+
+impl FromStr for Command {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+        match tokens.as_slice() {
+            [plane, '=', from] if plane.is_ascii_alphabetic() && from.is_ascii_alphabetic() => {
+                Ok(Self::CopyDestination {
+                    plane: *plane,
+                    from: *from,
+                })
+            }
+            [plane, '<', '>', other]
+                if plane.is_ascii_alphabetic() && other.is_ascii_alphabetic() =>
+            {
+                Ok(Self::SwapDestinations {
+                    plane: *plane,
+                    other: *other,
+                })
+            }
+            [plane, op @ ('^' | 'v'), other]
+                if plane.is_ascii_alphabetic() && other.is_ascii_alphabetic() =>
+            {
+                Ok(Self::AltitudeRelative {
+                    plane: *plane,
+                    other: *other,
+                    above: *op == '^',
+                })
+            }
+            [plane, 'o' | 'O', 'k' | 'K'] if plane.is_ascii_alphabetic() => {
+                Ok(Self::Clear { plane: *plane })
+            }
+            [plane, 'l' | 'L', 'a' | 'A', 'n' | 'N', 'd' | 'D'] if plane.is_ascii_alphabetic() => {
+                Ok(Self::Land { plane: *plane })
+            }
+            [plane, 'v' | 'V', 'i' | 'I', 'a' | 'A', rest @ ..]
+                if plane.is_ascii_alphabetic() && !rest.is_empty() =>
+            {
+                let rest: String = rest.iter().collect::<String>().to_ascii_lowercase();
+                let mut ids = rest.split('b');
+                if ids.next() != Some("") {
+                    return Err(Error::BadCommand(s.to_string()));
+                }
+                let beacons = ids
+                    .map(|id| id.parse().map_err(|_| Error::BadCommand(s.to_string())))
+                    .collect::<Result<Vec<u8>, Error>>()?;
+                Ok(Self::Via {
+                    plane: *plane,
+                    route: BeaconRoute::new(&beacons)?,
+                })
+            }
+            [plane, 'v', rest @ ..] if plane.is_ascii_alphabetic() && rest.len() > 1 => {
+                let split = rest
+                    .iter()
+                    .rposition(|c| !c.is_ascii_digit())
+                    .map_or(0, |i| i + 1);
+                let (heading, height) = rest.split_at(split);
+                if heading.is_empty() || height.is_empty() {
+                    return Err(Error::BadCommand(s.to_string()));
+                }
+                Ok(Self::Vector {
+                    plane: *plane,
+                    heading: heading.iter().collect::<String>().parse()?,
+                    height: height
+                        .iter()
+                        .collect::<String>()
+                        .parse()
+                        .map_err(|_| Error::BadCommand(s.to_string()))?,
+                })
+            }
+            _ => Err(Error::BadCommand(s.to_string())),
+        }
+    }
+}
+
+impl Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CopyDestination { plane, from } => write!(f, "{plane} = {from}"),
+            Self::SwapDestinations { plane, other } => write!(f, "{plane} <> {other}"),
+            Self::AltitudeRelative {
+                plane,
+                other,
+                above,
+            } => write!(f, "{plane} {} {other}", if *above { '^' } else { 'v' }),
+            Self::Clear { plane } => write!(f, "{plane} OK"),
+            Self::Vector {
+                plane,
+                heading,
+                height,
+            } => write!(f, "{plane} v {heading} {height}"),
+            Self::Via { plane, route } => {
+                write!(f, "{plane} via")?;
+                for beacon in route.beacons() {
+                    write!(f, " b{beacon}")?;
+                }
+                Ok(())
+            }
+            Self::Land { plane } => write!(f, "{plane} land"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        plane::{Destination, Plane, QueuedOrder, Trigger, START_HEIGHT},
+        world::{DirectionGrid, MovementMode, PlaneKind, State, WorldTile},
+    };
+
+    use super::*;
+
+    fn world() -> World {
+        let mut world = World::new(20, 20);
+        for (id, pos, destination) in [
+            ('a', [1, 1], Destination::Exit(0)),
+            ('c', [10, 10], Destination::Airport(1)),
+        ] {
+            world.insert_plane(Plane::new(
+                pos.into(),
+                DirectionCardinal::North,
+                PlaneKind::Jet,
+                id,
+                destination,
+            ));
+        }
+        world
+    }
+
+    fn run(world: &mut World, input: &str) -> Result<(), Error> {
+        input.parse::<Command>()?.apply(world)
+    }
+
+    fn run_as(world: &mut World, input: &str, controller: u8) -> Result<(), Error> {
+        input.parse::<Command>()?.apply_as(world, controller)
+    }
+
+    #[test]
+    fn test_command_copy_destination() {
+        let mut world = world();
+        let command: Command = "a = C".parse().unwrap();
+        assert_eq!(command, "a=C".parse().unwrap());
+        command.apply(&mut world).unwrap();
+        assert_eq!(
+            world.plane('a').unwrap().destination,
+            Destination::Airport(1)
+        );
+        assert_eq!(
+            world.plane('c').unwrap().destination,
+            Destination::Airport(1)
+        );
+    }
+
+    #[test]
+    fn test_command_copy_destination_missing_plane() {
+        let mut world = world();
+        for (input, missing) in [("a = x", 'x'), ("x = c", 'x')] {
+            let command: Command = input.parse().unwrap();
+            assert!(matches!(
+                command.apply(&mut world),
+                Err(Error::NoPlaneForID(id)) if id == missing
+            ));
+        }
+        assert_eq!(world.plane('a').unwrap().destination, Destination::Exit(0));
+        assert!("a = ".parse::<Command>().is_err());
+        assert!("a = 1".parse::<Command>().is_err());
+    }
+
+    #[test]
+    fn test_command_swap_destinations() {
+        let mut world = world();
+        let command: Command = "a <> c".parse().unwrap();
+        assert_eq!(
+            command,
+            Command::SwapDestinations {
+                plane: 'a',
+                other: 'c'
+            }
+        );
+        assert_eq!(command.to_string().parse::<Command>().unwrap(), command);
+        command.apply(&mut world).unwrap();
+        assert_eq!(
+            world.plane('a').unwrap().destination,
+            Destination::Airport(1)
+        );
+        assert_eq!(world.plane('c').unwrap().destination, Destination::Exit(0));
+
+        for input in ["a <> x", "x <> c"] {
+            assert!(matches!(
+                run(&mut world, input),
+                Err(Error::NoPlaneForID('x'))
+            ));
+        }
+        assert_eq!(
+            world.plane('a').unwrap().destination,
+            Destination::Airport(1)
+        );
+
+        world.plane_mut('c').unwrap().controller = 1;
+        assert!(matches!(
+            run_as(&mut world, "a <> c", 0),
+            Err(Error::NotYourPlane('c', 0))
+        ));
+        assert_eq!(world.plane('c').unwrap().destination, Destination::Exit(0));
+    }
+
+    #[test]
+    fn test_command_altitude_relative() {
+        let mut world = world();
+        world.plane_mut('c').unwrap().height = 5;
+
+        run(&mut world, "a ^ c").unwrap();
+        assert_eq!(world.plane('a').unwrap().target_height, 6);
+        run(&mut world, "a v c").unwrap();
+        assert_eq!(world.plane('a').unwrap().target_height, 4);
+
+        world.plane_mut('c').unwrap().height = MAX_HEIGHT;
+        run(&mut world, "a^c").unwrap();
+        assert_eq!(world.plane('a').unwrap().target_height, MAX_HEIGHT);
+        world.plane_mut('c').unwrap().height = MIN_FLIGHT_HEIGHT;
+        run(&mut world, "avc").unwrap();
+        assert_eq!(world.plane('a').unwrap().target_height, MIN_FLIGHT_HEIGHT);
+
+        assert!(matches!(
+            run(&mut world, "a ^ x"),
+            Err(Error::NoPlaneForID('x'))
+        ));
+    }
+
+    #[test]
+    fn test_command_clear() {
+        let mut world = world();
+        assert!(!world.plane('a').unwrap().cleared);
+        run(&mut world, "a OK").unwrap();
+        assert!(world.plane('a').unwrap().cleared);
+        assert_eq!(
+            "c ok".parse::<Command>().unwrap(),
+            Command::Clear { plane: 'c' }
+        );
+        assert!(matches!(
+            run(&mut world, "x OK"),
+            Err(Error::NoPlaneForID('x'))
+        ));
+    }
+
+    #[test]
+    fn test_command_suggestions() {
+        let mut world = world();
+        world.insert_plane(Plane::new(
+            [15, 15].into(),
+            DirectionCardinal::North,
+            PlaneKind::Jet,
+            'b',
+            Destination::Exit(0),
+        ));
+
+        assert_eq!(suggestions(&world, ""), vec!["a", "b", "c"]);
+        assert_eq!(
+            suggestions(&world, "a "),
+            vec![
+                "a = b", "a = c", "a <> b", "a <> c", "a ^ b", "a ^ c", "a v b", "a v c", "a OK",
+                "a land"
+            ]
+        );
+        assert_eq!(suggestions(&world, "a <"), vec!["a <> b", "a <> c"]);
+        assert_eq!(suggestions(&world, "a ^"), vec!["a ^ b", "a ^ c"]);
+        assert_eq!(suggestions(&world, "a o"), vec!["a OK"]);
+        assert_eq!(suggestions(&world, "a la"), vec!["a land"]);
+        assert!(suggestions(&world, "a t").is_empty());
+        assert!(suggestions(&world, "x ").is_empty());
+        assert!(suggestions(&world, "a = c").is_empty());
+    }
+
+    #[test]
+    fn test_command_apply_as_controller() {
+        let mut world = world();
+        world.plane_mut('c').unwrap().controller = 2;
+
+        assert!(matches!(
+            run_as(&mut world, "c OK", 1),
+            Err(Error::NotYourPlane('c', 1))
+        ));
+        assert!(matches!(
+            run_as(&mut world, "c = a", 1),
+            Err(Error::NotYourPlane('c', 1))
+        ));
+        let c = world.plane('c').unwrap();
+        assert!(!c.cleared);
+        assert_eq!(c.destination, Destination::Airport(1));
+
+        world.plane_mut('a').unwrap().controller = 1;
+        run_as(&mut world, "a = c", 1).unwrap();
+        assert_eq!(
+            world.plane('a').unwrap().destination,
+            Destination::Airport(1)
+        );
+        run_as(&mut world, "c OK", 2).unwrap();
+        assert!(world.plane('c').unwrap().cleared);
+    }
+
+    #[test]
+    fn test_command_vector() {
+        let mut world = world();
+        let command: Command = "a v NE 9".parse().unwrap();
+        assert_eq!(
+            command,
+            Command::Vector {
+                plane: 'a',
+                heading: DirectionCardinal::NorthEast,
+                height: 9
+            }
+        );
+        assert_eq!(command.to_string().parse::<Command>().unwrap(), command);
+        command.apply(&mut world).unwrap();
+        let a = world.plane('a').unwrap();
+        assert_eq!(a.direction, DirectionCardinal::NorthEast);
+        assert_eq!(a.target_height, 9);
+
+        run(&mut world, "avs3").unwrap();
+        let a = world.plane('a').unwrap();
+        assert_eq!(a.direction, DirectionCardinal::South);
+        assert_eq!(a.target_height, 3);
+        assert!(matches!(
+            "a v c".parse::<Command>().unwrap(),
+            Command::AltitudeRelative { .. }
+        ));
+    }
+
+    #[test]
+    fn test_command_vector_to_the_ground() {
+        let mut world = world();
+        world
+            .place_tile(WorldTile::Airport(DirectionGrid::Up, 1), [10, 3])
+            .unwrap();
+
+        for input in ["c v e 0", "a v n 0"] {
+            assert!(
+                matches!(run(&mut world, input), Err(Error::NotOverAirport(_))),
+                "{input}"
+            );
+        }
+        let c = world.plane('c').unwrap();
+        assert_eq!(c.direction, DirectionCardinal::North);
+        assert_eq!(c.target_height, START_HEIGHT);
+
+        // seven moves north from [10, 10] at height 7 end on the airport
+        run(&mut world, "c v n 0").unwrap();
+        assert_eq!(world.plane('c').unwrap().target_height, 0);
+    }
+
+    #[test]
+    fn test_command_via() {
+        let mut world = world();
+        world
+            .place_tile(WorldTile::Beacon(0), [5, 3])
+            .unwrap()
+            .place_tile(WorldTile::Beacon(1), [8, 8])
+            .unwrap();
+        let command: Command = "a via b0 B1".parse().unwrap();
+        assert_eq!(
+            command,
+            Command::Via {
+                plane: 'a',
+                route: BeaconRoute::new(&[0, 1]).unwrap(),
+            }
+        );
+        assert_eq!(command.to_string(), "a via b0 b1");
+        command.apply(&mut world).unwrap();
+        let plane = world.plane('a').unwrap();
+        assert_eq!(plane.direct_to, Some([5, 3].into()));
+        assert_eq!(plane.direction, DirectionCardinal::SouthEast);
+        // the world has no exit 0, so the plane flies on straight after the last beacon
+        assert_eq!(
+            world.orders('a'),
+            [QueuedOrder {
+                trigger: Trigger::AtBeacon(0),
+                order: Order::DirectTo([8, 8].into()),
+            }]
+        );
+
+        assert!(matches!(
+            run(&mut world, "c via b0 b7"),
+            Err(Error::NoBeaconForID(7))
+        ));
+        assert!(world.orders('c').is_empty());
+        for bad in ["a via", "a via 0", "a via bx", "a via b0b"] {
+            assert!(
+                matches!(bad.parse::<Command>(), Err(Error::BadCommand(_))),
+                "{bad}"
+            );
+        }
+        assert!(matches!(
+            "a via b1 b2 b3 b4 b5 b6 b7 b8 b9".parse::<Command>(),
+            Err(Error::RouteTooLong(9, MAX_ROUTE_BEACONS))
+        ));
+    }
+
+    #[test]
+    fn test_command_vector_rejected_atomically() {
+        let mut world = world();
+        world.set_movement_mode(MovementMode::Hex);
+        assert!(matches!(
+            run(&mut world, "a v n 3"),
+            Err(Error::HeadingNotAllowed(DirectionCardinal::North))
+        ));
+        assert!(matches!(
+            run(&mut world, "a v e 12"),
+            Err(Error::HeightTooHigh(12, MAX_HEIGHT))
+        ));
+        assert!(matches!(
+            run(&mut world, "a v up 3"),
+            Err(Error::UnknownDirection(_))
+        ));
+        assert!(matches!(
+            run(&mut world, "a v 3"),
+            Err(Error::BadCommand(_))
+        ));
+        let a = world.plane('a').unwrap();
+        assert_eq!(a.direction, DirectionCardinal::North);
+        assert_eq!(a.target_height, a.height);
+    }
+
+    #[test]
+    fn test_command_vector_safety() {
+        let mut world = world();
+        assert!(run(&mut world, "a v nw 5").is_ok());
+        world.plane_mut('a').unwrap().direction = DirectionCardinal::North;
+        world.set_safety_checks(true);
+        assert!(matches!(
+            run(&mut world, "a v nw 7"),
+            Err(Error::UnsafeHeading('a', DirectionCardinal::NorthWest))
+        ));
+        let a = world.plane('a').unwrap();
+        assert_eq!(a.direction, DirectionCardinal::North);
+        assert_eq!(a.target_height, 5);
+        assert!(run(&mut world, "a v se 7").is_ok());
+    }
+
+    #[test]
+    fn test_command_land() {
+        let mut world = world();
+        world
+            .place_tile(WorldTile::Airport(DirectionGrid::Up, 1), [1, 1])
+            .unwrap()
+            .place_tile(WorldTile::Airport(DirectionGrid::Right, 2), [10, 10])
+            .unwrap();
+        assert_eq!(
+            "a LAND".parse::<Command>().unwrap(),
+            Command::Land { plane: 'a' }
+        );
+
+        run(&mut world, "a land").unwrap();
+        let plane = world.plane('a').unwrap();
+        assert_eq!(plane.destination, Destination::Airport(1));
+        assert_eq!(plane.target_height, 0);
+        assert!(plane.cleared);
+
+        // c flies north over a runway that points east
+        assert!(matches!(
+            run(&mut world, "c land"),
+            Err(Error::NotLinedUp('c', 2, DirectionCardinal::East))
+        ));
+        let plane = world.plane('c').unwrap();
+        assert_eq!(plane.destination, Destination::Airport(1));
+        assert!(!plane.cleared);
+
+        world.plane_mut('c').unwrap().pos = [11, 10].into();
+        assert!(matches!(
+            run(&mut world, "c land"),
+            Err(Error::NotOverAirport('c'))
+        ));
+    }
+
+    #[test]
+    fn test_command_land_touches_down() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 0, 0)
+            .unwrap()
+            .place_tile(WorldTile::Airport(DirectionGrid::Up, 0), [5, 10])
+            .unwrap();
+        let mut plane = Plane::new(
+            [5, 10].into(),
+            DirectionCardinal::North,
+            PlaneKind::Small,
+            'a',
+            Destination::Exit(0),
+        );
+        plane.height = 3;
+        world.insert_plane(plane);
+        let id = world.resolve_plane_id('a').unwrap();
+        run(&mut world, &format!("{id} land")).unwrap();
+
+        let mut ticks = 0;
+        while world.stats().landings == 0 {
+            assert_eq!(world.tick_planes(), State::Ongoing);
+            if let Some(plane) = world.plane(id) {
+                assert_eq!(plane.pos, [5, 10].into(), "holds over the runway");
+            }
+            ticks += 1;
+            assert!(ticks < 20, "the plane never touched down");
+        }
+        assert!(world.plane(id).is_none());
+        assert_eq!(world.stats().crashes, 0);
+    }
+
+    #[test]
+    fn test_command_help_covers_every_command() {
+        // a new kind of command breaks this match, it needs an example in EXAMPLES too
+        fn kind(command: Command) -> usize {
+            match command {
+                Command::CopyDestination { .. } => 0,
+                Command::SwapDestinations { .. } => 1,
+                Command::AltitudeRelative { .. } => 2,
+                Command::Clear { .. } => 3,
+                Command::Vector { .. } => 4,
+                Command::Via { .. } => 5,
+                Command::Land { .. } => 6,
+            }
+        }
+        let mut covered = [false; 7];
+        for command in EXAMPLES {
+            covered[kind(command)] = true;
+        }
+        assert_eq!(covered, [true; 7]);
+
+        let help = help();
+        assert_eq!(help.len(), EXAMPLES.len() + 1);
+        for (line, command) in help.iter().zip(EXAMPLES) {
+            let example = command.to_string();
+            assert_eq!(example.parse::<Command>().unwrap(), command);
+            assert!(line.starts_with(&example), "{line}");
+            assert!(line.ends_with(command.description()), "{line}");
+        }
+        let spawn = SPAWN_EXAMPLE.to_string();
+        assert_eq!(spawn.parse::<SpawnRequest>().unwrap(), SPAWN_EXAMPLE);
+        assert!(help.last().unwrap().starts_with(&spawn));
+    }
+
+    #[test]
+    fn test_command_parse_spawn_request() {
+        assert_eq!(
+            "+0".parse::<SpawnRequest>().unwrap(),
+            SpawnRequest {
+                exit: 0,
+                kind: PlaneKind::Small,
+                destination: None,
+            }
+        );
+        assert_eq!(
+            " + 2 E1 JET".parse::<SpawnRequest>().unwrap(),
+            SpawnRequest {
+                exit: 2,
+                kind: PlaneKind::Jet,
+                destination: Some(Destination::Exit(1)),
+            }
+        );
+        for bad in ["0 jet", "+", "+ x", "+ 0 boat", "+ 0 e", "+ 0 ax"] {
+            assert!(
+                matches!(bad.parse::<SpawnRequest>(), Err(Error::BadCommand(_))),
+                "{bad}"
+            );
+        }
+    }
+}
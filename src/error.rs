@@ -1,15 +1,110 @@
 use thiserror::Error;
 
+use crate::{
+    plane::Destination,
+    world::{DirectionCardinal, Pos},
+};
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Plane {0} tried to go to a bad position")]
     PlaneNextPosBad(char),
+    #[error("Plane {0} has no fuel left")]
+    PlaneOutOfFuel(char),
     #[error("Exit position is out of bounds: not {0} < {1}")]
     ExitPosOutOfBounds(usize, usize),
+    #[error("A world of {0}x{1} tiles is too small, it needs at least {2} in each direction")]
+    WorldTooSmall(usize, usize, usize),
     #[error("Position is out of bounds: not {0} < {1}")]
     PosOutOfBounds(usize, usize),
+    #[error("There already is something at {0:?}")]
+    CellOccupied(Pos),
+    #[error("No Plane exists for ID {0}")]
+    NoPlaneForID(char),
+    #[error("Plane {0} is not under the control of controller {1}")]
+    NotYourPlane(char, u8),
+    #[error("Start the command with the number of your controller, 0 to {0}")]
+    NoController(u8),
     #[error("No Exit exists for ID {0}")]
     NoExitForID(u8),
+    #[error("No Beacon exists for ID {0}")]
+    NoBeaconForID(u8),
+    #[error("A route can lead over at most {1} beacons, not {0}")]
+    RouteTooLong(usize, usize),
+    #[error("No Airport exists for ID {0}")]
+    NoAirportForID(u8),
+    #[error("Plane {0} is not over an airport")]
+    NotOverAirport(char),
+    #[error("Plane {0} is not lined up with the runway of airport {1}, it has to come around and approach heading {2}")]
+    NotLinedUp(char, u8, DirectionCardinal),
+    #[error("All {0} plane ids are taken by planes in the air")]
+    NoFreePlaneID(usize),
+    #[error("Exit {0} is occupied by another plane")]
+    ExitOccupied(u8),
+    #[error("There is no exit planes could come in through")]
+    NoSpawnPoints,
+    #[error("No destination exists for a new plane")]
+    NoDestinations,
+    #[error("Planes can only be let in by hand in practice mode")]
+    NotPractice,
+    #[error("Planes coming in through exit {0} can not fly to {1}")]
+    BadDestination(u8, Destination),
+    #[error("Destination {0} can not be reached from any exit")]
+    UnreachableDestination(u8),
     #[error("Negative Positions are not allowed: {0:?}")]
     PosFromSigned((i32, i32)),
+    #[error("Planes can not fly at height {0}, the maximum is {1}")]
+    HeightTooHigh(u8, u8),
+    #[error("Could not understand the command: {0}")]
+    BadCommand(String),
+    #[error("Plane {0} would crash flying {1}")]
+    UnsafeHeading(char, DirectionCardinal),
+    #[error("Planes can not fly {0} here")]
+    HeadingNotAllowed(DirectionCardinal),
+    #[error("Unknown direction: {0}")]
+    UnknownDirection(String),
+    #[error("Direction {0} does not point along the grid")]
+    NotAGridDirection(DirectionCardinal),
+    #[error("Wind looks like `ne 2`, a direction and a strength from 1 to 3, not: {0}")]
+    BadWind(String),
+    #[error("Unknown fuel unit: {0}")]
+    UnknownFuelUnit(String),
+    #[error("Unknown color mode: {0}, use auto, always or never")]
+    UnknownColorMode(String),
+    #[error("Unknown color: {0}")]
+    UnknownColor(String),
+    #[error("Unknown theme setting: {0}")]
+    UnknownThemeKey(String),
+    #[error("Unknown theme: {0}")]
+    UnknownTheme(String),
+    #[error("Theme lines look like `name = color`, not: {0}")]
+    BadThemeLine(String),
+    #[error(
+        "Score weights look like `exit = 2`, a key and a number that is not negative, not: {0}"
+    )]
+    BadScoreWeight(String),
+    #[error("Unknown difficulty: {0}")]
+    UnknownDifficulty(String),
+    #[error("The level file does not say which version it is")]
+    LevelVersionMissing,
+    #[error("The level file is version {0}, only versions 1 to {1} are supported")]
+    LevelVersionUnsupported(i64, u32),
+    #[error("Could not read the level file: {0}")]
+    BadLevelFile(String),
+    #[error("The level file has more than one exit with ID {0}")]
+    DuplicateExitID(u8),
+    #[error("The level file has more than one beacon with ID {0}")]
+    DuplicateBeaconID(u8),
+    #[error("The level file has more than one airport with ID {0}")]
+    DuplicateAirportID(u8),
+    #[error("Airport {0} at {1:?} is on the wall, planes could not land there")]
+    AirportOnWall(u8, Pos),
+    #[error("The saved game is version {0}, only version {1} can be resumed")]
+    SaveVersionUnsupported(i64, u32),
+    #[error("Could not read the saved game: {0}")]
+    BadSave(String),
+    #[error("Tick {0} was not recorded, the replay goes from tick {1} to {2}")]
+    TickNotRecorded(u64, u64, u64),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
 }
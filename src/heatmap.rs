@@ -0,0 +1,206 @@
+//! How often planes were on each tile, to find the busy parts of a level
+//!
+//! A [`Heatmap`] counts the planes on every tile once per tick. [`simulate`] fills one without
+//! a player, by letting the [autopilot](crate::autopilot) fly a game for a while.
+
+use std::fmt::Display;
+
+use crate::{
+    autopilot,
+    session::GameSession,
+    world::{Pos, World},
+};
+
+/// Characters for the heat of a tile in the text rendering, from cold to hot
+const HEAT_CHARS: [char; 10] = ['.', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Heatmap {
+    width: usize,
+    height: usize,
+    /// Planes counted on each tile, by row
+    counts: Vec<Vec<u32>>,
+    /// How often [`Heatmap::record`] was called
+    ticks: u64,
+}
+
+impl Heatmap {
+    /// An empty heatmap for a world of the size of `world`
+    pub fn new(world: &World) -> Self {
+        Self {
+            width: world.width(),
+            height: world.height(),
+            counts: vec![vec![0; world.width()]; world.height()],
+            ticks: 0,
+        }
+    }
+
+    /// Count the planes that are in `world` right now
+    pub fn record(&mut self, world: &World) {
+        for plane in world.planes() {
+            if let Some(count) = self
+                .counts
+                .get_mut(plane.pos.y)
+                .and_then(|row| row.get_mut(plane.pos.x))
+            {
+                *count += 1;
+            }
+        }
+        self.ticks += 1;
+    }
+
+    /// How many planes were counted on the tile at `pos`
+    pub fn count(&self, pos: Pos) -> u32 {
+        self.counts
+            .get(pos.y)
+            .and_then(|row| row.get(pos.x))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The count of the busiest tile
+    pub fn max(&self) -> u32 {
+        self.counts
+            .iter()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// How hot the tile at `pos` is compared to the busiest tile, from 0 to 1
+    pub fn heat(&self, pos: Pos) -> f64 {
+        match self.max() {
+            0 => 0.0,
+            max => self.count(pos) as f64 / max as f64,
+        }
+    }
+
+    /// The `n` busiest tiles with their counts, the busiest first
+    ///
+    /// Tiles no plane was on are left out.
+    pub fn hotspots(&self, n: usize) -> Vec<(Pos, u32)> {
+        let mut tiles: Vec<(Pos, u32)> = self
+            .tiles()
+            .map(|pos| (pos, self.count(pos)))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        tiles.sort_by_key(|(pos, count)| (std::cmp::Reverse(*count), pos.y, pos.x));
+        tiles.truncate(n);
+        tiles
+    }
+
+    /// Every tile of the map, row by row
+    pub fn tiles(&self) -> impl Iterator<Item = Pos> + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| Pos { x, y }))
+    }
+}
+
+/// The heatmap as text, two characters per tile like the map, with `.` for tiles no plane was
+/// on and `1` to `9` for the heat of the others
+impl Display for Heatmap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let pos = Pos { x, y };
+                let level = match self.count(pos) {
+                    0 => 0,
+                    _ => ((self.heat(pos) * 9.0).ceil() as usize).clamp(1, 9),
+                };
+                write!(f, "{} ", HEAT_CHARS[level])?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Let the autopilot play `session` for up to `ticks` ticks and count where the planes were
+///
+/// The run stops early when the game is over.
+pub fn simulate(session: &mut GameSession, ticks: u64) -> Heatmap {
+    let mut heatmap = Heatmap::new(session.level().world());
+    for _ in 0..ticks {
+        if session.is_over() {
+            break;
+        }
+        autopilot::fly(session.level_mut().world_mut());
+        session.step();
+        heatmap.record(session.level().world());
+    }
+    heatmap
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        level::Level,
+        plane::{Destination, Plane},
+        world::{DirectionCardinal, DirectionGrid, PlaneKind},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_heatmap_counts_planes_per_tick() {
+        let mut world = World::new(10, 10);
+        world
+            .place_exit(DirectionGrid::Right, DirectionCardinal::West, 2, 0)
+            .unwrap();
+        world.insert_plane(Plane::new(
+            [2, 2].into(),
+            DirectionCardinal::East,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        ));
+        let mut held = Plane::new(
+            [5, 7].into(),
+            DirectionCardinal::North,
+            PlaneKind::Jet,
+            'b',
+            Destination::Exit(0),
+        );
+        held.holding = true;
+        world.insert_plane(held);
+
+        let mut heatmap = Heatmap::new(&world);
+        for _ in 0..4 {
+            heatmap.record(&world);
+            world.tick_planes();
+        }
+        assert_eq!(heatmap.ticks(), 4);
+        for x in 2..6 {
+            assert_eq!(heatmap.count([x, 2].into()), 1, "x = {x}");
+        }
+        assert_eq!(heatmap.count([6, 2].into()), 0);
+        assert_eq!(heatmap.count([5, 7].into()), 4);
+        assert_eq!(heatmap.max(), 4);
+        assert_eq!(
+            heatmap.hotspots(2),
+            vec![([5, 7].into(), 4), ([2, 2].into(), 1)]
+        );
+        assert_eq!(
+            heatmap.tiles().map(|pos| heatmap.count(pos)).sum::<u32>(),
+            8
+        );
+
+        let rendered = heatmap.to_string();
+        let rows: Vec<&str> = rendered.lines().collect();
+        assert_eq!(rows[2], ". . 3 3 3 3 . . . . ");
+        assert_eq!(rows[7], ". . . . . 9 . . . . ");
+    }
+
+    #[test]
+    fn test_heatmap_simulate() {
+        let mut session = GameSession::new(Level::builtin());
+        session.level_mut().set_seed(7);
+        let heatmap = simulate(&mut session, 50);
+        assert_eq!(heatmap.ticks(), session.tick());
+        assert!(heatmap.max() > 0);
+    }
+}
@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use crate::{session::GameSession, wind::Wind};
+
+/// Separator between the fields of the HUD line
+const SEPARATOR: &str = " | ";
+
+/// Segments of the traffic meter
+const METER_WIDTH: usize = 5;
+
+/// A small bar that fills up with `percent`, like `[###--]`
+fn traffic_meter(percent: u8) -> String {
+    let filled = (percent.min(100) as usize * METER_WIDTH + 50) / 100;
+    format!(
+        "[{}{}]",
+        "#".repeat(filled),
+        "-".repeat(METER_WIDTH - filled)
+    )
+}
+
+/// The numbers shown in the single line overview above the map
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Hud {
+    pub score: u64,
+    /// Planes in the air right now
+    pub airborne: usize,
+    /// How full the airspace is in percent, see [`Level::congestion`](crate::level::Level::congestion)
+    pub traffic: u8,
+    /// Planes that run out of fuel next tick
+    pub fuel_critical: usize,
+    /// None in calm air
+    pub wind: Option<Wind>,
+    pub tick: u64,
+    /// Time between two ticks, None while the game is paused
+    pub tick_rate: Option<Duration>,
+}
+
+impl Hud {
+    pub fn new(session: &GameSession, tick_rate: Option<Duration>) -> Self {
+        let world = session.level().world();
+        Self {
+            score: session.level().score(),
+            airborne: world.planes().count(),
+            traffic: (session.level().congestion() * 100.0).round() as u8,
+            fuel_critical: world.planes().filter(|p| p.fuel_critical()).count(),
+            wind: world.wind(),
+            tick: session.tick(),
+            tick_rate,
+        }
+    }
+
+    /// Render the HUD in at most `width` characters
+    ///
+    /// The fields are ordered by importance. If the line does not fit, the least important
+    /// fields are left out, and if not even the first fits it is cut off.
+    pub fn render(&self, width: usize) -> String {
+        let speed = match self.tick_rate {
+            Some(rate) => format!("{}ms/tick", rate.as_millis()),
+            None => "paused".to_string(),
+        };
+        let mut fields = vec![
+            format!("Score {}", self.score),
+            format!("Planes {}", self.airborne),
+            format!("Traffic {}", traffic_meter(self.traffic)),
+            format!("Low fuel {}", self.fuel_critical),
+        ];
+        if let Some(wind) = self.wind {
+            fields.push(format!("Wind {wind}"));
+        }
+        fields.extend([format!("Tick {}", self.tick), speed]);
+
+        let mut line = String::new();
+        for field in fields {
+            let needed = if line.is_empty() {
+                field.len()
+            } else {
+                line.len() + SEPARATOR.len() + field.len()
+            };
+            if needed > width {
+                break;
+            }
+            if !line.is_empty() {
+                line.push_str(SEPARATOR);
+            }
+            line.push_str(&field);
+        }
+        if line.is_empty() {
+            line = format!("Score {}", self.score)
+                .chars()
+                .take(width)
+                .collect();
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hud() -> Hud {
+        Hud {
+            score: 12,
+            airborne: 4,
+            traffic: 40,
+            fuel_critical: 1,
+            wind: None,
+            tick: 345,
+            tick_rate: Some(Duration::from_millis(500)),
+        }
+    }
+
+    #[test]
+    fn test_hud_render_widths() {
+        let hud = hud();
+        assert_eq!(
+            hud.render(80),
+            "Score 12 | Planes 4 | Traffic [##---] | Low fuel 1 | Tick 345 | 500ms/tick"
+        );
+        assert_eq!(
+            hud.render(56),
+            "Score 12 | Planes 4 | Traffic [##---] | Low fuel 1"
+        );
+        assert_eq!(hud.render(40), "Score 12 | Planes 4 | Traffic [##---]");
+        assert_eq!(hud.render(12), "Score 12");
+        assert_eq!(hud.render(5), "Score");
+        assert_eq!(hud.render(0), "");
+
+        let paused = Hud {
+            tick_rate: None,
+            ..hud
+        };
+        assert!(paused.render(80).ends_with("| paused"));
+
+        let windy = Hud {
+            wind: Some(Wind::new(crate::world::DirectionCardinal::SouthWest, 3)),
+            ..hud
+        };
+        assert_eq!(
+            windy.render(80),
+            "Score 12 | Planes 4 | Traffic [##---] | Low fuel 1 | Wind ↙3 | Tick 345"
+        );
+    }
+
+    #[test]
+    fn test_hud_traffic_meter() {
+        assert_eq!(traffic_meter(0), "[-----]");
+        assert_eq!(traffic_meter(50), "[###--]");
+        assert_eq!(traffic_meter(100), "[#####]");
+        assert_eq!(traffic_meter(255), "[#####]");
+    }
+}
@@ -1,14 +1,26 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
 use crate::{
     error::Error,
     world::{DirectionCardinal, DirectionGrid, World},
 };
 
-use super::Level;
+use super::{scoring::ScoreWeights, Level};
 
 pub const X: usize = 20;
 pub const Y: usize = 20;
+/// The seed of [`Level::builtin`], so the same planes come in every time
+pub const DEFAULT_SEED: u64 = 1337;
 
 impl Level {
+    /// The [builtin](Level::builtin) level, with the planes that come in picked from `seed`
+    pub fn with_seed(seed: u64) -> Self {
+        let mut level = Self::builtin();
+        level.set_seed(seed);
+        level
+    }
+
     pub fn builtin() -> Self {
         let mut world = World::new(X, Y);
 
@@ -27,9 +39,11 @@ impl Level {
             world.place_exit(DirectionGrid::Up, DirectionCardinal::South, 12, 0)?;
             world.place_exit(DirectionGrid::Right, DirectionCardinal::SouthWest, 2, 1)?;
             world.place_exit(DirectionGrid::Right, DirectionCardinal::West, 10, 2)?;
-            world.place_exit(DirectionGrid::Left, DirectionCardinal::East, 10, 3)?;
+            world.place_exit_at_height(DirectionGrid::Left, DirectionCardinal::East, 10, 3, 5)?;
             world.place_exit(DirectionGrid::Down, DirectionCardinal::North, 12, 4)?;
 
+            world.validate()?;
+
             Ok(())
         }
         place_stuff(&mut world).expect("could not place tiles in world");
@@ -37,7 +51,17 @@ impl Level {
         Level {
             world,
             name: "default".to_string(),
-            seed: rand::random(),
+            seed: DEFAULT_SEED,
+            rng: ChaCha12Rng::seed_from_u64(DEFAULT_SEED),
+            difficulty: Default::default(),
+            spawned: Vec::new(),
+            spawning_paused: false,
+            spawn_deferred: false,
+            spawn_queue: Vec::new(),
+            commands: 0,
+            ticks: 0,
+            weights: ScoreWeights::default(),
+            spawn_policy: Default::default(),
         }
     }
 }
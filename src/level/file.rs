@@ -0,0 +1,496 @@
+//! Levels stored as TOML files
+//!
+//! Every level file has a `version`. Files of older versions are migrated step by step to
+//! [`LEVEL_VERSION`] before they are read, so levels keep loading when the format changes.
+//!
+//! Only the layout of the world is stored: its size, the tiles that are not empty and the exits,
+//! and the [spawn schedule](SpawnPolicy::Schedule) if the level has one.
+//! Settings like [mandatory routes](World::set_routes_mandatory) are not part of the file, and
+//! neither is the seed of the random numbers.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+use tracing::info;
+
+use crate::{
+    error::Error,
+    world::{AltitudeRestriction, DirectionCardinal, DirectionGrid, Pos, World, WorldTile},
+};
+
+use super::{scoring::ScoreWeights, Difficulty, Level, ScheduledSpawn, SpawnPolicy};
+
+/// The version of level files written by this version of the game
+pub const LEVEL_VERSION: u32 = 2;
+
+/// Changes a level file of version `n + 1` into version `n + 2`, in the table as it was read
+const MIGRATIONS: [fn(&mut Table); LEVEL_VERSION as usize - 1] = [migrate_v1];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LevelFile {
+    version: u32,
+    name: String,
+    difficulty: Difficulty,
+    width: usize,
+    height: usize,
+    #[serde(default)]
+    tiles: Vec<TileEntry>,
+    #[serde(default)]
+    exits: Vec<ExitEntry>,
+    /// Planes come in at random if there is no schedule
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    schedule: Vec<ScheduleEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TileEntry {
+    x: usize,
+    y: usize,
+    #[serde(flatten)]
+    tile: Tile,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum Tile {
+    Route,
+    Beacon {
+        id: u8,
+        /// Height planes have to cross the beacon at
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        restriction: Option<AltitudeRestriction>,
+    },
+    Airport {
+        id: u8,
+        runway: DirectionGrid,
+    },
+    SlowZone,
+    Obstacle,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExitEntry {
+    id: u8,
+    wall: DirectionGrid,
+    pos: usize,
+    heading: DirectionCardinal,
+    /// Added in version 2
+    spawn_weight: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_height: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lane_separation: Option<u8>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ScheduleEntry {
+    tick: u64,
+    #[serde(flatten)]
+    spawn: ScheduledSpawn,
+}
+
+/// Version 1 had no spawn weights, every exit was used equally often
+fn migrate_v1(table: &mut Table) {
+    if let Some(Value::Array(exits)) = table.get_mut("exits") {
+        for exit in exits.iter_mut().filter_map(Value::as_table_mut) {
+            exit.entry("spawn_weight").or_insert(Value::Integer(1));
+        }
+    }
+}
+
+/// Bring a level file of any supported version to [`LEVEL_VERSION`]
+fn migrate(table: &mut Table) -> Result<(), Error> {
+    let version = match table.get("version") {
+        Some(Value::Integer(version)) => *version,
+        Some(other) => {
+            return Err(Error::BadLevelFile(format!(
+                "the version is not a number: {other}"
+            )))
+        }
+        None => return Err(Error::LevelVersionMissing),
+    };
+    if version < 1 || version > LEVEL_VERSION as i64 {
+        return Err(Error::LevelVersionUnsupported(version, LEVEL_VERSION));
+    }
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        info!(from = from + 1, "migrating level file");
+        migration(table);
+    }
+    table.insert("version".to_string(), Value::Integer(LEVEL_VERSION as i64));
+    Ok(())
+}
+
+impl Level {
+    /// Read a level from the content of a level file
+    ///
+    /// Files of older versions are migrated first. A file without a version or with a version
+    /// newer than [`LEVEL_VERSION`] is rejected.
+    ///
+    /// Two exits, beacons or airports with the same id are an error, and so is an airport on the
+    /// wall. Names with tabs or line breaks are rejected, they would break the
+    /// [high score file](crate::scores::HighScores).
+    ///
+    /// The level gets a random seed, call [`Level::set_seed`] afterwards to play it the same way
+    /// as before, like a [saved game](crate::session::GameSession::load) does.
+    pub fn parse(content: &str) -> Result<Self, Error> {
+        let table: Table = content
+            .parse()
+            .map_err(|e: toml::de::Error| Error::BadLevelFile(e.message().to_string()))?;
+        Self::from_table(table)
+    }
+
+    /// Like [`Level::parse`], for a level file that is already read as TOML, for example as
+    /// part of a saved game
+    ///
+    /// The seed is random here as well, see [`Level::parse`].
+    pub fn from_table(mut table: Table) -> Result<Self, Error> {
+        migrate(&mut table)?;
+        let file = LevelFile::deserialize(table)
+            .map_err(|e| Error::BadLevelFile(e.message().to_string()))?;
+        if file.name.contains(['\t', '\n', '\r']) {
+            return Err(Error::BadLevelFile(format!(
+                "the name can not contain tabs or line breaks: {:?}",
+                file.name
+            )));
+        }
+
+        let mut world = World::try_new(file.width, file.height)?;
+        let mut restrictions = Vec::new();
+        let (mut beacons, mut airports) = (BTreeSet::new(), BTreeSet::new());
+        for entry in file.tiles {
+            let tile = match entry.tile {
+                Tile::Route => WorldTile::Route,
+                Tile::Beacon { id, restriction } => {
+                    if !beacons.insert(id) {
+                        return Err(Error::DuplicateBeaconID(id));
+                    }
+                    restrictions.extend(restriction.map(|r| (id, r)));
+                    WorldTile::Beacon(id)
+                }
+                Tile::Airport { id, runway } => {
+                    if !airports.insert(id) {
+                        return Err(Error::DuplicateAirportID(id));
+                    }
+                    let on_wall = entry.x == 0
+                        || entry.y == 0
+                        || entry.x + 1 == file.width
+                        || entry.y + 1 == file.height;
+                    if on_wall {
+                        return Err(Error::AirportOnWall(id, [entry.x, entry.y].into()));
+                    }
+                    WorldTile::Airport(runway, id)
+                }
+                Tile::SlowZone => WorldTile::SlowZone,
+                Tile::Obstacle => WorldTile::Obstacle,
+            };
+            world.place_tile(tile, [entry.x, entry.y])?;
+        }
+        for (id, restriction) in restrictions {
+            world.restrict_beacon(id, restriction)?;
+        }
+        let mut exits = BTreeSet::new();
+        for exit in file.exits {
+            if !exits.insert(exit.id) {
+                return Err(Error::DuplicateExitID(exit.id));
+            }
+            match exit.start_height {
+                Some(height) => world.place_exit_at_height(
+                    exit.wall,
+                    exit.heading,
+                    exit.pos,
+                    exit.id,
+                    height,
+                )?,
+                None => world.place_exit(exit.wall, exit.heading, exit.pos, exit.id)?,
+            };
+            world.set_exit_spawn_weight(exit.id, exit.spawn_weight)?;
+            if let Some(separation) = exit.lane_separation {
+                world.set_exit_lanes(exit.id, separation)?;
+            }
+        }
+        world.validate()?;
+        let spawn_policy = if file.schedule.is_empty() {
+            SpawnPolicy::Random
+        } else {
+            SpawnPolicy::Schedule(
+                file.schedule
+                    .into_iter()
+                    .map(|entry| (entry.tick, entry.spawn))
+                    .collect::<BTreeMap<_, _>>(),
+            )
+        };
+
+        // not part of the file, callers that replay a game set it afterwards
+        let seed = rand::random();
+        Ok(Level {
+            world,
+            name: file.name,
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            difficulty: file.difficulty,
+            spawned: Vec::new(),
+            spawning_paused: false,
+            spawn_deferred: false,
+            spawn_queue: Vec::new(),
+            commands: 0,
+            ticks: 0,
+            weights: ScoreWeights::default(),
+            spawn_policy,
+        })
+    }
+
+    /// Read a level from the file at `path`, see [`Level::parse`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// The level as the content of a level file of [`LEVEL_VERSION`]
+    pub fn to_file_string(&self) -> String {
+        toml::to_string(&self.to_file()).expect("level files can always be written as TOML")
+    }
+
+    /// The level as a level file read as TOML, see [`Level::from_table`]
+    pub fn to_table(&self) -> Table {
+        Table::try_from(self.to_file()).expect("level files can always be written as TOML")
+    }
+
+    fn to_file(&self) -> LevelFile {
+        let world = &self.world;
+        let mut tiles = Vec::new();
+        for y in 0..world.height() {
+            for x in 0..world.width() {
+                let tile = match world.tile(Pos { x, y }) {
+                    Some(WorldTile::Route) => Tile::Route,
+                    Some(WorldTile::Beacon(id)) => Tile::Beacon {
+                        id,
+                        restriction: world.beacon_restriction(id),
+                    },
+                    Some(WorldTile::Airport(runway, id)) => Tile::Airport { id, runway },
+                    Some(WorldTile::SlowZone) => Tile::SlowZone,
+                    Some(WorldTile::Obstacle) => Tile::Obstacle,
+                    Some(WorldTile::Empty) | None => continue,
+                };
+                tiles.push(TileEntry { x, y, tile });
+            }
+        }
+        let mut exits: Vec<ExitEntry> = world
+            .exits()
+            .map(|(id, exit)| ExitEntry {
+                id,
+                wall: exit.wall_direction,
+                pos: exit.wall_pos,
+                heading: exit.plane_out_direction,
+                spawn_weight: exit.spawn_weight,
+                start_height: exit.start_height,
+                lane_separation: exit.lane_separation,
+            })
+            .collect();
+        exits.sort_by_key(|exit| exit.id);
+        let schedule = match &self.spawn_policy {
+            SpawnPolicy::Random | SpawnPolicy::Manual => Vec::new(),
+            SpawnPolicy::Schedule(schedule) => schedule
+                .iter()
+                .map(|(tick, spawn)| ScheduleEntry {
+                    tick: *tick,
+                    spawn: *spawn,
+                })
+                .collect(),
+        };
+        LevelFile {
+            version: LEVEL_VERSION,
+            name: self.name.clone(),
+            difficulty: self.difficulty,
+            width: world.width(),
+            height: world.height(),
+            tiles,
+            exits,
+            schedule,
+        }
+    }
+
+    /// Write the level to a level file at `path`
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        Ok(std::fs::write(path, self.to_file_string())?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const V1: &str = r#"
+version = 1
+name = "old"
+difficulty = "hard"
+width = 10
+height = 8
+
+[[tiles]]
+kind = "airport"
+x = 4
+y = 4
+id = 0
+runway = "right"
+
+[[tiles]]
+kind = "beacon"
+x = 6
+y = 4
+id = 0
+
+[[exits]]
+id = 0
+wall = "up"
+pos = 4
+heading = "south"
+
+[[exits]]
+id = 1
+wall = "left"
+pos = 4
+heading = "east"
+start_height = 5
+"#;
+
+    #[test]
+    fn test_level_file_migrates_v1() {
+        let level = Level::parse(V1).unwrap();
+        assert_eq!(level.get_name(), "old");
+        assert_eq!(level.difficulty(), Difficulty::Hard);
+        let world = level.world();
+        assert_eq!((world.width(), world.height()), (10, 8));
+        assert_eq!(
+            world.tile([4, 4].into()),
+            Some(WorldTile::Airport(DirectionGrid::Right, 0))
+        );
+        assert_eq!(world.beacon_pos(0), Some([6, 4].into()));
+        let exit = world.exit(1).unwrap();
+        assert_eq!(exit.spawn_weight, 1);
+        assert_eq!(exit.start_height, Some(5));
+        assert_eq!(exit.wall_direction, DirectionGrid::Left);
+
+        let saved = level.to_file_string();
+        assert!(saved.contains(&format!("version = {LEVEL_VERSION}")));
+        let reloaded = Level::parse(&saved).unwrap();
+        assert_eq!(reloaded.render(), level.render());
+        assert_eq!(reloaded.to_file_string(), saved);
+    }
+
+    #[test]
+    fn test_level_file_round_trips_builtin() {
+        let level = Level::builtin();
+        let reloaded = Level::parse(&level.to_file_string()).unwrap();
+        assert_eq!(reloaded.render(), level.render());
+        assert_eq!(reloaded.world().exit(3).unwrap().start_height, Some(5));
+        assert_eq!(reloaded.spawn_policy(), &SpawnPolicy::Random);
+    }
+
+    #[test]
+    fn test_level_file_spawn_schedule() {
+        let content = format!(
+            "{}\n[[schedule]]\ntick = 7\nexit = 1\nkind = \"jet\"\ndestination = {{ airport = 0 }}\n",
+            V1
+        );
+        let level = Level::parse(&content).unwrap();
+        let expected = ScheduledSpawn {
+            exit: 1,
+            kind: crate::world::PlaneKind::Jet,
+            destination: crate::plane::Destination::Airport(0),
+        };
+        assert_eq!(
+            level.spawn_policy(),
+            &SpawnPolicy::Schedule(BTreeMap::from([(7, expected)]))
+        );
+        let reloaded = Level::parse(&level.to_file_string()).unwrap();
+        assert_eq!(reloaded.spawn_policy(), level.spawn_policy());
+    }
+
+    #[test]
+    fn test_level_file_beacon_restriction() {
+        let content = V1.replace(
+            "y = 4\nid = 0\n\n[[exits]]",
+            "y = 4\nid = 0\nrestriction = { at-or-above = 5 }\n\n[[exits]]",
+        );
+        let level = Level::parse(&content).unwrap();
+        assert_eq!(
+            level.world().beacon_restriction(0),
+            Some(AltitudeRestriction::AtOrAbove(5))
+        );
+        let reloaded = Level::parse(&level.to_file_string()).unwrap();
+        assert_eq!(
+            reloaded.world().beacon_restriction(0),
+            Some(AltitudeRestriction::AtOrAbove(5))
+        );
+        assert_eq!(
+            Level::parse(V1).unwrap().world().beacon_restriction(0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_level_file_rejects_bad_layouts() {
+        let twice = |what: &str| format!("{V1}\n{what}");
+        let exit = twice("[[exits]]\nid = 1\nwall = \"down\"\npos = 2\nheading = \"north\"\n");
+        assert!(matches!(
+            Level::parse(&exit),
+            Err(Error::DuplicateExitID(1))
+        ));
+        let beacon = twice("[[tiles]]\nkind = \"beacon\"\nx = 2\ny = 2\nid = 0\n");
+        assert!(matches!(
+            Level::parse(&beacon),
+            Err(Error::DuplicateBeaconID(0))
+        ));
+        let airport =
+            twice("[[tiles]]\nkind = \"airport\"\nx = 2\ny = 2\nid = 0\nrunway = \"up\"\n");
+        assert!(matches!(
+            Level::parse(&airport),
+            Err(Error::DuplicateAirportID(0))
+        ));
+
+        let on_wall = V1.replace(
+            "x = 4\ny = 4\nid = 0\nrunway",
+            "x = 9\ny = 4\nid = 0\nrunway",
+        );
+        assert!(matches!(
+            Level::parse(&on_wall),
+            Err(Error::AirportOnWall(0, pos)) if pos == [9, 4].into()
+        ));
+        let out_of_bounds = V1.replace("wall = \"up\"\npos = 4", "wall = \"up\"\npos = 10");
+        assert!(matches!(
+            Level::parse(&out_of_bounds),
+            Err(Error::ExitPosOutOfBounds(10, 10))
+        ));
+        for name in ["\"a\\tb\"", "\"\"\"a\nb\"\"\""] {
+            let bad_name = V1.replace("name = \"old\"", &format!("name = {name}"));
+            assert!(
+                matches!(Level::parse(&bad_name), Err(Error::BadLevelFile(_))),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_level_file_rejects_bad_versions() {
+        let future = V1.replace("version = 1", "version = 99");
+        assert!(matches!(
+            Level::parse(&future),
+            Err(Error::LevelVersionUnsupported(99, LEVEL_VERSION))
+        ));
+        let unversioned = V1.replace("version = 1", "");
+        assert!(matches!(
+            Level::parse(&unversioned),
+            Err(Error::LevelVersionMissing)
+        ));
+        let current = V1.replace("version = 1", &format!("version = {LEVEL_VERSION}"));
+        assert!(matches!(
+            Level::parse(&current),
+            Err(Error::BadLevelFile(e)) if e.contains("spawn_weight")
+        ));
+    }
+}
@@ -1,39 +1,396 @@
-use std::fmt::Display;
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
 
-use crate::world::{State, World};
+use rand::{seq::IndexedRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use tracing::{debug, warn};
+
+use crate::{
+    command::Command,
+    error::Error,
+    plane::Destination,
+    world::{PlaneKind, State, World},
+};
+
+use scoring::{ScoreBreakdown, ScoreWeights};
 
 pub mod builtin;
+pub mod file;
+pub mod scoring;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Level {
     name: String,
     world: World,
     seed: u64,
+    rng: ChaCha12Rng,
+    difficulty: Difficulty,
+    spawned: Vec<Spawn>,
+    /// No new planes come in, the ones in the air keep flying
+    spawning_paused: bool,
+    /// A plane could not come in because its exit was occupied, it tries again next tick
+    spawn_deferred: bool,
+    /// Planes of the [schedule](SpawnPolicy::Schedule) whose exit was occupied, they try again
+    /// every tick in the order they were due
+    spawn_queue: Vec<ScheduledSpawn>,
+    /// Commands carried out in this game
+    commands: u64,
+    /// The last tick that was simulated, see [`Level::tick`]
+    ticks: u64,
+    /// What the score is made of, see [`Level::set_score_weights`]
+    weights: ScoreWeights,
+    spawn_policy: SpawnPolicy,
+}
+
+/// How a level decides when new planes come in
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum SpawnPolicy {
+    /// Planes come in at random, about as often as the [`Difficulty`] says
+    #[default]
+    Random,
+    /// Planes come in exactly at the listed ticks and at no other time, for hand crafted
+    /// scenarios that play out the same every time
+    Schedule(BTreeMap<u64, ScheduledSpawn>),
+    /// Planes only come in when the player lets them in with [`Level::spawn_manual`], to
+    /// practice the controls or try out a layout
+    Manual,
+}
+
+/// A plane that comes in at a fixed tick, see [`SpawnPolicy::Schedule`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledSpawn {
+    pub exit: u8,
+    pub kind: PlaneKind,
+    pub destination: Destination,
+}
+
+/// A plane that entered the level
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Spawn {
+    pub plane: char,
+    pub exit: u8,
+    pub kind: PlaneKind,
+    pub destination: Destination,
+}
+
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// Chance that a new plane shows up in a tick
+    pub fn spawn_probability(self) -> f64 {
+        match self {
+            Self::Easy => 0.03,
+            Self::Normal => 0.05,
+            Self::Hard => 0.1,
+        }
+    }
+
+    /// Chance that a plane that comes in is a jet and not a small plane
+    pub fn jet_probability(self) -> f64 {
+        match self {
+            Self::Easy => 0.1,
+            Self::Normal => 0.25,
+            Self::Hard => 0.4,
+        }
+    }
+
+    /// How many planes the airspace is meant to hold, the top of the traffic meter
+    ///
+    /// This does not stop planes from coming in, the airspace can hold more than that.
+    pub fn max_airborne(self) -> usize {
+        match self {
+            Self::Easy => 6,
+            Self::Normal => 10,
+            Self::Hard => 16,
+        }
+    }
+}
+
+/// How busy an airspace with `airborne` planes is, from 0 for empty to 1 for `capacity` or
+/// more planes
+pub fn congestion(airborne: usize, capacity: usize) -> f64 {
+    if capacity == 0 {
+        return 1.0;
+    }
+    (airborne as f64 / capacity as f64).min(1.0)
 }
 
 impl Level {
     pub fn get_name(&self) -> String {
         self.name.clone()
     }
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+    }
+    /// Planes that were brought to their destination so far, minus the
+    /// [command penalty](Self::set_command_penalty), weighted by the
+    /// [score weights](Self::set_score_weights)
+    ///
+    /// The score is rounded to whole points and never drops below zero.
+    pub fn score(&self) -> u64 {
+        self.score_breakdown().total()
+    }
+    /// The points the game got so far for each kind of event
+    pub fn score_breakdown(&self) -> ScoreBreakdown {
+        self.weights.breakdown(&self.world.stats(), self.commands)
+    }
+    /// Commands carried out in this game, see [`Level::apply_command`]
+    pub fn commands(&self) -> u64 {
+        self.commands
+    }
+    /// Make every command cost `penalty` points to reward careful play, the default is zero
+    pub fn set_command_penalty(&mut self, penalty: f64) {
+        self.weights.command = penalty.max(0.0);
+    }
+    pub fn command_penalty(&self) -> f64 {
+        self.weights.command
+    }
+    /// Set how many points each kind of event is worth, by default every plane that got to its
+    /// destination is one point
+    pub fn set_score_weights(&mut self, weights: ScoreWeights) {
+        self.weights = weights;
+    }
+    pub fn score_weights(&self) -> ScoreWeights {
+        self.weights
+    }
+    /// Start counting commands at `commands`, for a game that is picked up again
+    pub(crate) fn restore_commands(&mut self, commands: u64) {
+        self.commands = commands;
+    }
+    /// Carry out a command of the player and count it if it worked
+    pub fn apply_command(&mut self, command: Command) -> Result<(), Error> {
+        command.apply(&mut self.world)?;
+        self.commands += 1;
+        Ok(())
+    }
+    /// Like [`Level::apply_command`], but only for the planes of `controller`, see
+    /// [`Command::apply_as`]
+    pub fn apply_command_as(&mut self, command: Command, controller: u8) -> Result<(), Error> {
+        command.apply_as(&mut self.world, controller)?;
+        self.commands += 1;
+        Ok(())
+    }
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    /// Start the random number generator over from `seed`
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = ChaCha12Rng::seed_from_u64(seed);
+    }
+    /// How many random numbers were drawn since the [seed](Self::set_seed) was set, to save a
+    /// game and pick up the random numbers where they left off
+    pub(crate) fn rng_position(&self) -> u128 {
+        self.rng.get_word_pos()
+    }
+    /// Continue the random numbers at `position`, see [`Level::rng_position`]
+    pub(crate) fn restore_rng_position(&mut self, position: u128) {
+        self.rng.set_word_pos(position);
+    }
+    /// Start the level over with the same seed, the layout of the world is kept
+    pub fn reset(&mut self) {
+        self.world.reset();
+        self.spawned.clear();
+        self.spawn_deferred = false;
+        self.spawn_queue.clear();
+        self.commands = 0;
+        self.ticks = 0;
+        self.set_seed(self.seed);
+    }
     pub fn world(&self) -> &World {
         &self.world
     }
+    /// How full the airspace is, from 0 when no plane is in the air to 1 at
+    /// [`Difficulty::max_airborne`]
+    pub fn congestion(&self) -> f64 {
+        congestion(self.world.planes().count(), self.difficulty.max_airborne())
+    }
     pub fn world_mut(&mut self) -> &mut World {
         &mut self.world
     }
-    pub fn tick(&mut self) -> State {
-        if rand::random_bool(0.05) {
-            // TODO: add get_max_exit_id function
+    /// Pick the exit the next plane comes in through, busier exits are picked more often
+    pub fn choose_spawn_exit(&mut self) -> Option<u8> {
+        self.world.random_exit_id(&mut self.rng)
+    }
+    /// Let a new plane enter the level
+    ///
+    /// The exit, the kind and the destination of the plane are picked with the random number
+    /// generator of the level, so the same [seed](Self::set_seed) brings in the same planes.
+    pub fn spawn_plane(&mut self) -> Result<Spawn, Error> {
+        let exit = self.choose_spawn_exit().ok_or(Error::NoSpawnPoints)?;
+        let kind = if self.rng.random_bool(self.difficulty.jet_probability()) {
+            PlaneKind::Jet
+        } else {
+            PlaneKind::Small
+        };
+        let destinations: Vec<Destination> = self
+            .world
+            .destinations()
+            .into_iter()
+            .filter(|destination| *destination != Destination::Exit(exit))
+            .collect();
+        let destination = *destinations
+            .choose(&mut self.rng)
+            .ok_or(Error::NoDestinations)?;
+        let plane = self.world.spawn_plane_to(exit, kind, destination)?;
+        Ok(self.record_spawn(Spawn {
+            plane,
+            exit,
+            kind,
+            destination,
+        }))
+    }
+    /// Let the plane from a [schedule](SpawnPolicy::Schedule) enter the level
+    pub fn spawn_scheduled(&mut self, scheduled: ScheduledSpawn) -> Result<Spawn, Error> {
+        let plane =
             self.world
-                .spawn_plane_at_exit(4, crate::world::PlaneKind::Small)
-                .expect("could not spawn plane");
+                .spawn_plane_to(scheduled.exit, scheduled.kind, scheduled.destination)?;
+        Ok(self.record_spawn(Spawn {
+            plane,
+            exit: scheduled.exit,
+            kind: scheduled.kind,
+            destination: scheduled.destination,
+        }))
+    }
+    /// Let a plane the player asked for enter the level through `exit`, only while the
+    /// [spawn policy](SpawnPolicy::Manual) is manual
+    ///
+    /// Without a `destination` the plane flies to a random one.
+    pub fn spawn_manual(
+        &mut self,
+        exit: u8,
+        kind: PlaneKind,
+        destination: Option<Destination>,
+    ) -> Result<Spawn, Error> {
+        if self.spawn_policy != SpawnPolicy::Manual {
+            return Err(Error::NotPractice);
+        }
+        let plane = match destination {
+            Some(destination) => self.world.spawn_plane_to(exit, kind, destination)?,
+            None => self.world.spawn_plane_at_exit(exit, kind, &mut self.rng)?,
+        };
+        let destination = self
+            .world
+            .plane(plane)
+            .ok_or(Error::NoPlaneForID(plane))?
+            .destination;
+        Ok(self.record_spawn(Spawn {
+            plane,
+            exit,
+            kind,
+            destination,
+        }))
+    }
+    fn record_spawn(&mut self, spawn: Spawn) -> Spawn {
+        self.spawned.push(spawn);
+        spawn
+    }
+    pub fn spawn_policy(&self) -> &SpawnPolicy {
+        &self.spawn_policy
+    }
+    pub fn set_spawn_policy(&mut self, policy: SpawnPolicy) {
+        self.spawn_policy = policy;
+    }
+    /// Returns the planes that spawned since the last call
+    pub fn take_spawns(&mut self) -> Vec<Spawn> {
+        std::mem::take(&mut self.spawned)
+    }
+    pub fn spawning_paused(&self) -> bool {
+        self.spawning_paused
+    }
+    /// Stop or resume new planes from coming in, without pausing the planes in the air
+    pub fn set_spawning_paused(&mut self, paused: bool) {
+        self.spawning_paused = paused;
+    }
+    /// Every plane of the [schedule](SpawnPolicy::Schedule) came in by tick `tick`, none is
+    /// waiting for its exit and none is left in the air
+    ///
+    /// Levels where planes come in at random or by hand are never finished.
+    pub fn finished(&self, tick: u64) -> bool {
+        let SpawnPolicy::Schedule(schedule) = &self.spawn_policy else {
+            return false;
+        };
+        schedule.keys().all(|at| *at <= tick)
+            && self.spawn_queue.is_empty()
+            && self.world.planes().next().is_none()
+    }
+    /// Simulate the next tick of the game
+    pub fn tick(&mut self) -> State {
+        self.tick_at(self.ticks + 1)
+    }
+    /// Simulate tick number `tick` of the game, counted from 1, for callers like
+    /// [`GameSession`](crate::session::GameSession) that count the ticks themselves
+    pub fn tick_at(&mut self, tick: u64) -> State {
+        self.ticks = tick;
+        match &self.spawn_policy {
+            SpawnPolicy::Random => self.spawn_at_random(),
+            SpawnPolicy::Schedule(schedule) => {
+                let due = schedule.get(&tick).copied();
+                self.spawn_on_schedule(tick, due);
+            }
+            SpawnPolicy::Manual => (),
         }
-
         self.world.tick_planes()
     }
+    fn spawn_on_schedule(&mut self, tick: u64, due: Option<ScheduledSpawn>) {
+        if self.spawning_paused {
+            if due.is_some() {
+                debug!(tick, "skipping scheduled spawn, spawning is paused");
+            }
+            return;
+        }
+        self.spawn_queue.extend(due);
+        for scheduled in std::mem::take(&mut self.spawn_queue) {
+            match self.spawn_scheduled(scheduled) {
+                Ok(_) => (),
+                Err(Error::ExitOccupied(exit)) => {
+                    debug!(tick, exit, "exit is occupied, queueing scheduled spawn");
+                    self.spawn_queue.push(scheduled);
+                }
+                Err(e) => warn!(
+                    tick,
+                    exit = scheduled.exit,
+                    "could not spawn scheduled plane: {e}"
+                ),
+            }
+        }
+    }
+    fn spawn_at_random(&mut self) {
+        // roll even while paused, so pausing does not change which planes come later
+        let spawn = self.rng.random_bool(self.difficulty.spawn_probability());
+        if spawn && self.spawning_paused {
+            debug!("skipping spawn, spawning is paused");
+        }
+        if (spawn || self.spawn_deferred) && !self.spawning_paused {
+            match self.spawn_plane() {
+                Ok(_) => self.spawn_deferred = false,
+                Err(Error::ExitOccupied(exit)) => {
+                    debug!(exit, "exit is occupied, deferring spawn");
+                    self.spawn_deferred = true;
+                }
+                Err(e) => warn!("could not spawn plane: {e}"),
+            }
+        }
+    }
     pub fn render(&self) -> String {
         self.world.to_string()
     }
+    pub fn render_with_rulers(&self) -> String {
+        self.world.render_with_rulers()
+    }
 }
 
 impl Display for Level {
@@ -42,8 +399,47 @@ impl Display for Level {
     }
 }
 
+impl Display for Spawn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Plane {} entering from exit {} ({}, to {})",
+            self.plane, self.exit, self.kind, self.destination
+        )
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Easy => "easy",
+                Self::Normal => "normal",
+                Self::Hard => "hard",
+            }
+        )
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "easy" => Ok(Self::Easy),
+            "normal" => Ok(Self::Normal),
+            "hard" => Ok(Self::Hard),
+            _ => Err(Error::UnknownDifficulty(s.to_string())),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use crate::{snapshot::assert_snapshot, world::DirectionGrid};
+
     use super::*;
 
     #[test]
@@ -52,8 +448,350 @@ mod test {
         let rendered = level.render();
         assert!(rendered.contains("+ "));
         assert!(rendered.contains(". "));
-        assert!(rendered.contains("e0"));
-        assert!(rendered.contains("e1"));
         assert!(rendered.contains("b0"));
+        assert!(rendered.contains("↓0"));
+        assert!(rendered.contains("↙1"));
+    }
+
+    #[test]
+    fn test_level_render_exit_directions() {
+        let level = Level::builtin();
+        let rendered = level.render();
+        let lines: Vec<&str> = rendered.lines().collect();
+        let world = level.world();
+        for (id, exit) in world.exits() {
+            let marker = format!("{}{id}", exit.plane_out_direction.arrow());
+            let wall = match exit.wall_direction {
+                DirectionGrid::Up => lines[0],
+                DirectionGrid::Down => lines[world.height() + 1],
+                DirectionGrid::Left | DirectionGrid::Right => lines[exit.wall_pos + 1],
+            };
+            assert!(wall.contains(&marker), "{marker} not in {wall}");
+        }
+        assert!(lines[0].contains("↓0"));
+        assert!(lines[3].ends_with("↙1"));
+        assert!(lines[11].starts_with("→3"));
+        assert!(lines[11].ends_with("←2"));
+        assert!(lines[world.height() + 1].contains("↑4"));
+        // markers take the place of a tile on the wall, the map stays aligned
+        assert_eq!(lines[0].chars().position(|c| c == '↓'), Some(2 + 2 * 12));
+        assert_eq!(
+            lines[3].chars().position(|c| c == '↙'),
+            Some(2 + 2 * world.width())
+        );
+    }
+
+    #[test]
+    fn test_level_render_builtin_snapshot() {
+        let level = Level::builtin();
+        assert_snapshot("builtin", &level.render());
+        assert_snapshot("builtin_rulers", &level.render_with_rulers());
+    }
+
+    #[test]
+    fn test_level_builtin_exits() {
+        use crate::world::{DirectionCardinal, DirectionGrid};
+
+        let level = Level::builtin();
+        let exits: Vec<(u8, DirectionGrid, DirectionCardinal)> = level
+            .world()
+            .exits()
+            .map(|(id, exit)| (id, exit.wall_direction, exit.plane_out_direction))
+            .collect();
+        assert_eq!(
+            exits,
+            vec![
+                (0, DirectionGrid::Up, DirectionCardinal::South),
+                (1, DirectionGrid::Right, DirectionCardinal::SouthWest),
+                (2, DirectionGrid::Right, DirectionCardinal::West),
+                (3, DirectionGrid::Left, DirectionCardinal::East),
+                (4, DirectionGrid::Down, DirectionCardinal::North),
+            ]
+        );
+        assert_eq!(level.world().exit(2).unwrap().wall_pos, 10);
+        assert!(level.world().exit(5).is_none());
+    }
+
+    #[test]
+    fn test_level_spawn_weights() {
+        let mut level = Level::builtin();
+        level.set_seed(1337);
+        for (id, weight) in [(0, 1), (1, 0), (2, 0), (3, 0), (4, 3)] {
+            level.world_mut().set_exit_spawn_weight(id, weight).unwrap();
+        }
+        let mut counts = [0usize; 5];
+        for _ in 0..4000 {
+            counts[level.choose_spawn_exit().unwrap() as usize] += 1;
+        }
+        assert_eq!(counts[1..4], [0, 0, 0]);
+        assert!((900..1100).contains(&counts[0]), "{counts:?}");
+        assert!((2900..3100).contains(&counts[4]), "{counts:?}");
+    }
+
+    #[test]
+    fn test_level_tick_returns_state() {
+        use crate::{
+            plane::{Destination, Plane},
+            world::{DirectionCardinal, PlaneKind},
+        };
+
+        let mut level = Level::builtin();
+        level.set_spawn_policy(SpawnPolicy::Manual);
+        assert_eq!(level.tick(), State::Ongoing);
+
+        let mut plane = Plane::new(
+            [8, 8].into(),
+            DirectionCardinal::North,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        );
+        plane.just_spawned = false;
+        plane.height = 1;
+        plane.target_height = 0;
+        level.world_mut().insert_plane(plane);
+        assert!(matches!(level.tick(), State::PlaneCrash(plane) if plane.id == 'a'));
+    }
+
+    #[test]
+    fn test_level_spawning_without_exits() {
+        use crate::world::{DirectionGrid, WorldTile};
+
+        let mut level = Level::builtin();
+        level.set_difficulty(Difficulty::Hard);
+        let world = level.world_mut();
+        *world = World::new(20, 20);
+        world
+            .place_tile(WorldTile::Airport(DirectionGrid::Up, 0), [5, 10])
+            .unwrap();
+        assert!(matches!(level.spawn_plane(), Err(Error::NoSpawnPoints)));
+        for _ in 0..100 {
+            assert_eq!(level.tick(), State::Ongoing);
+        }
+        assert!(level.take_spawns().is_empty());
+    }
+
+    #[test]
+    fn test_level_spawning_paused() {
+        let mut level = Level::builtin();
+        level.set_difficulty(Difficulty::Hard);
+        let spawn = level.spawn_plane().unwrap();
+        level.take_spawns();
+        let start = level.world().plane(spawn.plane).unwrap().pos;
+
+        level.set_spawning_paused(true);
+        for _ in 0..10 {
+            assert!(matches!(level.tick(), State::Ongoing));
+        }
+        assert!(level.take_spawns().is_empty());
+        assert_eq!(level.world().planes().count(), 1);
+        assert_ne!(level.world().plane(spawn.plane).unwrap().pos, start);
+    }
+
+    #[test]
+    fn test_level_reset_replays_spawns() {
+        let mut level = Level::builtin();
+        level.set_seed(42);
+        // a plane does not come in while its exit is occupied, that has to repeat as well
+        let first: Vec<Option<Spawn>> = (0..5).map(|_| level.spawn_plane().ok()).collect();
+        assert!(first.iter().flatten().count() >= 2, "{first:?}");
+
+        level.reset();
+        assert_eq!(level.world().planes().count(), 0);
+        assert!(level.take_spawns().is_empty());
+        let again: Vec<Option<Spawn>> = (0..5).map(|_| level.spawn_plane().ok()).collect();
+        assert_eq!(first, again);
+    }
+
+    #[test]
+    fn test_level_command_penalty() {
+        use crate::{
+            plane::{Destination, Plane},
+            world::{DirectionCardinal, PlaneKind},
+        };
+
+        let mut level = Level::builtin();
+        level.set_spawning_paused(true);
+        let mut plane = Plane::new(
+            [12, 1].into(),
+            DirectionCardinal::North,
+            PlaneKind::Jet,
+            'p',
+            Destination::Exit(0),
+        );
+        plane.just_spawned = false;
+        plane.height = plane.spec.exit_height;
+        plane.target_height = plane.spec.exit_height;
+        level.world_mut().insert_plane(plane);
+        level.world_mut().insert_plane(Plane::new(
+            [5, 5].into(),
+            DirectionCardinal::South,
+            PlaneKind::Jet,
+            'q',
+            Destination::Exit(0),
+        ));
+        level.tick();
+        assert_eq!(level.world().planes_handled(), 1);
+
+        for input in ["q OK", "q v s 4", "x OK"] {
+            let _ = level.apply_command(input.parse().unwrap());
+        }
+        assert_eq!(level.commands(), 2);
+        assert_eq!(level.score(), 1, "no penalty by default");
+
+        level.set_command_penalty(0.2);
+        assert_eq!(level.score(), 1);
+        level.apply_command("q v s 3".parse().unwrap()).unwrap();
+        assert_eq!(level.commands(), 3);
+        assert_eq!(level.score(), 0);
+        level.set_command_penalty(2.0);
+        assert_eq!(level.score(), 0, "the score does not go below zero");
+
+        level.reset();
+        assert_eq!(level.commands(), 0);
+    }
+
+    #[test]
+    fn test_level_congestion() {
+        for (airborne, capacity, expected) in [
+            (0, 10, 0.0),
+            (3, 12, 0.25),
+            (5, 10, 0.5),
+            (10, 10, 1.0),
+            (14, 10, 1.0),
+            (1, 0, 1.0),
+        ] {
+            assert_eq!(congestion(airborne, capacity), expected);
+        }
+    }
+
+    #[test]
+    fn test_level_full_airspace_keeps_spawning() {
+        use crate::{
+            plane::{Destination, Plane},
+            world::{DirectionCardinal, PlaneKind},
+        };
+
+        let mut level = Level::builtin();
+        level.set_difficulty(Difficulty::Easy);
+        assert_eq!(level.congestion(), 0.0);
+        for (i, id) in ['p', 'q', 'r', 's', 't', 'u'].into_iter().enumerate() {
+            level.world_mut().insert_plane(Plane::new(
+                [2 + i, 15].into(),
+                DirectionCardinal::East,
+                PlaneKind::Jet,
+                id,
+                Destination::Exit(0),
+            ));
+        }
+        assert_eq!(level.congestion(), 1.0);
+
+        level.spawn_deferred = true;
+        level.tick();
+        assert_eq!(level.take_spawns().len(), 1);
+        assert_eq!(level.congestion(), 1.0);
+    }
+
+    #[test]
+    fn test_level_spawn_schedule() {
+        use crate::session::GameSession;
+
+        let scheduled = ScheduledSpawn {
+            exit: 3,
+            kind: PlaneKind::Jet,
+            destination: Destination::Airport(0),
+        };
+        let mut level = Level::builtin();
+        level.set_difficulty(Difficulty::Hard);
+        level.set_spawn_policy(SpawnPolicy::Schedule(BTreeMap::from([(5, scheduled)])));
+        let mut session = GameSession::new(level);
+        for tick in 1..=20 {
+            let report = session.step();
+            if tick == 5 {
+                assert_eq!(report.spawns.len(), 1);
+                let spawn = report.spawns[0];
+                assert_eq!(spawn.exit, 3);
+                assert_eq!(spawn.kind, PlaneKind::Jet);
+                assert_eq!(spawn.destination, Destination::Airport(0));
+                let plane = session.level().world().plane(spawn.plane).unwrap();
+                assert_eq!(plane.kind, PlaneKind::Jet);
+                assert_eq!(plane.destination, Destination::Airport(0));
+            } else {
+                assert!(report.spawns.is_empty(), "spawn at tick {tick}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_level_spawn_schedule_waits_for_exit() {
+        let scheduled = ScheduledSpawn {
+            exit: 3,
+            kind: PlaneKind::Small,
+            destination: Destination::Airport(0),
+        };
+        let mut level = Level::builtin();
+        level.set_spawn_policy(SpawnPolicy::Schedule(BTreeMap::from([
+            (5, scheduled),
+            (6, scheduled),
+        ])));
+        for _ in 0..5 {
+            level.tick();
+        }
+        assert_eq!(level.take_spawns().len(), 1);
+        // the small plane from tick 5 has not moved off the exit yet
+        level.tick();
+        assert!(level.take_spawns().is_empty());
+        assert!(!level.finished(6));
+        level.tick();
+        let spawns = level.take_spawns();
+        assert_eq!(spawns.len(), 1);
+        assert_eq!(spawns[0].exit, 3);
+        assert_eq!(level.world().planes().count(), 2);
+    }
+
+    #[test]
+    fn test_level_practice_spawns_only_by_hand() {
+        use crate::{command::SpawnRequest, session::GameSession};
+
+        let mut level = Level::builtin();
+        level.set_difficulty(Difficulty::Hard);
+        assert!(matches!(
+            level.spawn_manual(0, PlaneKind::Jet, None),
+            Err(Error::NotPractice)
+        ));
+        level.set_spawn_policy(SpawnPolicy::Manual);
+        let mut session = GameSession::new(level);
+        for _ in 0..50 {
+            assert!(session.step().spawns.is_empty());
+        }
+        assert_eq!(session.level().world().planes().count(), 0);
+
+        let request: SpawnRequest = "+ 3 jet a0".parse().unwrap();
+        let spawn = request.apply(session.level_mut()).unwrap();
+        assert_eq!(session.level().world().planes().count(), 1);
+        let plane = session.level().world().plane(spawn.plane).unwrap();
+        assert_eq!(plane.kind, PlaneKind::Jet);
+        assert_eq!(plane.destination, Destination::Airport(0));
+        assert_eq!(session.step().spawns, vec![spawn]);
+        assert_eq!(session.level().world().planes().count(), 1);
+    }
+
+    #[test]
+    fn test_level_manual_spawns_follow_the_seed() {
+        let destinations = || {
+            let mut level = Level::builtin();
+            level.set_seed(7);
+            level.set_spawn_policy(SpawnPolicy::Manual);
+            (0..12)
+                .map(|i| {
+                    let spawn = level.spawn_manual(i % 4, PlaneKind::Jet, None).unwrap();
+                    level.world_mut().clear_planes();
+                    spawn.destination
+                })
+                .collect::<Vec<_>>()
+        };
+        let first = destinations();
+        assert!(first.iter().any(|d| *d != first[0]), "{first:?}");
+        assert_eq!(first, destinations());
     }
 }
@@ -0,0 +1,193 @@
+//! How the score of a game is made up from the [`Stats`] of its world
+//!
+//! Every kind of event is worth some points, see [`ScoreWeights`]. By default only planes that
+//! left or landed count, one point each.
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{error::Error, world::Stats};
+
+/// Points for each kind of event, the score is their sum
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ScoreWeights {
+    /// Points for every plane that left through its exit
+    pub exit: f64,
+    /// Points for every plane that landed at its airport
+    pub landing: f64,
+    /// Points for every tick of fuel the planes had left when they got to their destination
+    pub fuel: f64,
+    /// Points lost for every plane that did not make it, see [`Stats::lost`]
+    pub crash: f64,
+    /// Points lost for every command
+    pub command: f64,
+}
+
+/// The points a game got for each kind of event, see [`ScoreWeights::breakdown`]
+///
+/// Points that were lost are negative.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ScoreBreakdown {
+    pub exits: f64,
+    pub landings: f64,
+    pub fuel: f64,
+    pub crashes: f64,
+    pub commands: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            exit: 1.0,
+            landing: 1.0,
+            fuel: 0.0,
+            crash: 0.0,
+            command: 0.0,
+        }
+    }
+}
+
+impl ScoreWeights {
+    /// What a game with `stats` and `commands` commands is worth
+    pub fn breakdown(&self, stats: &Stats, commands: u64) -> ScoreBreakdown {
+        ScoreBreakdown {
+            exits: stats.exits as f64 * self.exit,
+            landings: stats.landings as f64 * self.landing,
+            fuel: stats.fuel_left as f64 * self.fuel,
+            crashes: -(stats.lost() as f64 * self.crash),
+            commands: -(commands as f64 * self.command),
+        }
+    }
+}
+
+impl ScoreBreakdown {
+    /// The points of every kind of event added up
+    pub fn sum(&self) -> f64 {
+        self.exits + self.landings + self.fuel + self.crashes + self.commands
+    }
+
+    /// The score: the [sum](Self::sum) rounded to whole points, never below zero
+    pub fn total(&self) -> u64 {
+        self.sum().max(0.0).round() as u64
+    }
+}
+
+/// Weights look like `exit = 2, fuel = 0.1`, the weights that are left out keep their default
+///
+/// The keys are `exit`, `landing`, `fuel`, `crash` and `command`. No weight can be negative,
+/// points that are lost are counted as lost already.
+impl FromStr for ScoreWeights {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut weights = Self::default();
+        for pair in s.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let bad = || Error::BadScoreWeight(pair.trim().to_string());
+            let (key, value) = pair.split_once('=').ok_or_else(bad)?;
+            let value: f64 = value.trim().parse().map_err(|_| bad())?;
+            if !value.is_finite() || value < 0.0 {
+                return Err(bad());
+            }
+            let weight = match key.trim() {
+                "exit" => &mut weights.exit,
+                "landing" => &mut weights.landing,
+                "fuel" => &mut weights.fuel,
+                "crash" => &mut weights.crash,
+                "command" => &mut weights.command,
+                _ => return Err(bad()),
+            };
+            *weight = value;
+        }
+        Ok(weights)
+    }
+}
+
+impl Display for ScoreBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn points(p: f64) -> f64 {
+            (p * 10.0).round() / 10.0 + 0.0
+        }
+        write!(
+            f,
+            "Exits {:+} | Landings {:+} | Fuel {:+} | Crashes {:+} | Commands {:+} = {}",
+            points(self.exits),
+            points(self.landings),
+            points(self.fuel),
+            points(self.crashes),
+            points(self.commands),
+            self.total()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn stats() -> Stats {
+        Stats {
+            exits: 3,
+            landings: 2,
+            crashes: 1,
+            fuel_left: 40,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_scoring_breakdown() {
+        let default = ScoreWeights::default().breakdown(&stats(), 7);
+        assert_eq!(default.total(), 5, "one point per plane by default");
+
+        let weights: ScoreWeights = "exit = 2, landing = 3, fuel = 0.1, crash = 4, command = 0.5"
+            .parse()
+            .unwrap();
+        let breakdown = weights.breakdown(&stats(), 7);
+        assert_eq!(
+            breakdown,
+            ScoreBreakdown {
+                exits: 6.0,
+                landings: 6.0,
+                fuel: 4.0,
+                crashes: -4.0,
+                commands: -3.5,
+            }
+        );
+        assert_eq!(breakdown.sum(), 8.5);
+        assert_eq!(breakdown.total(), 9);
+        assert_eq!(
+            breakdown.to_string(),
+            "Exits +6 | Landings +6 | Fuel +4 | Crashes -4 | Commands -3.5 = 9"
+        );
+
+        // one more point per landing is two more points in total
+        let more = ScoreWeights {
+            landing: 4.0,
+            ..weights
+        };
+        assert_eq!(more.breakdown(&stats(), 7).total(), 11);
+
+        let harsh = ScoreWeights {
+            crash: 100.0,
+            ..weights
+        };
+        assert_eq!(harsh.breakdown(&stats(), 7).total(), 0, "never below zero");
+    }
+
+    #[test]
+    fn test_scoring_parse_weights() {
+        assert_eq!("".parse::<ScoreWeights>().unwrap(), ScoreWeights::default());
+        assert_eq!(
+            "fuel=0.5".parse::<ScoreWeights>().unwrap(),
+            ScoreWeights {
+                fuel: 0.5,
+                ..Default::default()
+            }
+        );
+        for bad in ["fuel", "speed = 1", "exit = -1", "exit = lots"] {
+            assert!(
+                matches!(bad.parse::<ScoreWeights>(), Err(Error::BadScoreWeight(_))),
+                "{bad}"
+            );
+        }
+    }
+}
@@ -0,0 +1,16 @@
+pub mod autopilot;
+pub mod command;
+pub mod error;
+pub mod heatmap;
+pub mod hud;
+pub mod level;
+pub mod plane;
+pub mod roster;
+pub mod scores;
+pub mod session;
+#[cfg(test)]
+mod snapshot;
+pub mod theme;
+pub mod ui;
+pub mod wind;
+pub mod world;
@@ -3,78 +3,714 @@ use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifier
 use ratatui::{
     prelude::{Constraint, Layout, Margin},
     style::Stylize,
-    text::Line,
-    widgets::{Block, Paragraph},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph},
     DefaultTerminal, Frame,
 };
 
-mod error;
-mod level;
-mod plane;
-mod world;
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use base::{
+    autopilot,
+    command::{self, Command, SpawnRequest},
+    error::Error,
+    heatmap::Heatmap,
+    hud::Hud,
+    level::{Level, SpawnPolicy},
+    plane::{Plane, MAX_HEIGHT},
+    roster::{self, roster},
+    scores::HighScores,
+    session::{replay::Replay, GameSession, Outcome, TickReport},
+    theme::Theme,
+    ui,
+    wind::WindEffect,
+    world::{DirectionCardinal, Warning},
+};
+use tracing::{error, trace};
 
-use level::Level;
-use tracing::trace;
+/// How many lines of the event log are shown
+const LOG_LENGTH: usize = 5;
+/// How many of the last events of a game are shown when it is over
+const TIMELINE_LENGTH: usize = 3;
+/// How much time passes between two ticks of the game
+const TICK_RATE: Duration = Duration::from_millis(500);
+/// Width of the plane list next to the map
+const ROSTER_WIDTH: u16 = 29;
+/// How many critical events the alert panel of the accessibility mode shows
+const ALERT_LENGTH: usize = 3;
+/// How many ticks ahead the path of the selected plane is shown
+const GHOST_TICKS: usize = 6;
+/// How much time passes between two ticks while fast forwarding
+const FAST_FORWARD_RATE: Duration = Duration::from_millis(20);
+/// Fast forwarding stops after this many ticks even if nothing happened
+const FAST_FORWARD_MAX_TICKS: u32 = 200;
+/// How many ticks the start screen waits for a key before the demo starts
+const DEMO_IDLE_TICKS: u32 = 20;
+/// Lines the help screen scrolls with Page Up and Page Down
+const HELP_PAGE: u16 = 10;
+/// The keys listed on the help screen and what they do
+const KEYS: [(&str, &str); 19] = [
+    ("F1, ?", "show or hide this help"),
+    ("Enter", "carry out the command, or start the game"),
+    ("Tab", "take the first suggestion"),
+    (
+        "Arrow keys, n e s w",
+        "turn the selected plane north, south, west or east, type its id to select it",
+    ),
+    (
+        "PageUp, PageDown",
+        "let the selected plane climb or descend one level",
+    ),
+    ("Esc", "quit, a game that is still going is saved"),
+    ("F2", "show rulers around the map"),
+    ("F3", "list exits and airports instead of the log"),
+    ("F4", "all planes hold, press again to release them"),
+    ("F5", "pause"),
+    ("F6", "step one tick while paused"),
+    ("F7", "stop new planes from coming in"),
+    ("F8", "accessibility mode"),
+    ("F9", "color planes by their destination"),
+    ("F10", "switch between arrows and letters for headings"),
+    ("F11", "fast forward to the next event"),
+    ("F12", "show where planes were most often, for level design"),
+    ("R", "watch the replay of a finished game"),
+    (
+        "Left, Right",
+        "step through the replay, type a tick and Enter to jump there",
+    ),
+];
+/// Set this environment variable to start in accessibility mode
+const ACCESSIBLE_ENV: &str = "ATC_ACCESSIBLE";
+/// Set this environment variable to the name of a built in theme or the path of a theme file
+const THEME_ENV: &str = "ATC_THEME";
+/// Set this environment variable to reject commands that crash a plane right away
+const SAFETY_ENV: &str = "ATC_SAFETY";
+/// Set this environment variable to `ticks`, `minutes` or `percent` to choose how fuel is shown
+const FUEL_UNIT_ENV: &str = "ATC_FUEL_UNIT";
+/// Set this environment variable to the points every command costs, by default commands are free
+const COMMAND_PENALTY_ENV: &str = "ATC_COMMAND_PENALTY";
+/// Set this environment variable to what events are worth, like `exit = 2, fuel = 0.1`, see
+/// [`ScoreWeights`](base::level::scoring::ScoreWeights). [`COMMAND_PENALTY_ENV`] wins over the
+/// `command` weight.
+const SCORING_ENV: &str = "ATC_SCORING";
+/// Set this environment variable to the path a game is saved to when quitting in the middle
+const SAVE_ENV: &str = "ATC_SAVE";
+/// Set this environment variable to a direction and a strength like `ne 2` for wind that blows
+/// that way
+const WIND_ENV: &str = "ATC_WIND";
+/// Set this environment variable for practice mode, where planes only come in when the player
+/// lets them in with a command like `+ 0 jet a1`
+const PRACTICE_ENV: &str = "ATC_PRACTICE";
+/// Set this environment variable to draw the map as half blocks, which keeps its proportions
+/// but only shows the id and height of the selected plane
+const HALF_BLOCKS_ENV: &str = "ATC_HALF_BLOCKS";
+/// Set this environment variable to `always` or `never` to turn colors on or off, by default
+/// they are off if `NO_COLOR` is set or the terminal is dumb
+const COLOR_ENV: &str = "ATC_COLOR";
+/// Set this environment variable to the key that selects the next plane in a conflict, like `f12`
+/// or `x`, the default is Shift+Tab
+const CONFLICT_KEY_ENV: &str = "ATC_CONFLICT_KEY";
+/// Set this environment variable to the path of a level file to play instead of the built in level
+const LEVEL_ENV: &str = "ATC_LEVEL";
+/// Set this environment variable to a number of players to share the planes between them, every
+/// player starts their commands with their number counted from 0, like `1 a v n 5`
+const CONTROLLERS_ENV: &str = "ATC_CONTROLLERS";
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct AppFlags {
     pub accept: bool,
+    /// Show column and row indices around the map
+    pub rulers: bool,
+    /// The game does not advance on its own, but can be stepped through tick by tick
+    pub paused: bool,
+    /// Show where the exits and airports are instead of the event log
+    pub destinations: bool,
+    /// Color planes by their destination and show which color means what
+    pub color_by_destination: bool,
+    /// Accessibility mode: status text is bold and high contrast, and critical events that are
+    /// otherwise only visible on the map get their own panel
+    pub accessible: bool,
+    /// The arrows that show the heading of planes in the roster
+    pub glyphs: ui::GlyphSet,
+    /// Draw the map with half blocks, two tiles per character, so it is about as high as wide
+    pub half_blocks: bool,
+    /// Draw with colors, see [`COLOR_ENV`]
+    pub colors: bool,
 }
 
 #[derive(Debug)]
 pub struct App {
     state: GameState,
-    level: Level,
+    session: GameSession,
     status_info: Option<String>,
+    log: VecDeque<String>,
     flags: AppFlags,
+    scores_path: PathBuf,
+    /// The command the player is typing
+    input: String,
+    /// The plane the arrow keys and compass letters steer, picked by typing its id
+    selected_plane: Option<char>,
+    /// The controller that picked [`App::selected_plane`] if several players share the
+    /// planes
+    selected_controller: Option<u8>,
+    /// Critical events for the alert panel of the accessibility mode
+    alerts: VecDeque<String>,
+    theme: Theme,
+    /// The game the autopilot plays on the start screen, see [`GameState::Demo`]
+    demo: Option<GameSession>,
+    /// Ticks since the last key was pressed on the start screen
+    idle_ticks: u32,
+    /// Where the game is saved when quitting in the middle of it
+    save_path: PathBuf,
+    /// The game saved when the player last quit, it can be resumed from the start screen
+    saved: Option<GameSession>,
+    /// Ticks left to fast forward while waiting for something to happen, see
+    /// [`App::fast_forward`]
+    fast_forward: Option<u32>,
+    /// How far the help screen is scrolled down, None while it is hidden
+    help_scroll: Option<u16>,
+    /// Where the planes were since the heatmap was turned on, None while it is off
+    heatmap: Option<Heatmap>,
+    /// Selects the next plane in a proximity conflict, see [`CONFLICT_KEY_ENV`]
+    conflict_key: KeyCode,
+    /// Why the level from [`LEVEL_ENV`] could not be played, shown on the start screen
+    level_error: Option<String>,
+    /// The finished game watched again, see [`GameState::Replay`]
+    replay: Option<Replay>,
 }
 
 #[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
 enum GameState {
     #[default]
     Startup,
+    /// The autopilot plays on its own until a key is pressed
+    Demo,
     Ongoing,
     Results,
+    /// The finished game is shown again and can be stepped through
+    Replay,
     Exit,
 }
 
 impl App {
     /// Construct a new instance of [`App`].
     pub fn new() -> Self {
-        Self {
+        Self::with_paths(
+            std::env::var_os(SAVE_ENV)
+                .map(PathBuf::from)
+                .unwrap_or_else(GameSession::default_save_path),
+            HighScores::default_path(),
+        )
+    }
+
+    /// Like [`App::new`], with the game saved at `save_path` and the high scores kept at
+    /// `scores_path`
+    fn with_paths(save_path: PathBuf, scores_path: PathBuf) -> Self {
+        let mut app = Self {
             state: Default::default(),
-            level: Level::builtin(),
+            session: GameSession::new(Level::with_seed(rand::random())),
             status_info: Default::default(),
+            log: Default::default(),
             flags: Default::default(),
+            scores_path,
+            input: String::new(),
+            selected_plane: None,
+            selected_controller: None,
+            alerts: Default::default(),
+            theme: Theme::default(),
+            demo: None,
+            idle_ticks: 0,
+            save_path,
+            saved: None,
+            fast_forward: None,
+            help_scroll: None,
+            heatmap: None,
+            conflict_key: KeyCode::BackTab,
+            level_error: None,
+            replay: None,
+        };
+        if let Some(path) = std::env::var_os(LEVEL_ENV) {
+            match load_level(Path::new(&path)) {
+                Ok(level) => app.session = GameSession::new(level),
+                Err(e) => app.level_error = Some(e),
+            }
+        }
+        app.flags.accessible = std::env::var_os(ACCESSIBLE_ENV).is_some();
+        app.flags.half_blocks = std::env::var_os(HALF_BLOCKS_ENV).is_some();
+        let color_mode = match std::env::var(COLOR_ENV).map(|mode| mode.parse()) {
+            Ok(Ok(mode)) => mode,
+            Ok(Err(e)) => {
+                error!("{COLOR_ENV}: {e}");
+                ui::ColorMode::Auto
+            }
+            Err(_) => ui::ColorMode::Auto,
+        };
+        app.flags.colors = color_mode.colors();
+        if let Ok(theme) = std::env::var(THEME_ENV) {
+            let loaded = Theme::preset(&theme).or_else(|_| Theme::load(&theme));
+            match loaded {
+                Ok(theme) => app.theme = theme,
+                Err(e) => error!("{THEME_ENV}: {e}"),
+            }
+        }
+        if let Ok(key) = std::env::var(CONFLICT_KEY_ENV) {
+            match parse_key(&key) {
+                Some(key) => app.conflict_key = key,
+                None => error!("{CONFLICT_KEY_ENV}: unknown key {key}"),
+            }
+        }
+        apply_settings(&mut app.session);
+        app.saved = find_saved_game(&app.save_path);
+        app.status_info = Some(app.startup_info());
+        app
+    }
+
+    /// What the start screen says
+    fn startup_info(&self) -> String {
+        let mut info = String::new();
+        if let Some(e) = &self.level_error {
+            info.push_str(&format!("{e}, playing the built in level\n"));
+        }
+        info.push_str(&format!("{}\nPress Enter to start", self.high_score_info()));
+        if self.saved.is_some() {
+            info.push_str(", R to resume the last game");
+        }
+        info.push_str(", F1 for help");
+        info
+    }
+
+    /// Pick up the game saved when the player last quit
+    fn resume(&mut self) {
+        let Some(mut saved) = self.saved.take() else {
+            return;
+        };
+        apply_settings(&mut saved);
+        self.session = saved;
+        self.status_info = None;
+        self.state = GameState::Ongoing;
+        let tick = self.session.tick();
+        self.log_event(format!("{tick}: resumed the last game"));
+    }
+
+    fn high_score_info(&self) -> String {
+        let name = self.session.level().get_name();
+        let difficulty = self.session.level().difficulty();
+        match HighScores::load(&self.scores_path).get(&name, difficulty) {
+            Some(score) => format!("High score for {name} ({difficulty}): {score}"),
+            None => format!("No high score for {name} ({difficulty}) yet"),
         }
     }
 
+    /// Store the score of the finished game and describe how it went
+    fn finish_game(&mut self, outcome: Outcome) -> String {
+        let level = self.session.level();
+        let score = outcome.score();
+        let high_score = match HighScores::record(
+            &self.scores_path,
+            &level.get_name(),
+            level.difficulty(),
+            score,
+        ) {
+            Ok(true) => "New high score!".to_string(),
+            Ok(false) => self.high_score_info(),
+            Err(e) => format!("Could not save high score: {e}"),
+        };
+        let stats = level.world().stats();
+        let breakdown = level.score_breakdown();
+        if let Err(e) = std::fs::remove_file(&self.save_path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                error!("could not remove the saved game: {e}");
+            }
+        }
+        let timeline = self.session.timeline();
+        let last_events: String = timeline[timeline.len().saturating_sub(TIMELINE_LENGTH)..]
+            .iter()
+            .map(|event| format!("\n  {event}"))
+            .collect();
+        format!(
+            "{outcome}\nScore: {score}. {high_score}\n{breakdown}\n{stats}\nLast events:{last_events}\nPress R to watch the replay"
+        )
+    }
+
     /// Run the application's main loop.
     pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+        let mut last_tick = Instant::now();
         while self.state != GameState::Exit {
             terminal.draw(|frame| self.render(frame))?;
-            self.handle_crossterm_events()?;
-            match self.state {
-                GameState::Startup => {
+            let tick_rate = if self.fast_forward.is_some() {
+                FAST_FORWARD_RATE
+            } else {
+                TICK_RATE
+            };
+            self.handle_crossterm_events(tick_rate.saturating_sub(last_tick.elapsed()))?;
+            if last_tick.elapsed() >= tick_rate {
+                self.update();
+                last_tick = Instant::now();
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the game along, called once every [`TICK_RATE`]
+    ///
+    /// Nothing moves while the help screen is open.
+    fn update(&mut self) {
+        if self.help_scroll.is_some() {
+            return;
+        }
+        match self.state {
+            GameState::Startup => {
+                if self.flags.accept {
+                    self.status_info = None;
                     self.state = GameState::Ongoing;
+                    self.flags.accept = false
+                } else {
+                    self.idle_ticks += 1;
+                    if self.idle_ticks >= DEMO_IDLE_TICKS {
+                        self.start_demo();
+                    }
+                }
+            }
+            GameState::Demo => self.advance_demo(),
+            GameState::Ongoing => {
+                if let Some(left) = self.fast_forward {
+                    let report = self.advance();
+                    self.fast_forward =
+                        (!report.has_events() && left > 1 && self.state == GameState::Ongoing)
+                            .then_some(left - 1);
+                } else if !self.flags.paused {
+                    self.advance();
                 }
-                GameState::Ongoing => match self.level.tick() {
-                    world::State::Onging => (),
-                    other => {
-                        self.status_info = Some(format!("{other}"));
-                        self.state = GameState::Results;
+            }
+            GameState::Results => {
+                if self.flags.accept {
+                    self.state = GameState::Exit;
+                    self.flags.accept = false
+                }
+            }
+            GameState::Replay | GameState::Exit => (),
+        }
+    }
+
+    /// Simulate a single tick of the game
+    fn advance(&mut self) -> TickReport {
+        let report = self.session.step();
+        if let Some(heatmap) = &mut self.heatmap {
+            heatmap.record(self.session.level().world());
+        }
+        self.log_report(&report);
+        if let Some(outcome) = report.outcome {
+            self.status_info = Some(self.finish_game(outcome));
+            self.state = GameState::Results;
+        }
+        report
+    }
+
+    /// Let the autopilot play a game on the start screen
+    fn start_demo(&mut self) {
+        self.demo = Some(GameSession::new(Level::with_seed(rand::random())));
+        self.log.clear();
+        self.status_info = Some("Demo, press any key".to_string());
+        self.state = GameState::Demo;
+    }
+
+    /// Go back to the start screen from the demo
+    fn stop_demo(&mut self) {
+        self.demo = None;
+        self.log.clear();
+        self.idle_ticks = 0;
+        self.status_info = Some(self.startup_info());
+        self.state = GameState::Startup;
+    }
+
+    /// Simulate a tick of the demo, a lost game starts a new one
+    fn advance_demo(&mut self) {
+        let Some(demo) = self.demo.as_mut() else {
+            return;
+        };
+        autopilot::fly(demo.level_mut().world_mut());
+        let report = demo.step();
+        self.log_report(&report);
+        if report.outcome.is_some() {
+            self.demo = Some(GameSession::new(Level::with_seed(rand::random())));
+        }
+    }
+
+    /// Watch the finished game again, from its first tick
+    fn start_replay(&mut self) {
+        let Some(mut replay) = self.session.replay() else {
+            return;
+        };
+        replay
+            .seek(replay.first_tick())
+            .expect("the first tick is recorded");
+        self.replay = Some(replay);
+        self.input.clear();
+        self.state = GameState::Replay;
+    }
+
+    /// Go back to the results from the replay
+    fn stop_replay(&mut self) {
+        self.replay = None;
+        self.input.clear();
+        self.state = GameState::Results;
+    }
+
+    /// Jump to the tick the player typed in the replay
+    fn seek_replay(&mut self) {
+        let input = std::mem::take(&mut self.input);
+        let Some(replay) = self.replay.as_mut() else {
+            return;
+        };
+        let result = match input.trim().parse() {
+            Ok(tick) => replay.seek(tick),
+            Err(_) => Err(Error::BadCommand(input)),
+        };
+        if let Err(e) = result {
+            self.log_event(e.to_string());
+        }
+    }
+
+    /// The game shown on the map, the demo or the replay if one runs
+    fn shown_session(&self) -> &GameSession {
+        self.demo
+            .as_ref()
+            .or(self.replay.as_ref().map(Replay::session))
+            .unwrap_or(&self.session)
+    }
+
+    /// Advance by a single tick while paused, to see what the planes do frame by frame
+    fn step(&mut self) {
+        if self.state == GameState::Ongoing && self.flags.paused {
+            self.advance();
+        }
+    }
+
+    /// Skip quiet ticks while paused: the game runs quickly until a plane comes in, there is a
+    /// warning or the game ends, at most for [`FAST_FORWARD_MAX_TICKS`]
+    ///
+    /// Any key stops fast forwarding.
+    fn fast_forward(&mut self) {
+        if self.state == GameState::Ongoing && self.flags.paused {
+            self.fast_forward = Some(FAST_FORWARD_MAX_TICKS);
+        }
+    }
+
+    /// Hold every plane at once, or let them fly on if they already are
+    fn toggle_panic_hold(&mut self) {
+        if self.state != GameState::Ongoing {
+            return;
+        }
+        let world = self.session.level_mut().world_mut();
+        let event = if world.panic_holding() {
+            world.release_panic_hold();
+            "all planes resume their headings"
+        } else {
+            world.panic_hold();
+            "all planes holding"
+        };
+        let tick = self.session.tick();
+        self.log_event(format!("{tick}: {event}"));
+    }
+
+    /// The selected plane, None once it left the map
+    fn selected_plane(&self) -> Option<char> {
+        self.selected_plane
+            .filter(|id| self.session.level().world().plane(*id).is_some())
+    }
+
+    /// Handle a character typed while playing
+    ///
+    /// Typing the id of a plane at the start of a command selects it, a compass letter right
+    /// after the id of the selected plane turns it instead of being typed.
+    fn type_char(&mut self, c: char) {
+        let world = self.session.level().world();
+        if let Ok((controller, command)) = self.split_controller(&self.input) {
+            let mut typed = command.chars().filter(|c| !c.is_whitespace());
+            match (typed.next(), typed.next()) {
+                (None, _) if c.is_ascii_alphabetic() => match world.resolve_plane_id(c) {
+                    Ok(id) => {
+                        self.selected_plane = Some(id);
+                        self.selected_controller = controller;
+                        self.status_info = None;
                     }
+                    Err(e) => self.status_info = Some(e.to_string()),
                 },
-                GameState::Results => {
-                    if self.flags.accept {
-                        self.state = GameState::Exit;
-                        self.flags.accept = false
+                (Some(id), None)
+                    if matches!(c, 'n' | 'e' | 's' | 'w')
+                        && world.resolve_plane_id(id).ok() == self.selected_plane() =>
+                {
+                    if let Ok(heading) = c.to_string().parse() {
+                        self.turn_selected(heading);
+                        return;
                     }
                 }
-                GameState::Exit => break,
+                _ => (),
             }
         }
-        Ok(())
+        self.input.push(c)
+    }
+
+    /// Split the number of the controller off the start of `input` if several players share
+    /// the planes, `None` if there is only one controller
+    fn split_controller<'a>(&self, input: &'a str) -> Result<(Option<u8>, &'a str), Error> {
+        let controllers = self.session.level().world().controllers();
+        if controllers == 1 {
+            return Ok((None, input));
+        }
+        let input = input.trim_start();
+        let split = input
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(input.len());
+        let (controller, command) = input.split_at(split);
+        match controller.parse::<u8>() {
+            Ok(controller) if controller < controllers => Ok((Some(controller), command)),
+            _ => Err(Error::NoController(controllers - 1)),
+        }
+    }
+
+    /// Select the plane after the selected one among the planes in a proximity conflict
+    fn select_next_conflict(&mut self) {
+        let world = self.session.level().world();
+        match roster::next_conflicting(world, self.selected_plane()) {
+            Some(id) => {
+                self.selected_plane = Some(id);
+                self.input = match self.selected_controller {
+                    Some(controller) => format!("{controller} {id}"),
+                    None => id.to_string(),
+                };
+            }
+            None => {
+                let tick = self.session.tick();
+                self.log_event(format!("{tick}: no planes in conflict"));
+            }
+        }
+    }
+
+    /// Turn the selected plane to `heading` at the height it is flying to
+    fn turn_selected(&mut self, heading: DirectionCardinal) {
+        self.vector_selected(|plane| (heading, plane.target_height));
+    }
+
+    /// Let the selected plane climb one level higher than it is flying to, or descend one
+    /// level lower if `climb` is false
+    ///
+    /// The height changes one level per move of the plane. Planes can only be told to go down
+    /// to the ground if they will be over an airport when they touch down, see
+    /// [`Command::Vector`].
+    fn climb_selected(&mut self, climb: bool) {
+        self.vector_selected(|plane| {
+            let height = if climb {
+                plane.target_height.saturating_add(1).min(MAX_HEIGHT)
+            } else {
+                plane.target_height.saturating_sub(1)
+            };
+            (plane.direction, height)
+        });
+    }
+
+    /// Give the selected plane the heading and height `vector` picks for it
+    fn vector_selected(&mut self, vector: impl FnOnce(&Plane) -> (DirectionCardinal, u8)) {
+        let tick = self.session.tick();
+        let Some(plane) = self.selected_plane() else {
+            self.log_event(format!("{tick}: select a plane first"));
+            return;
+        };
+        let world = self.session.level().world();
+        let result = world
+            .plane(plane)
+            .ok_or(Error::NoPlaneForID(plane))
+            .map(vector)
+            .map(|(heading, height)| Command::Vector {
+                plane,
+                heading,
+                height,
+            })
+            .and_then(|command| {
+                match self.selected_controller {
+                    None => self.session.apply_command(command)?,
+                    Some(controller) => self.session.apply_command_as(command, controller)?,
+                }
+                Ok(command)
+            });
+        match result {
+            Ok(command) => self.log_event(format!("{tick}: {command}")),
+            Err(e) => self.log_event(format!("{tick}: {e}")),
+        }
+    }
+
+    /// Parse and carry out the command the player typed
+    fn run_command(&mut self) {
+        let input = std::mem::take(&mut self.input);
+        let result = if input.trim_start().starts_with('+') {
+            input
+                .parse::<SpawnRequest>()
+                .and_then(|request| self.session.apply_spawn_request(request).map(|_| ()))
+        } else {
+            self.apply_typed_command(&input)
+        };
+        let tick = self.session.tick();
+        match result {
+            Ok(()) => self.log_event(format!("{tick}: {input}")),
+            Err(e) => self.log_event(format!("{tick}: {e}")),
+        }
+    }
+
+    /// Parse and carry out a command, for the controller it starts with if several players
+    /// share the planes
+    fn apply_typed_command(&mut self, input: &str) -> Result<(), Error> {
+        match self.split_controller(input)? {
+            (None, command) => self.session.apply_command(command.parse()?),
+            (Some(controller), command) => {
+                self.session.apply_command_as(command.parse()?, controller)
+            }
+        }
+    }
+
+    /// Add the events of a tick to the log shown below the map
+    fn log_report(&mut self, report: &TickReport) {
+        for spawn in &report.spawns {
+            self.log_event(format!("{}: {spawn}", report.tick));
+        }
+        for warning in &report.warnings {
+            self.log_event(format!("{}: {warning}", report.tick));
+        }
+        if self.flags.accessible {
+            self.alert_report(report);
+        }
+    }
+
+    /// Put the critical events of a tick into the alert panel
+    fn alert_report(&mut self, report: &TickReport) {
+        for warning in &report.warnings {
+            if matches!(
+                warning,
+                Warning::FuelCritical(..) | Warning::ConvergingLanding(..) | Warning::Proximity(..)
+            ) {
+                self.alert(format!("{}: {warning}", report.tick));
+            }
+        }
+        if let Some(outcome) = report.outcome {
+            self.alert(format!("{}: {outcome}", report.tick));
+        }
+    }
+
+    fn alert(&mut self, event: String) {
+        self.alerts.push_back(event);
+        while self.alerts.len() > ALERT_LENGTH {
+            self.alerts.pop_front();
+        }
+    }
+
+    fn log_event(&mut self, event: String) {
+        self.log.push_back(event);
+        while self.log.len() > LOG_LENGTH {
+            self.log.pop_front();
+        }
     }
 
     /// Renders the user interface.
@@ -85,34 +721,233 @@ impl App {
     /// - <https://github.com/ratatui/ratatui/tree/main/ratatui-widgets/examples>
     fn render(&mut self, frame: &mut Frame) {
         let whole_area = frame.area().inner(Margin::default());
-        let chunks =
-            Layout::vertical([Constraint::Length(frame.area().height), Constraint::Min(3)])
-                .split(whole_area);
-        let map_area = chunks[0];
-        let status_area = chunks[1];
-
-        let title = Line::from("Air Traffic Controller")
-            .bold()
-            .blue()
-            .centered();
-        let map: String = self.level.render();
+        let alert_height = if self.flags.accessible {
+            ALERT_LENGTH as u16 + 2
+        } else {
+            0
+        };
+        let chunks = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(alert_height),
+            Constraint::Length(LOG_LENGTH as u16 + 4),
+        ])
+        .split(whole_area);
+        let top_chunks = Layout::horizontal([Constraint::Min(0), Constraint::Length(ROSTER_WIDTH)])
+            .split(chunks[1]);
+        let map_area = top_chunks[0];
+        let roster_area = top_chunks[1];
+        let hud_area = chunks[0];
+        let alert_area = chunks[2];
+        let status_area = chunks[3];
+
+        let session = self.shown_session();
+        let tick_rate = (!self.flags.paused).then_some(TICK_RATE);
+        let hud = Hud::new(session, tick_rate).render(hud_area.width as usize);
+        frame.render_widget(Paragraph::new(Line::from(hud).bold()), hud_area);
+
+        let mut title = "Air Traffic Controller".to_string();
+        if self.state == GameState::Demo {
+            title.push_str(" (demo)");
+        }
+        if let Some(replay) = &self.replay {
+            title.push_str(&format!(
+                " (replay, tick {} of {})",
+                replay.tick(),
+                replay.last_tick()
+            ));
+        }
+        if self.heatmap.is_some() {
+            title.push_str(" (heatmap)");
+        }
+        if session.level().spawn_policy() == &SpawnPolicy::Manual {
+            title.push_str(" (practice)");
+        }
+        if self.fast_forward.is_some() {
+            title.push_str(" (fast forward)");
+        } else if self.flags.paused {
+            title.push_str(" (paused)");
+        }
+        if session.level().spawning_paused() {
+            title.push_str(" (spawning paused)");
+        }
+        if session.level().world().panic_holding() {
+            title.push_str(" (all holding)");
+        }
+        let title = Line::from(title).bold().fg(self.theme.title).centered();
+        let map: String = if self.flags.rulers {
+            session.level().render_with_rulers()
+        } else {
+            session.level().render()
+        };
+        let world = session.level().world();
+        let mut styles = match self.selected_plane() {
+            Some(id) => ui::ghost_styles(world, &self.theme, id, GHOST_TICKS),
+            None => Vec::new(),
+        };
+        if let Some(heatmap) = &self.heatmap {
+            styles.extend(ui::heatmap_styles(heatmap));
+        }
+        if self.flags.color_by_destination {
+            styles.extend(ui::destination_styles(world, &self.theme));
+        } else if world.controllers() > 1 {
+            styles.extend(ui::controller_styles(world, &self.theme));
+        }
+        let map = if self.flags.half_blocks {
+            ui::half_block_map(
+                world,
+                &self.theme,
+                &styles,
+                self.selected_plane(),
+                self.flags.colors,
+            )
+        } else {
+            ui::themed_map(world, &self.theme, &map, self.flags.rulers, &styles)
+        };
         frame.render_widget(
             Paragraph::new(map).block(Block::bordered().title(title)),
             map_area,
         );
+        let mut roster_lines: Vec<Line> = roster(world)
+            .iter()
+            .map(|entry| {
+                let fuel = Span::from(entry.fuel.clone());
+                let line = Line::from(vec![
+                    Span::from(format!(
+                        "{}{}{} -> {} ",
+                        entry.id,
+                        entry.height,
+                        self.flags.glyphs.heading(entry.direction),
+                        entry.destination,
+                    )),
+                    if entry.fuel_low {
+                        fuel.fg(self.theme.fuel_low)
+                    } else {
+                        fuel
+                    },
+                    Span::from(format!(
+                        "{} {}{}",
+                        entry.wind.map_or("", WindEffect::symbol),
+                        entry.orders,
+                        if entry.fuel_critical { " !" } else { "" }
+                    )),
+                ]);
+                if entry.conflict {
+                    line.fg(self.theme.conflict).bold()
+                } else if entry.fuel_critical {
+                    line.fg(self.theme.warning).bold()
+                } else {
+                    line.fg(self.theme.plane_color(entry.kind))
+                }
+            })
+            .collect();
+        if self.flags.color_by_destination {
+            roster_lines.push(Line::default());
+            roster_lines.extend(ui::destination_legend(world, &self.theme));
+        }
+        frame.render_widget(
+            Paragraph::new(roster_lines).block(Block::bordered().title("Planes")),
+            roster_area,
+        );
+
+        let mut status: Vec<Line> = Vec::new();
         if let Some(status_info) = self.status_info.as_ref() {
+            status.extend(
+                status_info
+                    .lines()
+                    .map(|l| Line::from(l.to_string()).bold()),
+            );
+        }
+        if self.state == GameState::Replay {
+            status.push(Line::from(format!("Jump to tick: {}", self.input)));
+        }
+        if self.state == GameState::Ongoing {
+            status.push(Line::from(format!("> {}", self.input)));
+            if let Some(id) = self.selected_plane() {
+                status.push(
+                    Line::from(format!(
+                        "  plane {id} selected, the arrow keys and n, e, s, w turn it, PageUp and PageDown climb and descend"
+                    ))
+                    .dim(),
+                );
+            }
+            if !self.input.is_empty() {
+                let suggestions = command::suggestions(world, &self.input);
+                status.push(Line::from(format!("  {}", suggestions.join(" | "))).dim());
+            }
+        }
+        if self.flags.destinations {
+            let world = session.level().world();
+            status.extend(
+                world
+                    .destinations()
+                    .into_iter()
+                    .filter_map(|d| world.describe_destination(d))
+                    .map(Line::from),
+            );
+        } else {
+            status.extend(self.log.iter().map(|l| Line::from(l.as_str())));
+        }
+        if self.flags.accessible {
+            status = status
+                .into_iter()
+                .map(|l| l.bold().white().on_black())
+                .collect();
+            let alerts: Vec<Line> = self
+                .alerts
+                .iter()
+                .map(|l| {
+                    Line::from(l.as_str())
+                        .bold()
+                        .fg(self.theme.alert_text)
+                        .bg(self.theme.alert)
+                })
+                .collect();
             frame.render_widget(
-                Paragraph::new(status_info.as_str()).block(Block::bordered()),
-                status_area,
-            )
+                Paragraph::new(alerts).block(Block::bordered().title("Alerts").bold()),
+                alert_area,
+            );
+        }
+        frame.render_widget(Paragraph::new(status).block(Block::bordered()), status_area);
+
+        if let Some(scroll) = self.help_scroll {
+            let help_area =
+                whole_area.inner(Margin::new(whole_area.width / 10, whole_area.height / 10));
+            let lines: Vec<Line> = help_lines(self.conflict_key)
+                .into_iter()
+                .map(Line::from)
+                .collect();
+            frame.render_widget(Clear, help_area);
+            frame.render_widget(
+                Paragraph::new(lines).scroll((scroll, 0)).block(
+                    Block::bordered()
+                        .title(Line::from("Help").bold().fg(self.theme.title).centered())
+                        .title_bottom("Up/Down to scroll, F1 or Esc to close"),
+                ),
+                help_area,
+            );
+        }
+        if !self.flags.colors {
+            ui::strip_colors(frame.buffer_mut());
         }
     }
 
+    /// Scroll the open help screen down by `lines`, or up if `lines` is negative
+    fn scroll_help(&mut self, lines: i32) {
+        let Some(scroll) = self.help_scroll else {
+            return;
+        };
+        let last = help_lines(self.conflict_key).len().saturating_sub(1) as i32;
+        self.help_scroll = Some((scroll as i32 + lines).clamp(0, last) as u16);
+    }
+
     /// Reads the crossterm events and updates the state of [`App`].
     ///
-    /// If your application needs to perform work in between handling events, you can use the
-    /// [`event::poll`] function to check if there are any events available with a timeout.
-    fn handle_crossterm_events(&mut self) -> Result<()> {
+    /// Waits at most `timeout` for an event, so the game keeps moving when no keys are pressed.
+    fn handle_crossterm_events(&mut self, timeout: Duration) -> Result<()> {
+        if !event::poll(timeout)? {
+            return Ok(());
+        }
         match event::read()? {
             // it's important to check KeyEventKind::Press to avoid handling key release events
             Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(key),
@@ -125,16 +960,129 @@ impl App {
 
     /// Handles the key events and updates the state of [`App`].
     fn on_key_event(&mut self, key: KeyEvent) {
+        self.idle_ticks = 0;
+        if self.help_scroll.is_some() {
+            match (key.modifiers, key.code) {
+                (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
+                (_, KeyCode::Esc | KeyCode::F(1) | KeyCode::Char('?')) => self.help_scroll = None,
+                (_, KeyCode::Up) => self.scroll_help(-1),
+                (_, KeyCode::Down) => self.scroll_help(1),
+                (_, KeyCode::PageUp) => self.scroll_help(-(HELP_PAGE as i32)),
+                (_, KeyCode::PageDown) => self.scroll_help(HELP_PAGE as i32),
+                _ => {}
+            }
+            return;
+        }
+        if self.state == GameState::Replay {
+            match (key.modifiers, key.code) {
+                (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
+                (_, KeyCode::Esc) => self.stop_replay(),
+                (_, KeyCode::Left) => {
+                    if let Some(replay) = self.replay.as_mut() {
+                        replay.step_back();
+                    }
+                }
+                (_, KeyCode::Right) => {
+                    if let Some(replay) = self.replay.as_mut() {
+                        replay.step_forward();
+                    }
+                }
+                (_, KeyCode::Char(c)) if c.is_ascii_digit() => self.input.push(c),
+                (_, KeyCode::Backspace) => {
+                    self.input.pop();
+                }
+                (_, KeyCode::Enter) => self.seek_replay(),
+                _ => {}
+            }
+            return;
+        }
         match (key.modifiers, key.code) {
             (_, KeyCode::Esc)
             | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
-            (_, KeyCode::Enter) => self.flags.accept = true,
+            _ if self.state == GameState::Demo => self.stop_demo(),
+            _ if self.fast_forward.is_some() => self.fast_forward = None,
+            (_, KeyCode::F(1) | KeyCode::Char('?')) => self.help_scroll = Some(0),
+            (_, KeyCode::Enter) => {
+                if self.state == GameState::Ongoing && !self.input.is_empty() {
+                    self.run_command();
+                } else {
+                    self.flags.accept = true
+                }
+            }
+            (_, KeyCode::Backspace) => {
+                self.input.pop();
+            }
+            (_, KeyCode::Tab) => {
+                let world = self.session.level().world();
+                if let Some(first) = command::suggestions(world, &self.input).into_iter().next() {
+                    self.input = first;
+                }
+            }
+            (_, KeyCode::Char('r' | 'R')) if self.state == GameState::Startup => self.resume(),
+            (_, KeyCode::Char('r' | 'R')) if self.state == GameState::Results => {
+                self.start_replay()
+            }
+            (_, code) if code == self.conflict_key && self.state == GameState::Ongoing => {
+                self.select_next_conflict()
+            }
+            (_, KeyCode::Up) if self.state == GameState::Ongoing => {
+                self.turn_selected(DirectionCardinal::North)
+            }
+            (_, KeyCode::Down) if self.state == GameState::Ongoing => {
+                self.turn_selected(DirectionCardinal::South)
+            }
+            (_, KeyCode::Left) if self.state == GameState::Ongoing => {
+                self.turn_selected(DirectionCardinal::West)
+            }
+            (_, KeyCode::Right) if self.state == GameState::Ongoing => {
+                self.turn_selected(DirectionCardinal::East)
+            }
+            (_, KeyCode::PageUp) if self.state == GameState::Ongoing => self.climb_selected(true),
+            (_, KeyCode::PageDown) if self.state == GameState::Ongoing => {
+                self.climb_selected(false)
+            }
+            (KeyModifiers::NONE | KeyModifiers::SHIFT, KeyCode::Char(c))
+                if self.state == GameState::Ongoing =>
+            {
+                self.type_char(c)
+            }
+            (_, KeyCode::F(2)) => self.flags.rulers = !self.flags.rulers,
+            (_, KeyCode::F(3)) => self.flags.destinations = !self.flags.destinations,
+            (_, KeyCode::F(5)) => self.flags.paused = !self.flags.paused,
+            (_, KeyCode::F(6)) => self.step(),
+            (_, KeyCode::F(11)) => self.fast_forward(),
+            (_, KeyCode::F(12)) => {
+                self.heatmap = match self.heatmap {
+                    Some(_) => None,
+                    None => Some(Heatmap::new(self.session.level().world())),
+                }
+            }
+            (_, KeyCode::F(4)) => self.toggle_panic_hold(),
+            (_, KeyCode::F(7)) => {
+                let level = self.session.level_mut();
+                level.set_spawning_paused(!level.spawning_paused());
+            }
+            (_, KeyCode::F(8)) => self.flags.accessible = !self.flags.accessible,
+            (_, KeyCode::F(9)) => {
+                self.flags.color_by_destination = !self.flags.color_by_destination
+            }
+            (_, KeyCode::F(10)) => self.flags.glyphs = self.flags.glyphs.toggled(),
             _ => {}
         }
     }
 
     /// Set running to false to quit the application.
+    ///
+    /// A game that is still going is saved, so it can be resumed next time.
     fn quit(&mut self) {
+        if self.state == GameState::Ongoing && !self.session.is_over() {
+            if let Err(e) = self.session.save(&self.save_path) {
+                error!(
+                    "could not save the game to {}: {e}",
+                    self.save_path.display()
+                );
+            }
+        }
         self.state = GameState::Exit;
     }
 }
@@ -145,6 +1093,127 @@ impl Default for App {
     }
 }
 
+/// A key from its name, like `f12`, `backtab` or a single character
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let lower = name.trim().to_ascii_lowercase();
+    match lower.as_str() {
+        "backtab" | "shift+tab" => return Some(KeyCode::BackTab),
+        "tab" => return Some(KeyCode::Tab),
+        _ => (),
+    }
+    if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+        return (1..=12).contains(&n).then_some(KeyCode::F(n));
+    }
+    let mut chars = name.trim().chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(KeyCode::Char(c)),
+        _ => None,
+    }
+}
+
+/// Everything the help screen says, the commands and then the keys
+///
+/// `conflict_key` is the key set with [`CONFLICT_KEY_ENV`].
+fn help_lines(conflict_key: KeyCode) -> Vec<String> {
+    let mut lines = vec![
+        "Commands start with the id of a plane, c stands for a second plane:".to_string(),
+        String::new(),
+    ];
+    lines.extend(command::help().into_iter().map(|line| format!("  {line}")));
+    lines.extend([String::new(), "Keys:".to_string(), String::new()]);
+    let mut keys: Vec<(String, &str)> = KEYS
+        .iter()
+        .map(|(key, description)| (key.to_string(), *description))
+        .collect();
+    keys.insert(
+        3,
+        (
+            conflict_key.to_string(),
+            "select the next plane that is too close to another",
+        ),
+    );
+    let width = keys
+        .iter()
+        .map(|(key, _)| key.len())
+        .max()
+        .unwrap_or_default();
+    lines.extend(
+        keys.iter()
+            .map(|(key, description)| format!("  {key:<width$}  {description}")),
+    );
+    lines
+}
+
+/// Apply the settings from the environment to a new or resumed game
+fn apply_settings(session: &mut GameSession) {
+    if let Ok(weights) = std::env::var(SCORING_ENV) {
+        match weights.parse() {
+            Ok(weights) => session.level_mut().set_score_weights(weights),
+            Err(e) => error!("{SCORING_ENV}: {e}"),
+        }
+    }
+    if let Ok(penalty) = std::env::var(COMMAND_PENALTY_ENV) {
+        match penalty.parse() {
+            Ok(penalty) => session.level_mut().set_command_penalty(penalty),
+            Err(e) => error!("{COMMAND_PENALTY_ENV}: {e}"),
+        }
+    }
+    if std::env::var_os(PRACTICE_ENV).is_some() {
+        session.level_mut().set_spawn_policy(SpawnPolicy::Manual);
+    }
+    let world = session.level_mut().world_mut();
+    if std::env::var_os(SAFETY_ENV).is_some() {
+        world.set_safety_checks(true);
+    }
+    if let Ok(controllers) = std::env::var(CONTROLLERS_ENV) {
+        match controllers.parse() {
+            Ok(controllers) => {
+                world.set_controllers(controllers);
+            }
+            Err(e) => error!("{CONTROLLERS_ENV}: {e}"),
+        }
+    }
+    if let Ok(unit) = std::env::var(FUEL_UNIT_ENV) {
+        match unit.parse() {
+            Ok(unit) => {
+                world.set_fuel_unit(unit);
+            }
+            Err(e) => error!("{FUEL_UNIT_ENV}: {e}"),
+        }
+    }
+    if let Ok(wind) = std::env::var(WIND_ENV) {
+        match wind.parse() {
+            Ok(wind) => {
+                world.set_wind(Some(wind));
+            }
+            Err(e) => error!("{WIND_ENV}: {e}"),
+        }
+    }
+}
+
+/// The level in the level file at `path`, or why it can not be played
+fn load_level(path: &Path) -> Result<Level, String> {
+    Level::load(path).map_err(|e| {
+        error!("could not load the level at {}: {e}", path.display());
+        format!("Could not load level {}: {e}", path.display())
+    })
+}
+
+/// The game saved at `path`, if there is one that can be resumed
+///
+/// A save that is corrupt or from an incompatible version is logged and ignored, the player
+/// just starts a new game then.
+fn find_saved_game(path: &Path) -> Option<GameSession> {
+    match GameSession::load(path) {
+        Ok(session) => Some(session),
+        Err(Error::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            error!("could not resume the game saved at {}: {e}", path.display());
+            None
+        }
+    }
+}
+
 fn setup_logging() {
     let logfile = std::fs::OpenOptions::new()
         .write(true)
@@ -168,11 +1237,612 @@ fn setup_logging() {
     trace!("Setup logging");
 }
 
+/// Make sure the terminal is usable again if the game panics
+///
+/// `restore` runs before the previously installed hook, so the panic message and backtrace are
+/// printed to a normal terminal instead of one still in raw mode. The panic is also logged.
+fn install_panic_hook(restore: impl Fn() + Send + Sync + 'static) {
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore();
+        error!("{info}");
+        previous_hook(info);
+    }));
+}
+
 fn main() -> color_eyre::Result<()> {
     setup_logging();
     color_eyre::install()?;
     let terminal = ratatui::init();
+    install_panic_hook(ratatui::restore);
     let result = App::new().run(terminal);
     ratatui::restore();
     result
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use super::*;
+
+    /// An app that saves the game and the high scores in its own temporary directory, so tests
+    /// never touch the files of the player
+    fn test_app(name: &str) -> App {
+        let dir = std::env::temp_dir().join(format!("atc-test-{}-app-{name}", std::process::id()));
+        App::with_paths(dir.join("autosave.toml"), dir.join("highscores.tsv"))
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_step_while_paused() {
+        let mut app = test_app("step_while_paused");
+        app.state = GameState::Ongoing;
+
+        // stepping does nothing while the game runs normally
+        app.on_key_event(key(KeyCode::F(6)));
+        assert_eq!(app.session.tick(), 0);
+
+        app.on_key_event(key(KeyCode::F(5)));
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(app.session.tick(), 0);
+        for _ in 0..7 {
+            app.on_key_event(key(KeyCode::F(6)));
+        }
+        assert_eq!(app.session.tick(), 7);
+
+        app.on_key_event(key(KeyCode::F(5)));
+        app.update();
+        assert_eq!(app.session.tick(), 8);
+    }
+
+    #[test]
+    fn test_fast_forward_stops_at_spawn() {
+        let mut reference = GameSession::new(Level::with_seed(11));
+        let first_spawn = loop {
+            let report = reference.step();
+            if report.has_events() {
+                assert!(!report.spawns.is_empty());
+                break report.tick;
+            }
+        };
+        assert!(first_spawn > 1 && first_spawn < FAST_FORWARD_MAX_TICKS as u64);
+
+        let mut app = test_app("fast_forward_stops_at_spawn");
+        app.state = GameState::Ongoing;
+        app.session.level_mut().set_seed(11);
+        // fast forwarding only works while paused
+        app.on_key_event(key(KeyCode::F(11)));
+        assert!(app.fast_forward.is_none());
+        app.on_key_event(key(KeyCode::F(5)));
+        app.on_key_event(key(KeyCode::F(11)));
+        while app.fast_forward.is_some() {
+            assert!(app.session.tick() < first_spawn);
+            assert_eq!(app.session.level().world().planes().count(), 0);
+            app.update();
+        }
+        assert_eq!(app.session.tick(), first_spawn);
+        assert_eq!(app.session.level().world().planes().count(), 1);
+
+        app.session.level_mut().set_spawning_paused(true);
+        app.session.level_mut().world_mut().clear_planes();
+        app.on_key_event(key(KeyCode::F(11)));
+        app.update();
+        app.on_key_event(key(KeyCode::Char('x')));
+        assert!(app.fast_forward.is_none());
+        assert!(app.input.is_empty());
+        assert_eq!(app.session.tick(), first_spawn + 1);
+
+        app.on_key_event(key(KeyCode::F(11)));
+        for _ in 0..FAST_FORWARD_MAX_TICKS {
+            app.update();
+        }
+        assert!(app.fast_forward.is_none());
+        assert_eq!(
+            app.session.tick(),
+            first_spawn + 1 + FAST_FORWARD_MAX_TICKS as u64
+        );
+    }
+
+    #[test]
+    fn test_replay_steps_and_seeks() {
+        let mut app = test_app("replay_steps_and_seeks");
+        app.state = GameState::Ongoing;
+        app.on_key_event(key(KeyCode::F(5)));
+        for _ in 0..6 {
+            app.on_key_event(key(KeyCode::F(6)));
+        }
+        app.state = GameState::Results;
+
+        app.on_key_event(key(KeyCode::Char('r')));
+        assert_eq!(app.state, GameState::Replay);
+        let tick = |app: &App| app.replay.as_ref().unwrap().tick();
+        assert_eq!(tick(&app), 0);
+        app.on_key_event(key(KeyCode::Right));
+        app.on_key_event(key(KeyCode::Right));
+        assert_eq!(tick(&app), 2);
+        assert_eq!(app.shown_session().tick(), 2);
+        app.on_key_event(key(KeyCode::Char('5')));
+        app.on_key_event(key(KeyCode::Enter));
+        assert_eq!(tick(&app), 5);
+        app.on_key_event(key(KeyCode::Left));
+        assert_eq!(tick(&app), 4);
+        app.on_key_event(key(KeyCode::Char('9')));
+        app.on_key_event(key(KeyCode::Enter));
+        assert_eq!(tick(&app), 4, "tick 9 was never played");
+        assert!(app.log.back().unwrap().contains("Tick 9"));
+
+        app.on_key_event(key(KeyCode::Esc));
+        assert_eq!(app.state, GameState::Results);
+        assert!(app.replay.is_none());
+        assert_eq!(app.shown_session().tick(), 6);
+    }
+
+    #[test]
+    fn test_typed_command() {
+        let mut app = test_app("typed_command");
+        app.state = GameState::Ongoing;
+        let spawn = app.session.level_mut().spawn_plane().unwrap();
+        let destination = app
+            .session
+            .level()
+            .world()
+            .plane(spawn.plane)
+            .unwrap()
+            .destination;
+        for c in format!("{} = {}", spawn.plane, spawn.plane).chars() {
+            app.on_key_event(key(KeyCode::Char(c)));
+        }
+        assert_eq!(app.input, format!("{} = {}", spawn.plane, spawn.plane));
+        app.on_key_event(key(KeyCode::Enter));
+        assert!(app.input.is_empty());
+        assert!(!app.flags.accept);
+        assert_eq!(
+            app.session
+                .level()
+                .world()
+                .plane(spawn.plane)
+                .unwrap()
+                .destination,
+            destination
+        );
+
+        for c in "x=".chars() {
+            app.on_key_event(key(KeyCode::Char(c)));
+        }
+        app.on_key_event(key(KeyCode::Enter));
+        assert!(app.log.back().unwrap().contains("Could not understand"));
+    }
+
+    #[test]
+    fn test_accessible_alerts() {
+        let mut app = test_app("accessible_alerts");
+        app.state = GameState::Ongoing;
+        app.flags.accessible = true;
+        app.session.level_mut().set_spawning_paused(true);
+        let spawn = app.session.level_mut().spawn_plane().unwrap();
+        let world = app.session.level_mut().world_mut();
+        let plane = world.plane_mut(spawn.plane).unwrap();
+        plane.spec.fuel = 2;
+
+        app.advance();
+        assert_eq!(app.alerts.len(), 1);
+        assert!(
+            app.alerts[0].contains("runs out of fuel"),
+            "{:?}",
+            app.alerts
+        );
+
+        app.flags.accessible = false;
+        app.alerts.clear();
+        app.advance();
+        assert!(app.alerts.is_empty());
+        assert_eq!(app.state, GameState::Results);
+        let _ = std::fs::remove_dir_all(app.scores_path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_demo_runs_until_key_press() {
+        let mut app = test_app("demo_runs_until_key_press");
+        for _ in 0..DEMO_IDLE_TICKS {
+            app.update();
+        }
+        assert_eq!(app.state, GameState::Demo);
+        for _ in 0..200 {
+            app.update();
+        }
+        assert_eq!(app.state, GameState::Demo);
+        assert!(app.demo.as_ref().unwrap().tick() > 0);
+        assert_eq!(app.session.tick(), 0);
+
+        app.on_key_event(key(KeyCode::Char('x')));
+        assert_eq!(app.state, GameState::Startup);
+        assert!(app.demo.is_none());
+        assert!(app.input.is_empty());
+        for _ in 1..DEMO_IDLE_TICKS {
+            app.update();
+        }
+        assert_eq!(app.state, GameState::Startup);
+    }
+
+    #[test]
+    fn test_help_screen() {
+        let mut app = test_app("help_screen");
+        app.state = GameState::Ongoing;
+        app.on_key_event(key(KeyCode::Char('?')));
+        assert_eq!(app.help_scroll, Some(0));
+        assert!(app.input.is_empty());
+        app.update();
+        assert_eq!(
+            app.session.tick(),
+            0,
+            "the game waits while the help is open"
+        );
+
+        app.on_key_event(key(KeyCode::Down));
+        assert_eq!(app.help_scroll, Some(1));
+        app.on_key_event(key(KeyCode::PageUp));
+        assert_eq!(app.help_scroll, Some(0));
+        for _ in 0..20 {
+            app.on_key_event(key(KeyCode::PageDown));
+        }
+        assert_eq!(
+            app.help_scroll,
+            Some(help_lines(app.conflict_key).len() as u16 - 1)
+        );
+
+        app.on_key_event(key(KeyCode::Esc));
+        assert_eq!(app.help_scroll, None);
+        assert_eq!(app.state, GameState::Ongoing);
+        app.update();
+        assert_eq!(app.session.tick(), 1);
+    }
+
+    #[test]
+    fn test_conflict_key_cycles_conflicting_planes() {
+        use base::plane::{Destination, Plane};
+        use base::world::{DirectionCardinal, PlaneKind};
+
+        let plane = |id, pos: [usize; 2]| {
+            Plane::new(
+                pos.into(),
+                DirectionCardinal::North,
+                PlaneKind::Jet,
+                id,
+                Destination::Exit(0),
+            )
+        };
+        let mut app = test_app("conflict_key_cycles_conflicting_planes");
+        app.state = GameState::Ongoing;
+        app.on_key_event(key(KeyCode::BackTab));
+        assert!(app.input.is_empty());
+        assert!(app
+            .log
+            .back()
+            .is_some_and(|event| event.ends_with("no planes in conflict")));
+
+        let world = app.session.level_mut().world_mut();
+        for (id, pos) in [('a', [1, 1]), ('b', [15, 1]), ('d', [1, 15])] {
+            world.insert_plane(plane(id, pos));
+        }
+        world.insert_plane(plane('x', [8, 8]));
+        world.insert_plane(plane('c', [7, 7]));
+        app.on_key_event(key(KeyCode::BackTab));
+        assert_eq!(app.input, "c");
+        app.on_key_event(key(KeyCode::BackTab));
+        assert_eq!(app.input, "x");
+        app.on_key_event(key(KeyCode::BackTab));
+        assert_eq!(app.input, "c");
+
+        app.conflict_key = KeyCode::F(9);
+        app.input.clear();
+        app.on_key_event(key(KeyCode::F(9)));
+        assert_eq!(app.input, "x", "the selection outlives the typed command");
+        assert!(help_lines(app.conflict_key)
+            .iter()
+            .any(|line| line.trim_start().starts_with("F9")));
+    }
+
+    #[test]
+    fn test_arrow_keys_turn_selected_plane() {
+        use base::plane::{Destination, Plane};
+        use base::world::PlaneKind;
+
+        let mut app = test_app("arrow_keys_turn_selected_plane");
+        app.state = GameState::Ongoing;
+        app.session.level_mut().world_mut().insert_plane(Plane::new(
+            [8, 8].into(),
+            DirectionCardinal::North,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        ));
+        app.on_key_event(key(KeyCode::Left));
+        assert!(app.log.back().unwrap().ends_with("select a plane first"));
+
+        app.on_key_event(key(KeyCode::Char('z')));
+        assert_eq!(app.status_info.as_deref(), Some("No Plane exists for ID z"));
+        assert_eq!(app.selected_plane(), None);
+        app.on_key_event(key(KeyCode::Backspace));
+
+        app.on_key_event(key(KeyCode::Char('a')));
+        assert_eq!(app.selected_plane(), Some('a'));
+        app.on_key_event(key(KeyCode::Right));
+        let plane = *app.session.level().world().plane('a').unwrap();
+        assert_eq!(plane.direction, DirectionCardinal::East);
+        assert_eq!(plane.pos, [8, 8].into(), "the plane turns on the next tick");
+        app.on_key_event(key(KeyCode::Down));
+        assert_eq!(
+            app.session.level().world().plane('a').unwrap().direction,
+            DirectionCardinal::South
+        );
+        assert_eq!(app.input, "a", "the plane stays selected");
+        assert_eq!(app.session.level().commands(), 2);
+        assert_eq!(app.status_info, None);
+
+        app.on_key_event(key(KeyCode::Char('w')));
+        assert_eq!(
+            app.session.level().world().plane('a').unwrap().direction,
+            DirectionCardinal::West
+        );
+        assert_eq!(app.input, "a", "compass letters are not typed");
+        app.on_key_event(key(KeyCode::Char('v')));
+        app.on_key_event(key(KeyCode::Char('n')));
+        assert_eq!(app.input, "avn", "only right after the id");
+        assert_eq!(app.session.level().commands(), 3);
+    }
+
+    #[test]
+    fn test_page_keys_change_height_of_selected_plane() {
+        use base::plane::Destination;
+        use base::world::PlaneKind;
+
+        let mut app = test_app("page_keys_change_height_of_selected_plane");
+        app.state = GameState::Ongoing;
+        app.flags.paused = true;
+        app.session.level_mut().world_mut().insert_plane(Plane::new(
+            [8, 8].into(),
+            DirectionCardinal::North,
+            PlaneKind::Small,
+            'a',
+            Destination::Exit(0),
+        ));
+        let plane = |app: &App| {
+            let world = app.session.level().world();
+            *world.plane(world.resolve_plane_id('a').unwrap()).unwrap()
+        };
+        assert_eq!(plane(&app).height, 7);
+        assert!(app.session.level().render().contains("a7"));
+
+        app.on_key_event(key(KeyCode::Char('a')));
+        for _ in 0..3 {
+            app.on_key_event(key(KeyCode::PageDown));
+        }
+        assert_eq!(plane(&app).target_height, 4);
+        assert_eq!(plane(&app).height, 7, "the height changes with the ticks");
+        // small planes move and change height every second tick
+        for _ in 0..2 {
+            app.on_key_event(key(KeyCode::F(6)));
+        }
+        assert!(app.session.level().render().contains("a6"));
+        for _ in 0..2 {
+            app.on_key_event(key(KeyCode::F(6)));
+        }
+        assert!(app.session.level().render().contains("a5"));
+
+        for _ in 0..4 {
+            app.on_key_event(key(KeyCode::PageDown));
+        }
+        assert_eq!(plane(&app).target_height, 1);
+        assert!(app.log.back().unwrap().ends_with("is not over an airport"));
+
+        for _ in 0..12 {
+            app.on_key_event(key(KeyCode::PageUp));
+        }
+        assert_eq!(plane(&app).target_height, MAX_HEIGHT);
+    }
+
+    #[test]
+    fn test_page_down_to_the_ground_only_onto_airport() {
+        use base::plane::Destination;
+        use base::world::PlaneKind;
+
+        let mut app = test_app("page_down_to_the_ground_only_onto_airport");
+        app.state = GameState::Ongoing;
+        app.flags.paused = true;
+        // the airport of the builtin level is at [5, 10]
+        for (id, x, height) in [('a', 2, 3), ('b', 5, 1)] {
+            let mut plane = Plane::new(
+                [x, 10].into(),
+                DirectionCardinal::East,
+                PlaneKind::Jet,
+                id,
+                Destination::Airport(0),
+            );
+            plane.height = height;
+            plane.target_height = 1;
+            app.session.level_mut().world_mut().insert_plane(plane);
+        }
+        let target = |app: &App, id| {
+            let world = app.session.level().world();
+            world
+                .plane(world.resolve_plane_id(id).unwrap())
+                .unwrap()
+                .target_height
+        };
+
+        app.on_key_event(key(KeyCode::Char('a')));
+        app.on_key_event(key(KeyCode::PageDown));
+        assert_eq!(target(&app, 'a'), 0);
+
+        // over the airport, but it moves past it before it is down
+        app.on_key_event(key(KeyCode::Backspace));
+        app.on_key_event(key(KeyCode::Char('b')));
+        app.on_key_event(key(KeyCode::PageDown));
+        assert_eq!(target(&app, 'b'), 1);
+        assert!(app.log.back().unwrap().ends_with("is not over an airport"));
+    }
+
+    #[test]
+    fn test_commands_of_several_controllers() {
+        use base::plane::Destination;
+        use base::world::PlaneKind;
+
+        let mut app = test_app("commands_of_several_controllers");
+        app.state = GameState::Ongoing;
+        let world = app.session.level_mut().world_mut();
+        world.set_controllers(2);
+        for (id, controller) in [('a', 0), ('b', 1)] {
+            let mut plane = Plane::new(
+                [8, 8].into(),
+                DirectionCardinal::North,
+                PlaneKind::Jet,
+                id,
+                Destination::Exit(0),
+            );
+            plane.controller = controller;
+            world.insert_plane(plane);
+        }
+        let target = |app: &App, id| app.session.level().world().plane(id).unwrap().target_height;
+
+        for (input, error) in [
+            ("a v n 5", Error::NoController(1)),
+            ("2 a v n 5", Error::NoController(1)),
+            ("1 a v n 5", Error::NotYourPlane('a', 1)),
+        ] {
+            app.input = input.to_string();
+            app.run_command();
+            assert!(
+                app.log.back().unwrap().ends_with(&error.to_string()),
+                "{input}"
+            );
+        }
+        assert_eq!(target(&app, 'a'), base::plane::START_HEIGHT);
+
+        for (input, id) in [("0 a v n 5", 'a'), ("1b v n 5", 'b')] {
+            app.input = input.to_string();
+            app.run_command();
+            assert_eq!(target(&app, id), 5, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_arrow_keys_steer_only_planes_of_the_controller() {
+        use base::plane::Destination;
+        use base::world::PlaneKind;
+
+        let mut app = test_app("arrow_keys_steer_only_planes_of_the_controller");
+        app.state = GameState::Ongoing;
+        let world = app.session.level_mut().world_mut();
+        world.set_controllers(2);
+        world.insert_plane(Plane::new(
+            [8, 8].into(),
+            DirectionCardinal::North,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        ));
+        let direction = |app: &App| app.session.level().world().plane('a').unwrap().direction;
+
+        for c in "1 a".chars() {
+            app.on_key_event(key(KeyCode::Char(c)));
+        }
+        assert_eq!(app.selected_plane(), Some('a'));
+        app.on_key_event(key(KeyCode::Right));
+        assert_eq!(direction(&app), DirectionCardinal::North);
+        assert!(app
+            .log
+            .back()
+            .unwrap()
+            .ends_with(&Error::NotYourPlane('a', 1).to_string()));
+
+        app.input.clear();
+        for c in "0 a".chars() {
+            app.on_key_event(key(KeyCode::Char(c)));
+        }
+        app.on_key_event(key(KeyCode::Right));
+        assert_eq!(direction(&app), DirectionCardinal::East);
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("shift+tab"), Some(KeyCode::BackTab));
+        assert_eq!(parse_key("F12"), Some(KeyCode::F(12)));
+        assert_eq!(parse_key("x"), Some(KeyCode::Char('x')));
+        assert_eq!(parse_key("f13"), None);
+        assert_eq!(parse_key("ctrl+x"), None);
+    }
+
+    #[test]
+    fn test_unplayable_level_is_shown_on_start_screen() {
+        let path = std::env::temp_dir().join(format!("atc-test-{}-level.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "version = 2\nname = \"empty\"\ndifficulty = \"easy\"\nwidth = 10\nheight = 10\n",
+        )
+        .unwrap();
+        let e = load_level(&path).unwrap_err();
+        assert!(e.contains(&Error::NoSpawnPoints.to_string()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut app = test_app("unplayable_level_is_shown_on_start_screen");
+        app.level_error = Some(e.clone());
+        assert!(app.startup_info().starts_with(&e));
+        assert_eq!(app.session.level().get_name(), "default");
+    }
+
+    #[test]
+    fn test_quit_saves_game_to_resume() {
+        let mut app = test_app("quit_saves_game_to_resume");
+        assert!(app.saved.is_none());
+        app.state = GameState::Ongoing;
+        app.session.level_mut().spawn_plane().unwrap();
+        for _ in 0..3 {
+            app.advance();
+        }
+        app.on_key_event(key(KeyCode::Esc));
+        assert_eq!(app.state, GameState::Exit);
+        assert!(app.save_path.exists());
+
+        let mut resumed = test_app("quit_saves_game_to_resume");
+        assert_eq!(resumed.save_path, app.save_path);
+        assert!(resumed.saved.is_some());
+        resumed.on_key_event(key(KeyCode::Char('r')));
+        assert_eq!(resumed.state, GameState::Ongoing);
+        assert_eq!(resumed.session.tick(), 3);
+        assert_eq!(
+            resumed.session.level().world().traffic(),
+            app.session.level().world().traffic()
+        );
+        assert_eq!(
+            resumed.session.level().render(),
+            app.session.level().render()
+        );
+
+        std::fs::write(&app.save_path, "version = 1\ntick = ").unwrap();
+        assert!(find_saved_game(&app.save_path).is_none());
+        let _ = std::fs::remove_dir_all(app.save_path.parent().unwrap());
+        assert!(find_saved_game(&app.save_path).is_none());
+    }
+
+    #[test]
+    fn test_panic_hook_restores_terminal() {
+        let restored = Arc::new(AtomicBool::new(false));
+        let flag = restored.clone();
+        install_panic_hook(move || flag.store(true, Ordering::SeqCst));
+
+        let result = std::panic::catch_unwind(|| panic!("simulated crash"));
+        let _ = std::panic::take_hook();
+
+        assert!(result.is_err());
+        assert!(restored.load(Ordering::SeqCst));
+    }
+}
@@ -1,27 +1,151 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
 use crate::{
     error::Error,
     world::{DirectionCardinal, PlaneKind, Pos},
 };
 
+// Altitudes go from 0 (on the ground) up to MAX_HEIGHT, one level is a thousand feet
+
 pub const START_HEIGHT: u8 = 7;
 pub const EXIT_HEIGHT: u8 = 9;
+/// No plane can fly higher than this
+pub const MAX_HEIGHT: u8 = 9;
+/// The lowest height a plane flies at without landing
+pub const MIN_FLIGHT_HEIGHT: u8 = 1;
+/// Simulated seconds that pass in one tick, for showing fuel as flight time
+pub const SECONDS_PER_TICK: usize = 30;
+/// Below this share of a full tank, in percent, the fuel of a plane is shown as low
+pub const FUEL_LOW_PERCENT: u8 = 20;
+/// A turn rate of half a circle, planes with it take any heading at once
+pub const FREE_TURN_RATE: u8 = 4;
+
+/// How the fuel a plane has left is shown to the player
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FuelUnit {
+    /// Ticks until the tank is empty, like `12t`
+    #[default]
+    Ticks,
+    /// Simulated flight time in minutes and seconds, like `6:00`
+    Minutes,
+    /// Share of a full tank, like `10%`
+    Percent,
+}
+
+/// How a kind of plane behaves
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaneSpec {
+    /// Ticks the plane can fly before it runs out of fuel
+    pub fuel: usize,
+    /// The plane moves one tile every this many ticks
+    pub cadence: usize,
+    /// Height the plane enters the map at, unless the exit says otherwise
+    pub start_height: u8,
+    /// Height the plane needs to leave the map through an exit
+    pub exit_height: u8,
+    /// How many eighths of a full circle the plane can turn in one move, it takes a new
+    /// heading over several moves if it has to turn further
+    #[serde(default = "free_turn_rate")]
+    pub turn_rate: u8,
+}
+
+/// Saves from before the turn rate turned planes freely
+fn free_turn_rate() -> u8 {
+    FREE_TURN_RATE
+}
+
+/// The [`PlaneSpec`] of every [`PlaneKind`], levels can change these to balance their game
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PlaneSpecs {
+    pub small: PlaneSpec,
+    pub jet: PlaneSpec,
+}
+
+/// Something a plane was told to do
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Order {
+    /// Fly in this direction
+    Heading(DirectionCardinal),
+    /// Climb or descend to this height
+    Height(u8),
+    /// Keep turning towards this tile until the plane is there or gets a new heading
+    DirectTo(Pos),
+}
+
+/// When a queued [`Order`] is carried out
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    /// When the plane flies over the beacon with this id
+    AtBeacon(u8),
+    /// After this many more ticks
+    InTicks(usize),
+}
+
+/// An [`Order`] waiting for its [`Trigger`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedOrder {
+    pub trigger: Trigger,
+    pub order: Order,
+}
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Destination {
     Exit(u8),
     Airport(u8),
 }
 
-#[derive(Copy, Clone, Debug)]
+/// A plane in the air
+///
+/// Planes compare equal field by field, so two copies of a plane are equal until one of them
+/// moves on without the other. Compare the [`id`](Plane::id)s to find out whether two values are
+/// the same plane, ids are unique among the planes in the air.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Plane {
     pub pos: Pos,
     pub height: u8,
+    /// The plane climbs or descends one level per move until it reaches this height
+    pub target_height: u8,
     pub direction: DirectionCardinal,
     pub kind: PlaneKind,
+    pub spec: PlaneSpec,
     pub id: char,
     pub ticks: usize,
+    /// Fuel burned on top of the one unit per tick, for example by diagonal moves
+    pub extra_fuel_burned: usize,
+    /// Fuel not burned thanks to a tailwind, see [`Wind`](crate::wind::Wind)
+    #[serde(default)]
+    pub fuel_saved: usize,
+    /// The plane is on the ground at the airport it takes off from and does not land there,
+    /// see [`World::spawn_plane_at_airport`](crate::world::World::spawn_plane_at_airport)
+    #[serde(default)]
+    pub taking_off: bool,
     pub destination: Destination,
+    /// The plane is still in its spawn immunity and is ignored by collision, wall, exit and
+    /// landing checks, see [`World::set_spawn_immunity`](crate::world::World::set_spawn_immunity)
     pub just_spawned: bool,
+    /// The plane is in a [slow zone](crate::world::WorldTile::SlowZone) and moves at landing speed
+    pub slowed: bool,
+    /// The player allowed the plane to leave or land, only matters if the world requires
+    /// clearance
+    pub cleared: bool,
+    /// The plane circles where it is until it gets a new heading
+    pub holding: bool,
+    /// The player in charge of the plane when several share the map, see
+    /// [`World::set_controllers`](crate::world::World::set_controllers)
+    pub controller: u8,
+    /// The tile the plane turns towards on its own, see [`Order::DirectTo`]
+    #[serde(default)]
+    pub direct_to: Option<Pos>,
+    /// The heading the plane was told to take and is still turning to, see
+    /// [`PlaneSpec::turn_rate`]
+    #[serde(default)]
+    pub turning_to: Option<DirectionCardinal>,
 }
 
 impl Plane {
@@ -31,87 +155,442 @@ impl Plane {
         kind: PlaneKind,
         id: char,
         destination: Destination,
+    ) -> Self {
+        Self::with_spec(
+            pos,
+            direction,
+            kind,
+            PlaneSpec::default_for(kind),
+            id,
+            destination,
+        )
+    }
+
+    pub fn with_spec(
+        pos: Pos,
+        direction: DirectionCardinal,
+        kind: PlaneKind,
+        spec: PlaneSpec,
+        id: char,
+        destination: Destination,
     ) -> Self {
         Self {
             pos,
-            height: START_HEIGHT,
+            height: spec.start_height,
+            target_height: spec.start_height,
             direction,
             kind,
+            spec,
             id: match kind {
                 PlaneKind::Small => id.to_ascii_uppercase(),
                 PlaneKind::Jet => id.to_ascii_lowercase(),
             },
             ticks: 0,
+            extra_fuel_burned: 0,
+            fuel_saved: 0,
+            taking_off: false,
             destination,
             just_spawned: true,
+            slowed: false,
+            cleared: false,
+            holding: false,
+            controller: 0,
+            direct_to: None,
+            turning_to: None,
         }
     }
 
-    /// Err if no fiel left on plane
-    pub fn tick(&mut self) -> Result<(), ()> {
+    /// Err if no fuel left on plane
+    pub fn tick(&mut self) -> Result<(), Error> {
         self.ticks += 1;
 
         if self.out_of_fuel() {
-            return Err(());
+            return Err(Error::PlaneOutOfFuel(self.id));
         }
 
         if self.moves_this_tick() {
-            self.next_pos();
+            if !self.holding {
+                if let Err(e) = self.next_pos() {
+                    warn!("{e}");
+                }
+                self.turn();
+            }
+            self.change_height();
+            self.taking_off &= self.height == 0;
         }
 
-        if self.ticks == 2 {
-            self.just_spawned = false;
+        Ok(())
+    }
+
+    /// Carry out an order right away
+    pub fn apply(&mut self, order: Order) -> Result<(), Error> {
+        match order {
+            Order::Heading(direction) => {
+                self.turning_to = Some(direction);
+                self.turn();
+                self.holding = false;
+                self.direct_to = None;
+            }
+            Order::Height(height) => self.set_target_height(height)?,
+            Order::DirectTo(pos) => {
+                self.direct_to = Some(pos);
+                self.turning_to = None;
+                self.holding = false;
+            }
         }
+        Ok(())
+    }
 
+    /// Turn towards the heading the plane was told to take, by at most its
+    /// [turn rate](PlaneSpec::turn_rate)
+    fn turn(&mut self) {
+        if let Some(heading) = self.turning_to {
+            self.direction = self.direction.turn_towards(heading, self.spec.turn_rate);
+            if self.direction == heading {
+                self.turning_to = None;
+            }
+        }
+    }
+
+    /// Tell the plane to climb or descend to `height`
+    ///
+    /// Heights above [`MAX_HEIGHT`] are rejected.
+    pub fn set_target_height(&mut self, height: u8) -> Result<(), Error> {
+        if height > MAX_HEIGHT {
+            return Err(Error::HeightTooHigh(height, MAX_HEIGHT));
+        }
+        self.target_height = height;
         Ok(())
     }
+
+    fn change_height(&mut self) {
+        if self.height < self.target_height {
+            self.height = (self.height + 1).min(MAX_HEIGHT);
+        } else if self.height > self.target_height {
+            self.height -= 1;
+        }
+    }
+
     fn out_of_fuel(&self) -> bool {
-        self.ticks
-            >= match self.kind {
-                PlaneKind::Jet => 120,
-                PlaneKind::Small => 50,
-            }
+        self.fuel_left() == 0
+    }
+
+    /// How much fuel the plane has left, one unit lasts one tick
+    ///
+    /// Fuel saved by a tailwind never fills the tank above its capacity.
+    pub fn fuel_left(&self) -> usize {
+        (self.spec.fuel + self.fuel_saved)
+            .saturating_sub(self.ticks + self.extra_fuel_burned)
+            .min(self.spec.fuel)
+    }
+
+    /// The fuel left as a share of a full tank, from 0 to 100 percent
+    pub fn fuel_remaining(&self) -> u8 {
+        (self.fuel_left() * 100)
+            .checked_div(self.spec.fuel)
+            .unwrap_or(0) as u8
+    }
+
+    /// The plane has less than [`FUEL_LOW_PERCENT`] of its fuel left
+    pub fn fuel_low(&self) -> bool {
+        self.fuel_remaining() < FUEL_LOW_PERCENT
     }
-    fn next_pos(&mut self) -> Result<(), Error> {
-        fn do_stuff(p: &mut Plane) -> Option<()> {
-            match p.direction {
-                DirectionCardinal::North => p.pos.y = p.pos.y.checked_sub(1)?,
-                DirectionCardinal::NorthEast => {
-                    p.pos.y = p.pos.y.checked_sub(1)?;
-                    p.pos.x = p.pos.x.checked_add(1)?;
-                }
-                DirectionCardinal::NorthWest => {
-                    p.pos.y = p.pos.y.checked_sub(1)?;
-                    p.pos.x = p.pos.x.checked_sub(1)?;
-                }
-                DirectionCardinal::South => p.pos.y = p.pos.y.checked_add(1)?,
-                DirectionCardinal::SouthEast => {
-                    p.pos.y = p.pos.y.checked_add(1)?;
-                    p.pos.x = p.pos.x.checked_add(1)?;
-                }
-                DirectionCardinal::SouthWest => {
-                    p.pos.y = p.pos.y.checked_add(1)?;
-                    p.pos.x = p.pos.x.checked_sub(1)?;
-                }
-                DirectionCardinal::West => p.pos.x = p.pos.x.checked_sub(1)?,
-                DirectionCardinal::East => p.pos.x = p.pos.x.checked_add(1)?,
-            };
 
-            Some(())
+    /// The plane runs out of fuel on its next tick unless it lands or leaves now
+    pub fn fuel_critical(&self) -> bool {
+        self.fuel_left() <= 1
+    }
+
+    /// The fuel left, to be shown in `unit`
+    pub fn fuel_reading(&self, unit: FuelUnit) -> FuelReading {
+        FuelReading {
+            left: self.fuel_left(),
+            capacity: self.spec.fuel,
+            unit,
         }
-        if do_stuff(self).is_none() {
-            Err(Error::PlaneNextPosBad(self.id))
+    }
+
+    fn next_pos(&mut self) -> Result<(), Error> {
+        self.pos = self
+            .pos
+            .step(self.direction)
+            .ok_or(Error::PlaneNextPosBad(self.id))?;
+        Ok(())
+    }
+
+    /// Every how many ticks the plane moves one tile
+    pub fn cadence(&self) -> usize {
+        let base = self.spec.cadence;
+        if self.slowed {
+            base * 2
         } else {
-            Ok(())
+            base
         }
     }
 
-    fn moves_this_tick(&self) -> bool {
-        self.ticks
-            % match self.kind {
-                PlaneKind::Jet => 1,
-                PlaneKind::Small => 2,
+    /// Whether the plane moved on its last [`tick`](Plane::tick)
+    pub(crate) fn moves_this_tick(&self) -> bool {
+        self.ticks.is_multiple_of(self.cadence())
+    }
+}
+
+impl PlaneSpec {
+    pub const fn default_for(kind: PlaneKind) -> Self {
+        match kind {
+            PlaneKind::Small => PlaneSpec {
+                fuel: 50,
+                cadence: 2,
+                start_height: START_HEIGHT,
+                exit_height: EXIT_HEIGHT,
+                turn_rate: FREE_TURN_RATE,
+            },
+            PlaneKind::Jet => PlaneSpec {
+                fuel: 120,
+                cadence: 1,
+                start_height: START_HEIGHT,
+                exit_height: EXIT_HEIGHT,
+                turn_rate: FREE_TURN_RATE,
+            },
+        }
+    }
+}
+
+/// The fuel a plane has left, displayed in its [`FuelUnit`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FuelReading {
+    pub left: usize,
+    pub capacity: usize,
+    pub unit: FuelUnit,
+}
+
+impl Display for FuelReading {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.unit.format(self.left, self.capacity))
+    }
+}
+
+impl FuelUnit {
+    /// Show `left` units of fuel of a tank that holds `capacity` units
+    pub fn format(self, left: usize, capacity: usize) -> String {
+        match self {
+            Self::Ticks => format!("{left}t"),
+            Self::Minutes => {
+                let seconds = left * SECONDS_PER_TICK;
+                format!("{}:{:02}", seconds / 60, seconds % 60)
             }
-            == 0
+            Self::Percent => format!("{}%", (left * 100).checked_div(capacity).unwrap_or(0)),
+        }
+    }
+}
+
+impl FromStr for FuelUnit {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ticks" | "t" => Ok(Self::Ticks),
+            "minutes" | "m" => Ok(Self::Minutes),
+            "percent" | "%" => Ok(Self::Percent),
+            _ => Err(Error::UnknownFuelUnit(s.to_string())),
+        }
+    }
+}
+
+impl PlaneSpecs {
+    pub fn get(&self, kind: PlaneKind) -> &PlaneSpec {
+        match kind {
+            PlaneKind::Small => &self.small,
+            PlaneKind::Jet => &self.jet,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: PlaneKind) -> &mut PlaneSpec {
+        match kind {
+            PlaneKind::Small => &mut self.small,
+            PlaneKind::Jet => &mut self.jet,
+        }
+    }
+}
+
+impl Default for PlaneSpecs {
+    fn default() -> Self {
+        Self {
+            small: PlaneSpec::default_for(PlaneKind::Small),
+            jet: PlaneSpec::default_for(PlaneKind::Jet),
+        }
+    }
+}
+
+impl Display for Destination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exit(eid) => write!(f, "exit {eid}"),
+            Self::Airport(aid) => write!(f, "airport {aid}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn plane() -> Plane {
+        Plane::new(
+            [5, 5].into(),
+            DirectionCardinal::East,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        )
+    }
+
+    #[test]
+    fn test_plane_equality() {
+        let a = plane();
+        let copy = a;
+        assert_eq!(a, copy);
+        let mut b = plane();
+        b.id = 'b';
+        assert_ne!(a, b);
+
+        let mut moved = copy;
+        moved.tick().unwrap();
+        assert_ne!(a, moved);
+        assert_eq!(a.id, moved.id);
+    }
+
+    #[test]
+    fn test_plane_climb_stops_at_ceiling() {
+        let mut plane = plane();
+        plane.set_target_height(MAX_HEIGHT).unwrap();
+        let mut heights = Vec::new();
+        for _ in 0..4 {
+            plane.tick().unwrap();
+            heights.push(plane.height);
+        }
+        assert_eq!(heights, vec![8, 9, 9, 9]);
+    }
+
+    #[test]
+    fn test_plane_spec_changes_behavior() {
+        let spec = PlaneSpec {
+            fuel: 4,
+            cadence: 3,
+            ..PlaneSpec::default_for(PlaneKind::Small)
+        };
+        let mut plane = Plane::with_spec(
+            [5, 5].into(),
+            DirectionCardinal::East,
+            PlaneKind::Small,
+            spec,
+            'a',
+            Destination::Exit(0),
+        );
+        let mut xs = Vec::new();
+        for _ in 0..3 {
+            plane.tick().unwrap();
+            xs.push(plane.pos.x);
+        }
+        assert_eq!(xs, vec![5, 5, 6]);
+        assert!(matches!(plane.tick(), Err(Error::PlaneOutOfFuel(_))));
+    }
+
+    #[test]
+    fn test_plane_turn_rate_spreads_turns_over_moves() {
+        let mut free = plane();
+        free.apply(Order::Heading(DirectionCardinal::West)).unwrap();
+        assert_eq!(free.direction, DirectionCardinal::West);
+
+        let mut slow = plane();
+        slow.spec.turn_rate = 1;
+        slow.apply(Order::Heading(DirectionCardinal::West)).unwrap();
+        let mut headings = vec![slow.direction];
+        for _ in 0..4 {
+            slow.tick().unwrap();
+            headings.push(slow.direction);
+        }
+        assert_eq!(
+            headings,
+            vec![
+                DirectionCardinal::SouthEast,
+                DirectionCardinal::South,
+                DirectionCardinal::SouthWest,
+                DirectionCardinal::West,
+                DirectionCardinal::West,
+            ]
+        );
+        assert_eq!(slow.turning_to, None);
+        assert_eq!(slow.pos, [4, 8].into(), "it flew along the turn");
+
+        slow.apply(Order::Heading(DirectionCardinal::NorthEast))
+            .unwrap();
+        assert_eq!(
+            slow.direction,
+            DirectionCardinal::NorthWest,
+            "the shorter way"
+        );
+    }
+
+    #[test]
+    fn test_plane_fuel_critical_one_tick_before_empty() {
+        let mut plane = plane();
+        plane.spec.fuel = 4;
+        let mut critical = Vec::new();
+        while plane.tick().is_ok() {
+            critical.push(plane.fuel_critical());
+        }
+        assert_eq!(critical, vec![false, false, true]);
+    }
+
+    #[test]
+    fn test_plane_climb_above_ceiling_rejected() {
+        let mut plane = plane();
+        assert!(matches!(
+            plane.set_target_height(MAX_HEIGHT + 1),
+            Err(Error::HeightTooHigh(10, MAX_HEIGHT))
+        ));
+        assert_eq!(plane.target_height, START_HEIGHT);
+        plane.tick().unwrap();
+        assert_eq!(plane.height, START_HEIGHT);
+    }
+
+    #[test]
+    fn test_plane_fuel_remaining() {
+        let mut plane = plane();
+        plane.spec.fuel = 50;
+        assert_eq!(plane.fuel_remaining(), 100);
+        assert!(!plane.fuel_low());
+
+        plane.ticks = 40;
+        assert_eq!(plane.fuel_remaining(), 20);
+        assert!(!plane.fuel_low());
+        plane.ticks = 41;
+        assert_eq!(plane.fuel_remaining(), 18);
+        assert!(plane.fuel_low());
+
+        plane.ticks = 60;
+        assert_eq!(plane.fuel_remaining(), 0);
+        plane.spec.fuel = 0;
+        assert_eq!(plane.fuel_remaining(), 0);
+    }
+
+    #[test]
+    fn test_plane_fuel_units() {
+        let mut plane = plane();
+        plane.spec.fuel = 40;
+        plane.ticks = 27;
+        assert_eq!(plane.fuel_reading(FuelUnit::Ticks).to_string(), "13t");
+        assert_eq!(plane.fuel_reading(FuelUnit::Minutes).to_string(), "6:30");
+        assert_eq!(plane.fuel_reading(FuelUnit::Percent).to_string(), "32%");
+
+        plane.ticks = 40;
+        assert_eq!(plane.fuel_reading(FuelUnit::Ticks).to_string(), "0t");
+        assert_eq!(plane.fuel_reading(FuelUnit::Minutes).to_string(), "0:00");
+        assert_eq!(plane.fuel_reading(FuelUnit::Percent).to_string(), "0%");
+        assert_eq!(FuelUnit::Percent.format(0, 0), "0%");
+
+        assert_eq!("Minutes".parse::<FuelUnit>().unwrap(), FuelUnit::Minutes);
+        assert_eq!("%".parse::<FuelUnit>().unwrap(), FuelUnit::Percent);
+        assert!("liters".parse::<FuelUnit>().is_err());
     }
 }
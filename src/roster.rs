@@ -0,0 +1,246 @@
+use crate::{
+    plane::{Destination, FuelUnit, Plane, QueuedOrder, Trigger},
+    wind::WindEffect,
+    world::{DirectionCardinal, PlaneKind, World},
+};
+
+/// One line of the roster, the list of all planes next to the map
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RosterEntry {
+    pub id: char,
+    pub kind: PlaneKind,
+    pub height: u8,
+    pub direction: DirectionCardinal,
+    pub destination: Destination,
+    /// The plane is too close to another plane
+    pub conflict: bool,
+    /// The plane runs out of fuel on its next tick
+    pub fuel_critical: bool,
+    /// The plane has little fuel left, see [`Plane::fuel_low`]
+    pub fuel_low: bool,
+    /// The fuel the plane has left, in the [`FuelUnit`] of the world
+    pub fuel: String,
+    /// Short note about the orders the plane still has to carry out, see [`orders_annotation`]
+    pub orders: String,
+    /// Whether the wind helps or hinders the plane on its heading, None in calm air
+    pub wind: Option<WindEffect>,
+}
+
+impl From<&Plane> for RosterEntry {
+    fn from(plane: &Plane) -> Self {
+        RosterEntry {
+            id: plane.id,
+            kind: plane.kind,
+            height: plane.height,
+            direction: plane.direction,
+            destination: plane.destination,
+            conflict: false,
+            fuel_critical: plane.fuel_critical(),
+            fuel_low: plane.fuel_low(),
+            fuel: plane.fuel_reading(FuelUnit::default()).to_string(),
+            orders: String::new(),
+            wind: None,
+        }
+    }
+}
+
+/// Describe the queued orders of a plane in a few characters
+///
+/// An idle plane gets an empty string. Otherwise this shows the trigger of the next order,
+/// `@b1` for beacon 1 or `@3t` for in three ticks, followed by `+n` if `n` more orders wait
+/// after it.
+pub fn orders_annotation(orders: &[QueuedOrder]) -> String {
+    let Some(next) = orders.first() else {
+        return String::new();
+    };
+    let mut buf = match next.trigger {
+        Trigger::AtBeacon(bid) => format!("@b{bid}"),
+        Trigger::InTicks(ticks) => format!("@{ticks}t"),
+    };
+    if orders.len() > 1 {
+        buf.push_str(&format!("+{}", orders.len() - 1));
+    }
+    buf
+}
+
+fn entry(world: &World, plane: &Plane, conflict: bool) -> RosterEntry {
+    RosterEntry {
+        conflict,
+        fuel: plane.fuel_reading(world.fuel_unit()).to_string(),
+        orders: orders_annotation(world.orders(plane.id)),
+        wind: world.wind().map(|wind| wind.effect(plane.direction)),
+        ..plane.into()
+    }
+}
+
+/// List all planes in the world, conflicts first
+///
+/// Planes in a proximity conflict come first, with the planes of each conflicting pair next to
+/// each other. The other planes follow ordered by id.
+pub fn roster(world: &World) -> Vec<RosterEntry> {
+    let mut entries: Vec<RosterEntry> = conflicting_planes(world)
+        .into_iter()
+        .filter_map(|id| world.plane(id))
+        .map(|plane| entry(world, plane, true))
+        .collect();
+
+    let mut rest: Vec<RosterEntry> = world
+        .planes()
+        .filter(|p| !entries.iter().any(|e| e.id == p.id))
+        .map(|p| entry(world, p, false))
+        .collect();
+    rest.sort_by_key(|e| e.id);
+    entries.extend(rest);
+    entries
+}
+
+/// Ids of the planes in a proximity conflict, in the order of the [`roster`]
+pub fn conflicting_planes(world: &World) -> Vec<char> {
+    let mut ids: Vec<char> = Vec::new();
+    for (a, b) in world.proximity_conflicts() {
+        for id in [a, b] {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    ids
+}
+
+/// The conflicting plane after `current`, to jump from one problem to the next
+///
+/// Starts over at the first conflicting plane after the last one, or if `current` is not in a
+/// conflict. None if no planes are in conflict.
+pub fn next_conflicting(world: &World, current: Option<char>) -> Option<char> {
+    let ids = conflicting_planes(world);
+    let next = current
+        .and_then(|current| ids.iter().position(|id| *id == current))
+        .map_or(0, |i| (i + 1) % ids.len());
+    ids.get(next).copied()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{plane::Order, world::PlaneKind};
+
+    use super::*;
+
+    fn plane(id: char, pos: [usize; 2]) -> Plane {
+        Plane::new(
+            pos.into(),
+            DirectionCardinal::North,
+            PlaneKind::Jet,
+            id,
+            Destination::Exit(0),
+        )
+    }
+
+    #[test]
+    fn test_roster_conflicts_first() {
+        let mut world = World::new(20, 20);
+        world.insert_plane(plane('a', [1, 1]));
+        world.insert_plane(plane('c', [10, 10]));
+        world.insert_plane(plane('x', [11, 11]));
+        world.insert_plane(plane('b', [18, 1]));
+
+        let order: Vec<(char, bool)> = roster(&world).iter().map(|e| (e.id, e.conflict)).collect();
+        assert_eq!(
+            order,
+            vec![('c', true), ('x', true), ('a', false), ('b', false)]
+        );
+    }
+
+    #[test]
+    fn test_roster_no_conflicts() {
+        let mut world = World::new(20, 20);
+        world.insert_plane(plane('c', [10, 10]));
+        world.insert_plane(plane('a', [1, 1]));
+        let mut far = plane('b', [11, 11]);
+        far.height = 3;
+        world.insert_plane(far);
+
+        let order: Vec<char> = roster(&world).iter().map(|e| e.id).collect();
+        assert_eq!(order, vec!['a', 'b', 'c']);
+    }
+
+    #[test]
+    fn test_roster_orders_annotation() {
+        let mut world = World::new(20, 20);
+        world.insert_plane(plane('a', [1, 1]));
+        world.insert_plane(plane('b', [10, 10]));
+        assert_eq!(orders_annotation(world.orders('a')), "");
+
+        world
+            .queue_order('a', Trigger::AtBeacon(1), Order::Height(3))
+            .unwrap();
+        assert_eq!(orders_annotation(world.orders('a')), "@b1");
+        world
+            .queue_order('a', Trigger::InTicks(4), Order::Height(5))
+            .unwrap();
+        world
+            .queue_order('b', Trigger::InTicks(4), Order::Height(5))
+            .unwrap();
+        assert_eq!(orders_annotation(world.orders('a')), "@b1+1");
+
+        let orders: Vec<String> = roster(&world).into_iter().map(|e| e.orders).collect();
+        assert_eq!(orders, vec!["@b1+1", "@4t"]);
+    }
+
+    #[test]
+    fn test_roster_fuel_unit() {
+        let mut world = World::new(20, 20);
+        let mut a = plane('a', [1, 1]);
+        a.spec.fuel = 40;
+        a.ticks = 30;
+        world.insert_plane(a);
+
+        let fuel = |world: &World| roster(world)[0].fuel.clone();
+        assert_eq!(fuel(&world), "10t");
+        world.set_fuel_unit(FuelUnit::Minutes);
+        assert_eq!(fuel(&world), "5:00");
+        world.set_fuel_unit(FuelUnit::Percent);
+        assert_eq!(fuel(&world), "25%");
+        assert!(!roster(&world)[0].fuel_low);
+
+        world.plane_mut('a').unwrap().ticks = 33;
+        assert_eq!(fuel(&world), "17%");
+        assert!(roster(&world)[0].fuel_low);
+    }
+
+    #[test]
+    fn test_roster_wind() {
+        let mut world = World::new(20, 20);
+        world.insert_plane(plane('a', [1, 1]));
+        let mut b = plane('b', [10, 10]);
+        b.direction = DirectionCardinal::South;
+        world.insert_plane(b);
+
+        let wind = |world: &World| -> Vec<Option<WindEffect>> {
+            roster(world).into_iter().map(|e| e.wind).collect()
+        };
+        assert_eq!(wind(&world), vec![None, None]);
+        world.set_wind(Some(crate::wind::Wind::new(DirectionCardinal::North, 1)));
+        assert_eq!(
+            wind(&world),
+            vec![Some(WindEffect::Tailwind), Some(WindEffect::Headwind)]
+        );
+    }
+
+    #[test]
+    fn test_roster_cycle_conflicts() {
+        let mut world = World::new(20, 20);
+        assert_eq!(next_conflicting(&world, None), None);
+        world.insert_plane(plane('a', [1, 1]));
+        world.insert_plane(plane('b', [18, 1]));
+        world.insert_plane(plane('d', [1, 18]));
+        assert_eq!(next_conflicting(&world, Some('a')), None);
+
+        world.insert_plane(plane('x', [11, 11]));
+        world.insert_plane(plane('c', [10, 10]));
+        assert_eq!(conflicting_planes(&world), vec!['c', 'x']);
+        assert_eq!(next_conflicting(&world, None), Some('c'));
+        assert_eq!(next_conflicting(&world, Some('c')), Some('x'));
+        assert_eq!(next_conflicting(&world, Some('x')), Some('c'));
+        assert_eq!(next_conflicting(&world, Some('b')), Some('c'));
+    }
+}
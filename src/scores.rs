@@ -0,0 +1,186 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use tracing::{info, warn};
+
+use crate::{error::Error, level::Difficulty};
+
+pub const SCORES_FILE_NAME: &str = "highscores.tsv";
+
+/// Best scores per level and difficulty, stored in a small tab separated file
+///
+/// Each line of the file has the form `level\tdifficulty\tscore`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HighScores {
+    scores: HashMap<(String, Difficulty), u64>,
+}
+
+impl HighScores {
+    /// Where the high scores are stored if nothing else is specified
+    ///
+    /// This is `$XDG_DATA_HOME/atc/highscores.tsv`, falling back to `~/.local/share` and the
+    /// temporary directory if those are not set.
+    pub fn default_path() -> PathBuf {
+        let data_dir = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+            })
+            .unwrap_or_else(std::env::temp_dir);
+        data_dir.join("atc").join(SCORES_FILE_NAME)
+    }
+
+    /// Load the high scores from `path`
+    ///
+    /// A missing file just means no scores were set yet. Lines that can not be parsed are
+    /// skipped, so a corrupt file never keeps the game from starting.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(content) => Self::parse(&content),
+            Err(e) => {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!("Could not read high scores from {}: {e}", path.display());
+                }
+                Self::default()
+            }
+        }
+    }
+
+    fn parse(content: &str) -> Self {
+        let mut hs = Self::default();
+        for (lineno, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            let parsed = match fields.as_slice() {
+                [level, difficulty, score] => difficulty
+                    .parse::<Difficulty>()
+                    .ok()
+                    .zip(score.parse::<u64>().ok())
+                    .map(|(difficulty, score)| (level.to_string(), difficulty, score)),
+                _ => None,
+            };
+            match parsed {
+                Some((level, difficulty, score)) => {
+                    hs.update(&level, difficulty, score);
+                }
+                None => warn!("Skipping corrupt high score line {}: {line:?}", lineno + 1),
+            }
+        }
+        hs
+    }
+
+    /// Write the high scores to `path`
+    ///
+    /// The scores are written to a temporary file first, which then replaces the old file, so
+    /// the file is never left half written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut entries: Vec<_> = self.scores.iter().collect();
+        entries.sort();
+        let mut buf = String::new();
+        for ((level, difficulty), score) in entries {
+            buf.push_str(&format!("{level}\t{difficulty}\t{score}\n"));
+        }
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, buf)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    pub fn get(&self, level: &str, difficulty: Difficulty) -> Option<u64> {
+        self.scores.get(&(level.to_string(), difficulty)).copied()
+    }
+
+    /// Set the high score if `score` beats the current one
+    ///
+    /// Returns true if `score` is the new high score.
+    pub fn update(&mut self, level: &str, difficulty: Difficulty, score: u64) -> bool {
+        let key = (level.to_string(), difficulty);
+        match self.scores.get(&key) {
+            Some(best) if *best >= score => false,
+            _ => {
+                self.scores.insert(key, score);
+                true
+            }
+        }
+    }
+
+    /// Read the scores at `path`, update them with a finished game and write them back
+    ///
+    /// Returns true if `score` is the new high score.
+    pub fn record(
+        path: impl AsRef<Path>,
+        level: &str,
+        difficulty: Difficulty,
+        score: u64,
+    ) -> Result<bool, Error> {
+        let path = path.as_ref();
+        let mut hs = Self::load(path);
+        let new_best = hs.update(level, difficulty, score);
+        if new_best {
+            info!("New high score for {level} ({difficulty}): {score}");
+            hs.save(path)?;
+        }
+        Ok(new_best)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tmp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("atc-test-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn test_scores_update_only_when_beaten() {
+        let mut hs = HighScores::default();
+        assert_eq!(hs.get("default", Difficulty::Normal), None);
+        assert!(hs.update("default", Difficulty::Normal, 10));
+        assert!(!hs.update("default", Difficulty::Normal, 5));
+        assert!(!hs.update("default", Difficulty::Normal, 10));
+        assert_eq!(hs.get("default", Difficulty::Normal), Some(10));
+        assert!(hs.update("default", Difficulty::Normal, 11));
+        assert_eq!(hs.get("default", Difficulty::Normal), Some(11));
+        assert_eq!(hs.get("default", Difficulty::Hard), None);
+    }
+
+    #[test]
+    fn test_scores_round_trip() {
+        let path = tmp_path("round-trip").join(SCORES_FILE_NAME);
+        let mut hs = HighScores::default();
+        hs.update("default", Difficulty::Normal, 10);
+        hs.update("default", Difficulty::Hard, 3);
+        hs.update("other", Difficulty::Easy, 42);
+        hs.save(&path).unwrap();
+        assert_eq!(HighScores::load(&path), hs);
+
+        assert!(!HighScores::record(&path, "default", Difficulty::Normal, 9).unwrap());
+        assert!(HighScores::record(&path, "default", Difficulty::Normal, 12).unwrap());
+        assert_eq!(
+            HighScores::load(&path).get("default", Difficulty::Normal),
+            Some(12)
+        );
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_scores_missing_or_corrupt() {
+        let path = tmp_path("missing");
+        assert_eq!(HighScores::load(&path), HighScores::default());
+
+        let hs = HighScores::parse("default\tnormal\t10\ngarbage\nother\thard\tnan\n");
+        assert_eq!(hs.get("default", Difficulty::Normal), Some(10));
+        assert_eq!(hs.get("other", Difficulty::Hard), None);
+    }
+}
@@ -0,0 +1,413 @@
+use std::fmt::Display;
+
+use tracing::{info, info_span};
+
+use crate::{
+    command::{Command, SpawnRequest},
+    error::Error,
+    level::{Level, Spawn},
+    world::{State, Warning},
+};
+
+pub mod replay;
+pub mod save;
+
+/// A single game being played on a [`Level`]
+///
+/// The session drives the simulation one tick at a time with [`step`](Self::step), which tells
+/// the caller everything that happened in that tick. The TUI and headless users both go through
+/// this.
+#[derive(Debug)]
+pub struct GameSession {
+    level: Level,
+    tick: u64,
+    outcome: Option<Outcome>,
+    /// Everything that happened so far, oldest first
+    timeline: Vec<TimedEvent>,
+    /// The tick and the level right before the first tick or command of this session, to
+    /// [replay](Self::replay) the game
+    start: Option<(u64, Level)>,
+    /// Commands carried out since the last tick, for the next [`TickReport`]
+    commands: Vec<Command>,
+}
+
+/// How a game ended, see [`GameSession::outcome`]
+// there is one outcome per game, boxing the state is not worth losing `Copy` for
+#[allow(clippy::large_enum_variant)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// Every plane of a level with a
+    /// [spawn schedule](crate::level::SpawnPolicy::Schedule) came in and got to its destination
+    Won { score: u64 },
+    /// The game ended in `state`, for example because two planes collided
+    Lost { state: State, score: u64 },
+}
+
+/// Something that happened in a game, see [`GameSession::timeline`]
+// only the last event of a timeline is an outcome
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    Spawn(Spawn),
+    Command(Command),
+    Outcome(Outcome),
+}
+
+/// An [`Event`] and the tick it happened in
+///
+/// Commands are given between two ticks, they get the tick before them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimedEvent {
+    pub tick: u64,
+    pub event: Event,
+}
+
+/// Everything that happened during one call to [`GameSession::step`]
+#[derive(Debug, Clone, Default)]
+pub struct TickReport {
+    /// The tick this report is about, the first tick is 1
+    pub tick: u64,
+    pub spawns: Vec<Spawn>,
+    /// Commands that were carried out since the last tick, oldest first
+    pub commands: Vec<Command>,
+    pub warnings: Vec<Warning>,
+    /// Set if the game ended in this tick
+    pub outcome: Option<Outcome>,
+}
+
+impl TickReport {
+    /// Something happened that the player should know about: a plane came in, there was a
+    /// warning or the game ended
+    pub fn has_events(&self) -> bool {
+        !self.spawns.is_empty() || !self.warnings.is_empty() || self.outcome.is_some()
+    }
+}
+
+impl GameSession {
+    pub fn new(level: Level) -> Self {
+        Self {
+            level,
+            tick: 0,
+            outcome: None,
+            timeline: Vec::new(),
+            start: None,
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn level(&self) -> &Level {
+        &self.level
+    }
+
+    pub fn level_mut(&mut self) -> &mut Level {
+        &mut self.level
+    }
+
+    /// How many ticks were simulated so far
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    /// How the game ended, None while it goes on
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    /// The spawns, commands and the outcome of the game so far, oldest first
+    ///
+    /// Only what happened since the session was created is in here, not what happened before
+    /// a saved game was resumed.
+    pub fn timeline(&self) -> &[TimedEvent] {
+        &self.timeline
+    }
+
+    /// Carry out `command` and put it on the [timeline](Self::timeline)
+    pub fn apply_command(&mut self, command: Command) -> Result<(), Error> {
+        self.keep_start();
+        self.level.apply_command(command)?;
+        self.commands.push(command);
+        self.record(Event::Command(command));
+        Ok(())
+    }
+
+    /// Like [`apply_command`](Self::apply_command), but only for the planes of `controller`
+    pub fn apply_command_as(&mut self, command: Command, controller: u8) -> Result<(), Error> {
+        self.keep_start();
+        self.level.apply_command_as(command, controller)?;
+        self.commands.push(command);
+        self.record(Event::Command(command));
+        Ok(())
+    }
+
+    /// Let a plane in by hand, it is put on the [timeline](Self::timeline) in the next tick
+    pub fn apply_spawn_request(&mut self, request: SpawnRequest) -> Result<Spawn, Error> {
+        self.keep_start();
+        request.apply(&mut self.level)
+    }
+
+    /// Remember the level as it is now if the game did not start yet
+    ///
+    /// Changes made with [`level_mut`](Self::level_mut) before that, like the settings of the
+    /// player, are part of the start of the replay.
+    fn keep_start(&mut self) {
+        if self.start.is_none() {
+            self.start = Some((self.tick, self.level.clone()));
+        }
+    }
+
+    fn record(&mut self, event: Event) {
+        self.timeline.push(TimedEvent {
+            tick: self.tick,
+            event,
+        });
+    }
+
+    /// Simulate one tick
+    ///
+    /// Once the game is over, this does not simulate anything anymore and only repeats the
+    /// outcome.
+    pub fn step(&mut self) -> TickReport {
+        if let Some(outcome) = self.outcome {
+            return TickReport {
+                tick: self.tick,
+                commands: std::mem::take(&mut self.commands),
+                outcome: Some(outcome),
+                ..Default::default()
+            };
+        }
+
+        self.keep_start();
+        self.tick += 1;
+        let _span = info_span!("tick", tick = self.tick).entered();
+        let state = self.level.tick_at(self.tick);
+        let score = self.level.score();
+        let outcome = match state {
+            State::Ongoing if self.level.finished(self.tick) => Some(Outcome::Won { score }),
+            State::Ongoing => None,
+            state => Some(Outcome::Lost { state, score }),
+        };
+        self.outcome = outcome;
+        if let Some(outcome) = outcome {
+            info!(%outcome, score = outcome.score(), "game over");
+        }
+
+        let spawns = self.level.take_spawns();
+        for spawn in &spawns {
+            self.record(Event::Spawn(*spawn));
+        }
+        if let Some(outcome) = outcome {
+            self.record(Event::Outcome(outcome));
+        }
+        TickReport {
+            tick: self.tick,
+            spawns,
+            commands: std::mem::take(&mut self.commands),
+            warnings: self.level.world_mut().take_warnings(),
+            outcome,
+        }
+    }
+}
+
+impl Outcome {
+    /// The score at the end of the game
+    pub fn score(self) -> u64 {
+        match self {
+            Self::Won { score } | Self::Lost { score, .. } => score,
+        }
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Won { .. } => write!(f, "Every plane made it, you won!"),
+            Self::Lost { state, .. } => write!(f, "{state}"),
+        }
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Spawn(spawn) => write!(f, "{spawn}"),
+            Self::Command(command) => write!(f, "Command {command}"),
+            Self::Outcome(outcome) => write!(f, "{outcome}"),
+        }
+    }
+}
+
+impl Display for TimedEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.tick, self.event)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_session_step_reports_spawn() {
+        let mut session = GameSession::new(Level::builtin());
+        let spawn = session.level_mut().spawn_plane().unwrap();
+
+        let report = session.step();
+        assert_eq!(report.tick, 1);
+        assert!(report.spawns.contains(&spawn));
+        assert!(report.commands.is_empty());
+        assert!(report.outcome.is_none());
+        assert!(report.has_events());
+        assert!(session.level().world().plane(spawn.plane).is_some());
+
+        let report = session.step();
+        assert_eq!(report.tick, 2);
+        assert!(!report.spawns.contains(&spawn));
+    }
+
+    #[test]
+    fn test_session_step_reports_random_spawn() {
+        let mut session = GameSession::new(Level::builtin());
+        // the first plane of this seed is a small one, jets already move in the tick they come in
+        session.level_mut().set_seed(1);
+        let report = loop {
+            let report = session.step();
+            if !report.spawns.is_empty() {
+                break report;
+            }
+        };
+        let spawn = report.spawns[0];
+        let plane = session.level().world().plane(spawn.plane).unwrap();
+        let exit = session.level().world().exit(spawn.exit).unwrap();
+        assert_eq!(plane.pos, session.level().world().exit_pos(exit));
+        assert_eq!(plane.destination, spawn.destination);
+        assert_eq!(plane.kind, spawn.kind);
+        assert_eq!(
+            spawn.to_string(),
+            format!(
+                "Plane {} entering from exit {} (small, to {})",
+                spawn.plane, spawn.exit, spawn.destination
+            )
+        );
+    }
+
+    #[test]
+    fn test_session_step_reports_end() {
+        let mut session = GameSession::new(Level::builtin());
+        let spawn = session.level_mut().spawn_plane().unwrap();
+        session
+            .level_mut()
+            .world_mut()
+            .plane_mut(spawn.plane)
+            .unwrap()
+            .ticks = 10_000;
+
+        assert_eq!(session.outcome(), None);
+        let report = session.step();
+        assert!(matches!(
+            report.outcome,
+            Some(Outcome::Lost { state: State::PlaneNoFuel(plane), score: 0 })
+                if plane.id == spawn.plane
+        ));
+        assert!(session.is_over());
+        assert_eq!(session.outcome(), report.outcome);
+
+        let report = session.step();
+        assert_eq!(report.tick, 1);
+        assert!(report.spawns.is_empty());
+        assert_eq!(session.outcome(), report.outcome);
+    }
+
+    #[test]
+    fn test_session_won_when_schedule_is_done() {
+        use std::collections::BTreeMap;
+
+        use crate::{
+            level::{ScheduledSpawn, SpawnPolicy},
+            plane::Destination,
+            world::PlaneKind,
+        };
+
+        let mut level = Level::builtin();
+        let spawn = ScheduledSpawn {
+            exit: 0,
+            kind: PlaneKind::Jet,
+            destination: Destination::Exit(2),
+        };
+        level.set_spawn_policy(SpawnPolicy::Schedule(BTreeMap::from([(2, spawn)])));
+        let mut session = GameSession::new(level);
+        session.step();
+        assert_eq!(session.outcome(), None, "the plane did not come in yet");
+        let plane = session.step().spawns[0].plane;
+        assert_eq!(session.outcome(), None, "the plane is still in the air");
+
+        // put the plane right before its exit
+        let world = session.level_mut().world_mut();
+        let exit = *world.exit(2).unwrap();
+        let pos = world
+            .exit_pos(&exit)
+            .step(exit.plane_out_direction)
+            .unwrap();
+        let plane = world.plane_mut(plane).unwrap();
+        plane.pos = pos;
+        plane.direction = exit.plane_out_direction.opposite();
+        plane.height = plane.spec.exit_height;
+        plane.target_height = plane.spec.exit_height;
+        plane.just_spawned = false;
+        let report = session.step();
+        assert_eq!(report.outcome, Some(Outcome::Won { score: 1 }));
+        assert_eq!(session.outcome(), Some(Outcome::Won { score: 1 }));
+    }
+
+    #[test]
+    fn test_session_timeline_ticks() {
+        let mut session = GameSession::new(Level::builtin());
+        session.level_mut().set_seed(7);
+        let mut spawned = None;
+        while spawned.is_none() {
+            let report = session.step();
+            spawned = report.spawns.first().map(|spawn| (report.tick, *spawn));
+        }
+        let (spawn_tick, spawn) = spawned.unwrap();
+        session.step();
+        session.step();
+        let command = Command::Clear { plane: spawn.plane };
+        session.apply_command(command).unwrap();
+        assert!(session
+            .apply_command(Command::Clear { plane: 'z' })
+            .is_err());
+
+        assert_eq!(
+            session.timeline(),
+            [
+                TimedEvent {
+                    tick: spawn_tick,
+                    event: Event::Spawn(spawn),
+                },
+                TimedEvent {
+                    tick: spawn_tick + 2,
+                    event: Event::Command(command),
+                },
+            ]
+        );
+        assert_eq!(
+            session.timeline()[1].to_string(),
+            format!("{}: Command {} OK", spawn_tick + 2, spawn.plane)
+        );
+
+        session
+            .level_mut()
+            .world_mut()
+            .plane_mut(spawn.plane)
+            .unwrap()
+            .ticks = 10_000;
+        let report = session.step();
+        assert_eq!(report.commands, vec![command]);
+        let last = session.timeline().last().unwrap();
+        assert_eq!(last.tick, report.tick);
+        assert_eq!(last.event, Event::Outcome(report.outcome.unwrap()));
+    }
+}
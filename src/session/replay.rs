@@ -0,0 +1,250 @@
+//! Watching a game again, tick by tick
+//!
+//! A [`Replay`] starts over from the level as it was before the first tick of the
+//! [session](GameSession) and plays the [timeline](GameSession::timeline) again: the recorded
+//! planes come in at the same ticks and the commands are given at the same ticks. Nothing is
+//! left to chance, so the same game plays out every time and any tick can be reached again.
+
+use std::ops::RangeInclusive;
+
+use tracing::warn;
+
+use crate::{
+    error::Error,
+    level::{Level, ScheduledSpawn},
+};
+
+use super::{Event, GameSession, TimedEvent};
+
+/// A recorded game that can be stepped through and [seeked](Replay::seek) to any tick
+#[derive(Debug)]
+pub struct Replay {
+    start_tick: u64,
+    last_tick: u64,
+    start: Level,
+    events: Vec<TimedEvent>,
+    /// The game as it is at the tick the replay is at
+    session: GameSession,
+}
+
+impl GameSession {
+    /// Everything that happened in this session so far as a [`Replay`], None if not a single
+    /// tick or command happened yet
+    pub fn replay(&self) -> Option<Replay> {
+        let (start_tick, start) = self.start.as_ref()?;
+        Some(Replay::new(
+            *start_tick..=self.tick,
+            start.clone(),
+            self.timeline.clone(),
+        ))
+    }
+}
+
+impl Replay {
+    fn new(ticks: RangeInclusive<u64>, start: Level, events: Vec<TimedEvent>) -> Self {
+        let start_tick = *ticks.start();
+        let mut replay = Self {
+            start_tick,
+            last_tick: *ticks.end(),
+            session: Self::session_at_start(start_tick, &start),
+            start,
+            events,
+        };
+        replay.apply_commands();
+        replay
+    }
+
+    fn session_at_start(start_tick: u64, start: &Level) -> GameSession {
+        let mut session = GameSession::new(start.clone());
+        session.tick = start_tick;
+        session
+    }
+
+    /// The game at the tick the replay is at
+    pub fn session(&self) -> &GameSession {
+        &self.session
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.session.tick
+    }
+
+    /// The tick the recorded game started at, later than 0 for a resumed game
+    pub fn first_tick(&self) -> u64 {
+        self.start_tick
+    }
+
+    /// The last tick of the recorded game
+    pub fn last_tick(&self) -> u64 {
+        self.last_tick
+    }
+
+    /// Simulate the next recorded tick, false if the replay is at the end already
+    pub fn step_forward(&mut self) -> bool {
+        if self.tick() >= self.last_tick() {
+            return false;
+        }
+        let session = &mut self.session;
+        session.tick += 1;
+        let tick = session.tick;
+        let spawns = self
+            .events
+            .iter()
+            .filter(|event| event.tick == tick)
+            .filter_map(|event| match event.event {
+                Event::Spawn(spawn) => Some(spawn),
+                _ => None,
+            });
+        for spawn in spawns {
+            let scheduled = ScheduledSpawn {
+                exit: spawn.exit,
+                kind: spawn.kind,
+                destination: spawn.destination,
+            };
+            match session.level.spawn_scheduled(scheduled) {
+                Ok(again) if again.plane != spawn.plane => {
+                    warn!(tick, recorded = %spawn.plane, replayed = %again.plane, "replayed plane got another id")
+                }
+                Ok(_) => (),
+                Err(e) => warn!(tick, plane = %spawn.plane, "could not replay spawn: {e}"),
+            }
+        }
+        session.level.world_mut().tick_planes();
+        for spawn in session.level.take_spawns() {
+            session.record(Event::Spawn(spawn));
+        }
+        session.level.world_mut().take_warnings();
+        if let Some(outcome) = self.events.iter().find_map(|event| match event.event {
+            Event::Outcome(outcome) if event.tick == tick => Some(outcome),
+            _ => None,
+        }) {
+            session.outcome = Some(outcome);
+            session.record(Event::Outcome(outcome));
+        }
+        self.apply_commands();
+        true
+    }
+
+    /// Go back by one tick, false if the replay is at the start already
+    pub fn step_back(&mut self) -> bool {
+        let tick = self.tick();
+        if tick <= self.start_tick {
+            return false;
+        }
+        self.seek(tick - 1).is_ok()
+    }
+
+    /// Jump to `tick`
+    ///
+    /// Going forward simulates the ticks in between, going back starts over from the start of
+    /// the game.
+    pub fn seek(&mut self, tick: u64) -> Result<(), Error> {
+        if !(self.start_tick..=self.last_tick()).contains(&tick) {
+            return Err(Error::TickNotRecorded(
+                tick,
+                self.start_tick,
+                self.last_tick(),
+            ));
+        }
+        if tick < self.tick() {
+            self.session = Self::session_at_start(self.start_tick, &self.start);
+            self.apply_commands();
+        }
+        while self.tick() < tick && self.step_forward() {}
+        Ok(())
+    }
+
+    /// Give the commands recorded for the tick the replay is at
+    fn apply_commands(&mut self) {
+        let tick = self.tick();
+        let commands = self
+            .events
+            .iter()
+            .filter(|event| event.tick == tick)
+            .filter_map(|event| match event.event {
+                Event::Command(command) => Some(command),
+                _ => None,
+            });
+        for command in commands {
+            if let Err(e) = self.session.apply_command(command) {
+                warn!(tick, %command, "could not replay command: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{command::Command, plane::Plane};
+
+    use super::*;
+
+    fn planes(session: &GameSession) -> Vec<Plane> {
+        let mut planes: Vec<Plane> = session.level().world().planes().copied().collect();
+        planes.sort_by_key(|plane| plane.id);
+        planes
+    }
+
+    #[test]
+    fn test_replay_seek_matches_stepping() {
+        let mut session = GameSession::new(Level::builtin());
+        session.level_mut().set_seed(7);
+        let mut played = vec![(session.level().render(), planes(&session))];
+        let mut commanded = false;
+        while session.tick() < 60 && !session.is_over() {
+            let report = session.step();
+            if let (false, Some(spawn)) = (commanded, report.spawns.first()) {
+                let plane = session.level().world().plane(spawn.plane).unwrap();
+                let command = Command::Vector {
+                    plane: plane.id,
+                    heading: plane.direction,
+                    height: 6,
+                };
+                if session.apply_command(command).is_ok() {
+                    commanded = true;
+                }
+            }
+            played.push((session.level().render(), planes(&session)));
+        }
+        assert!(commanded, "a plane should have come in");
+
+        let mut replay = session.replay().unwrap();
+        assert_eq!(replay.first_tick(), 0);
+        assert_eq!(replay.last_tick(), session.tick());
+
+        let mut stepped = 0;
+        while replay.step_forward() {
+            stepped += 1;
+            let (render, planes_then) = &played[stepped as usize];
+            assert_eq!(&replay.session().level().render(), render);
+            assert_eq!(&planes(replay.session()), planes_then);
+        }
+        assert_eq!(replay.tick(), session.tick());
+        assert!(!replay.step_forward());
+        assert_eq!(replay.session().outcome(), session.outcome());
+
+        let last = replay.last_tick();
+        for tick in [last, 3, last / 2, last / 2, 0] {
+            replay.seek(tick).unwrap();
+            assert_eq!(replay.tick(), tick);
+            let (render, planes_then) = &played[tick as usize];
+            assert_eq!(&replay.session().level().render(), render);
+            assert_eq!(&planes(replay.session()), planes_then);
+        }
+        assert!(!replay.step_back());
+        replay.seek(10).unwrap();
+        assert!(replay.step_back());
+        assert_eq!(planes(replay.session()), played[9].1);
+
+        assert!(matches!(
+            replay.seek(1000),
+            Err(Error::TickNotRecorded(1000, 0, _))
+        ));
+    }
+
+    #[test]
+    fn test_replay_needs_a_started_game() {
+        let session = GameSession::new(Level::builtin());
+        assert!(session.replay().is_none());
+    }
+}
@@ -0,0 +1,211 @@
+//! Games saved while they are still going, to be picked up again later
+//!
+//! A saved game is a TOML file with the [level file](crate::level::file) of the level, the
+//! planes in the air and how far the game got. The random numbers go on where they left off,
+//! so a resumed game brings in the same planes as if it had never stopped.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+use crate::{error::Error, level::Level, scores::HighScores, world::Traffic};
+
+use super::GameSession;
+
+/// The version of saved games written by this version of the game
+pub const SAVE_VERSION: u32 = 1;
+
+pub const SAVE_FILE_NAME: &str = "autosave.toml";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedGame {
+    version: u32,
+    tick: u64,
+    /// TOML numbers are signed, seeds can be larger than that
+    seed: String,
+    /// How many random numbers were drawn from the seed, see [`Level::rng_position`]. Saves
+    /// without it start over from the seed.
+    #[serde(default)]
+    rng_position: Option<String>,
+    spawning_paused: bool,
+    /// Commands carried out so far, for the [command penalty](Level::set_command_penalty)
+    #[serde(default)]
+    commands: u64,
+    traffic: Traffic,
+    level: Table,
+}
+
+impl GameSession {
+    /// Where the game is saved if nothing else is specified, next to the
+    /// [high scores](HighScores::default_path)
+    pub fn default_save_path() -> PathBuf {
+        HighScores::default_path().with_file_name(SAVE_FILE_NAME)
+    }
+
+    /// The game as the content of a save file
+    pub fn to_save_string(&self) -> String {
+        let saved = SavedGame {
+            version: SAVE_VERSION,
+            tick: self.tick,
+            seed: self.level.seed().to_string(),
+            rng_position: Some(self.level.rng_position().to_string()),
+            spawning_paused: self.level.spawning_paused(),
+            commands: self.level.commands(),
+            traffic: self.level.world().traffic(),
+            level: self.level.to_table(),
+        };
+        toml::to_string(&saved).expect("saved games can always be written as TOML")
+    }
+
+    /// Resume the game saved in `content`
+    ///
+    /// Saves of another [`SAVE_VERSION`] and files that are not a saved game are rejected.
+    pub fn parse_save(content: &str) -> Result<Self, Error> {
+        let table: Table = content
+            .parse()
+            .map_err(|e: toml::de::Error| Error::BadSave(e.message().to_string()))?;
+        match table.get("version") {
+            Some(Value::Integer(version)) if *version == SAVE_VERSION as i64 => (),
+            Some(Value::Integer(version)) => {
+                return Err(Error::SaveVersionUnsupported(*version, SAVE_VERSION))
+            }
+            _ => return Err(Error::BadSave("no version".to_string())),
+        }
+        let saved =
+            SavedGame::deserialize(table).map_err(|e| Error::BadSave(e.message().to_string()))?;
+
+        let seed = saved
+            .seed
+            .parse()
+            .map_err(|_| Error::BadSave(format!("bad seed: {}", saved.seed)))?;
+        let mut level = Level::from_table(saved.level)?;
+        level.set_seed(seed);
+        if let Some(position) = saved.rng_position {
+            let position = position
+                .parse()
+                .map_err(|_| Error::BadSave(format!("bad random number position: {position}")))?;
+            level.restore_rng_position(position);
+        }
+        level.set_spawning_paused(saved.spawning_paused);
+        level.restore_commands(saved.commands);
+        level.world_mut().restore_traffic(saved.traffic);
+        Ok(Self {
+            level,
+            tick: saved.tick,
+            outcome: None,
+            timeline: Vec::new(),
+            start: None,
+            commands: Vec::new(),
+        })
+    }
+
+    /// Save the game to `path`, creating the directory if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(std::fs::write(path, self.to_save_string())?)
+    }
+
+    /// Resume the game saved at `path`, see [`GameSession::parse_save`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::parse_save(&std::fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        command::Command,
+        level::Difficulty,
+        plane::{Order, Trigger},
+        world::DirectionCardinal,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_save_round_trip() {
+        let mut session = GameSession::new(Level::builtin());
+        session.level_mut().set_seed(u64::MAX);
+        session.level_mut().set_difficulty(Difficulty::Hard);
+        let a = session.level_mut().spawn_plane().unwrap().plane;
+        for _ in 0..3 {
+            session.step();
+        }
+        let b = session.level_mut().spawn_plane().unwrap().plane;
+        let world = session.level_mut().world_mut();
+        world
+            .queue_order(
+                a,
+                Trigger::InTicks(4),
+                Order::Heading(DirectionCardinal::West),
+            )
+            .unwrap();
+        world.panic_hold();
+        world.release_panic_hold();
+        world.plane_mut(b).unwrap().holding = true;
+        session
+            .level_mut()
+            .apply_command(Command::Clear { plane: a })
+            .unwrap();
+        session.level_mut().set_spawning_paused(true);
+
+        let resumed = GameSession::parse_save(&session.to_save_string()).unwrap();
+        assert_eq!(resumed.tick(), session.tick());
+        assert_eq!(resumed.level().seed(), u64::MAX);
+        assert_eq!(resumed.level().difficulty(), Difficulty::Hard);
+        assert!(resumed.level().spawning_paused());
+        assert_eq!(resumed.level().commands(), 1);
+        assert_eq!(resumed.level().render(), session.level().render());
+        assert_eq!(
+            resumed.level().world().traffic(),
+            session.level().world().traffic()
+        );
+        assert_eq!(resumed.level().world().orders(a).len(), 1);
+        assert_eq!(resumed.to_save_string(), session.to_save_string());
+    }
+
+    #[test]
+    fn test_save_resumes_random_numbers() {
+        let mut session = GameSession::new(Level::with_seed(5));
+        while session.tick() < 30 && !session.is_over() {
+            session.step();
+        }
+        assert_ne!(session.level().rng_position(), 0);
+        let mut resumed = GameSession::parse_save(&session.to_save_string()).unwrap();
+        assert_eq!(
+            resumed.level().rng_position(),
+            session.level().rng_position()
+        );
+
+        let spawns = |session: &mut GameSession| {
+            (0..5)
+                .map(|_| session.level_mut().spawn_plane().ok())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(spawns(&mut resumed), spawns(&mut session));
+    }
+
+    #[test]
+    fn test_save_rejects_bad_files() {
+        let saved = GameSession::new(Level::builtin()).to_save_string();
+        let future = saved.replacen(&format!("version = {SAVE_VERSION}"), "version = 7", 1);
+        assert!(matches!(
+            GameSession::parse_save(&future),
+            Err(Error::SaveVersionUnsupported(7, SAVE_VERSION))
+        ));
+        assert!(matches!(
+            GameSession::parse_save("tick = 4"),
+            Err(Error::BadSave(_))
+        ));
+        assert!(matches!(
+            GameSession::parse_save("not [ toml"),
+            Err(Error::BadSave(_))
+        ));
+        let truncated = &saved[..saved.len() / 2];
+        assert!(GameSession::parse_save(truncated).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+//! Golden snapshots of rendered output for tests
+//!
+//! A snapshot is stored in `src/snapshots/<name>.snap`. Run the tests with the environment
+//! variable [`UPDATE_ENV`] set to write the current output as the new snapshot instead of
+//! comparing against it.
+
+use std::path::PathBuf;
+
+/// Set this environment variable to update the snapshots instead of checking them
+pub const UPDATE_ENV: &str = "UPDATE_SNAPSHOTS";
+
+/// Where the snapshot called `name` is stored
+pub fn snapshot_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src")
+        .join("snapshots")
+        .join(format!("{name}.snap"))
+}
+
+/// Make rendered output comparable: unix line endings, no trailing whitespace on a line and
+/// exactly one line break at the end
+pub fn normalize(rendered: &str) -> String {
+    let mut out: String = rendered
+        .replace("\r\n", "\n")
+        .lines()
+        .map(|line| format!("{}\n", line.trim_end()))
+        .collect();
+    while out.ends_with("\n\n") {
+        out.pop();
+    }
+    out
+}
+
+/// The lines that differ between `expected` and `actual`, `-` for expected and `+` for actual
+pub fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut out = String::new();
+    for row in 0..expected.len().max(actual.len()) {
+        let (e, a) = (expected.get(row), actual.get(row));
+        if e != a {
+            if let Some(e) = e {
+                out.push_str(&format!("{row:>3} - {e}\n"));
+            }
+            if let Some(a) = a {
+                out.push_str(&format!("{row:>3} + {a}\n"));
+            }
+        }
+    }
+    out
+}
+
+/// Panic with a line diff if `rendered` does not match the snapshot called `name`
+///
+/// A missing snapshot is an error too, unless the snapshots are being updated.
+#[track_caller]
+pub fn assert_snapshot(name: &str, rendered: &str) {
+    let path = snapshot_path(name);
+    let actual = normalize(rendered);
+    if std::env::var_os(UPDATE_ENV).is_some() {
+        std::fs::create_dir_all(path.parent().expect("snapshots are in a directory"))
+            .expect("could not create the snapshot directory");
+        std::fs::write(&path, &actual).expect("could not write the snapshot");
+        return;
+    }
+    let expected = match std::fs::read_to_string(&path) {
+        Ok(expected) => normalize(&expected),
+        Err(e) => panic!(
+            "could not read snapshot {}: {e}\nrun with {UPDATE_ENV}=1 to create it",
+            path.display()
+        ),
+    };
+    if expected != actual {
+        panic!(
+            "snapshot {name} does not match, run with {UPDATE_ENV}=1 to update it\n{}",
+            diff(&expected, &actual)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_normalize_and_diff() {
+        assert_eq!(normalize("a  \r\nb\n\n\n"), "a\nb\n");
+        assert_eq!(normalize("a\nb"), "a\nb\n");
+        assert_eq!(
+            diff("a\nb\nc\n", "a\nx\nc\nd\n"),
+            "  1 - b\n  1 + x\n  3 + d\n"
+        );
+        assert!(diff("a\n", "a\n").is_empty());
+    }
+}
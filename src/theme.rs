@@ -0,0 +1,306 @@
+use std::{path::Path, str::FromStr};
+
+use ratatui::style::Color;
+
+use crate::{error::Error, world::PlaneKind};
+
+/// Names of the built in themes, the first is the default
+pub const PRESETS: [&str; 3] = ["classic", "dark", "light"];
+
+/// All colors the game is drawn in
+///
+/// A theme is written as one `name = color` line per color, see [`Theme::parse`]. Colors are
+/// ratatui color names like `light-blue`, ANSI indices or `#rrggbb`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// The walls around the map
+    pub border: Color,
+    pub empty: Color,
+    pub route: Color,
+    pub beacon: Color,
+    pub airport: Color,
+    pub slow_zone: Color,
+    pub obstacle: Color,
+    pub exit: Color,
+    /// Jets on the map and in the roster, `plane = color` in a theme file sets this and
+    /// [`small`](Self::small)
+    pub jet: Color,
+    /// Small planes on the map and in the roster
+    pub small: Color,
+    /// The title above the map
+    pub title: Color,
+    /// Planes in the roster that are too close to another plane
+    pub conflict: Color,
+    /// Planes in the roster that run out of fuel
+    pub warning: Color,
+    /// The fuel of planes in the roster that have little fuel left
+    pub fuel_low: Color,
+    /// Background of the tiles the selected plane flies over next
+    pub ghost: Color,
+    /// Background of the alert panel
+    pub alert: Color,
+    /// Text of the alert panel
+    pub alert_text: Color,
+    /// Colors to tell planes apart by destination or controller, used in this order
+    pub palette: Vec<Color>,
+}
+
+impl Theme {
+    /// The built in theme called `name`, one of [`PRESETS`]
+    pub fn preset(name: &str) -> Result<Self, Error> {
+        let palette = vec![
+            Color::LightRed,
+            Color::LightGreen,
+            Color::LightYellow,
+            Color::LightBlue,
+            Color::LightMagenta,
+            Color::LightCyan,
+            Color::Red,
+            Color::Green,
+        ];
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "classic" => Self {
+                border: Color::Reset,
+                empty: Color::Reset,
+                route: Color::Reset,
+                beacon: Color::Reset,
+                airport: Color::Reset,
+                slow_zone: Color::Reset,
+                obstacle: Color::Reset,
+                exit: Color::Reset,
+                jet: Color::Reset,
+                small: Color::Cyan,
+                title: Color::Blue,
+                conflict: Color::Red,
+                warning: Color::Yellow,
+                fuel_low: Color::Red,
+                ghost: Color::DarkGray,
+                alert: Color::Yellow,
+                alert_text: Color::Black,
+                palette,
+            },
+            "dark" => Self {
+                border: Color::DarkGray,
+                empty: Color::DarkGray,
+                route: Color::Gray,
+                beacon: Color::LightCyan,
+                airport: Color::LightGreen,
+                slow_zone: Color::Blue,
+                obstacle: Color::LightRed,
+                exit: Color::LightYellow,
+                jet: Color::White,
+                small: Color::LightMagenta,
+                title: Color::LightBlue,
+                conflict: Color::LightRed,
+                warning: Color::LightYellow,
+                fuel_low: Color::LightRed,
+                ghost: Color::DarkGray,
+                alert: Color::LightYellow,
+                alert_text: Color::Black,
+                palette,
+            },
+            "light" => Self {
+                border: Color::Gray,
+                empty: Color::Gray,
+                route: Color::DarkGray,
+                beacon: Color::Blue,
+                airport: Color::Green,
+                slow_zone: Color::Cyan,
+                obstacle: Color::Red,
+                exit: Color::Magenta,
+                jet: Color::Black,
+                small: Color::Blue,
+                title: Color::Blue,
+                conflict: Color::Red,
+                warning: Color::Magenta,
+                fuel_low: Color::Red,
+                ghost: Color::Gray,
+                alert: Color::Yellow,
+                alert_text: Color::Black,
+                palette: vec![
+                    Color::Red,
+                    Color::Green,
+                    Color::Blue,
+                    Color::Magenta,
+                    Color::Cyan,
+                    Color::Yellow,
+                    Color::DarkGray,
+                    Color::LightRed,
+                ],
+            },
+            _ => return Err(Error::UnknownTheme(name.to_string())),
+        })
+    }
+
+    /// Read a theme from `content`
+    ///
+    /// Every line is `name = color`, where the names are the fields of [`Theme`] and the
+    /// palette is a comma separated list of colors. A `preset = name` line picks the built in
+    /// theme the other lines change, the default is the first of [`PRESETS`]. Empty lines and
+    /// lines starting with `#` are skipped.
+    pub fn parse(content: &str) -> Result<Self, Error> {
+        let settings: Vec<(&str, &str)> = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                line.split_once('=')
+                    .map(|(key, value)| (key.trim(), value.trim()))
+                    .ok_or_else(|| Error::BadThemeLine(line.to_string()))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let preset = settings
+            .iter()
+            .find(|(key, _)| *key == "preset")
+            .map_or(PRESETS[0], |(_, value)| value);
+        let mut theme = Self::preset(preset)?;
+        for (key, value) in settings {
+            if key == "preset" {
+                continue;
+            }
+            if key == "palette" {
+                theme.palette = value
+                    .split(',')
+                    .map(|color| parse_color(color.trim()))
+                    .collect::<Result<_, _>>()?;
+                continue;
+            }
+            if key == "plane" {
+                theme.jet = parse_color(value)?;
+                theme.small = theme.jet;
+                continue;
+            }
+            *theme.color_mut(key)? = parse_color(value)?;
+        }
+        Ok(theme)
+    }
+
+    /// Read a theme from the file at `path`, see [`Theme::parse`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// The color of a plane of `kind`
+    pub fn plane_color(&self, kind: PlaneKind) -> Color {
+        match kind {
+            PlaneKind::Jet => self.jet,
+            PlaneKind::Small => self.small,
+        }
+    }
+
+    /// The color of a plane by its index in the destinations or controllers
+    pub fn palette_color(&self, idx: usize) -> Color {
+        self.palette[idx % self.palette.len()]
+    }
+
+    fn color_mut(&mut self, key: &str) -> Result<&mut Color, Error> {
+        Ok(match key {
+            "border" => &mut self.border,
+            "empty" => &mut self.empty,
+            "route" => &mut self.route,
+            "beacon" => &mut self.beacon,
+            "airport" => &mut self.airport,
+            "slow_zone" => &mut self.slow_zone,
+            "obstacle" => &mut self.obstacle,
+            "exit" => &mut self.exit,
+            "jet" => &mut self.jet,
+            "small" => &mut self.small,
+            "title" => &mut self.title,
+            "conflict" => &mut self.conflict,
+            "warning" => &mut self.warning,
+            "fuel_low" => &mut self.fuel_low,
+            "ghost" => &mut self.ghost,
+            "alert" => &mut self.alert,
+            "alert_text" => &mut self.alert_text,
+            _ => return Err(Error::UnknownThemeKey(key.to_string())),
+        })
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::preset(PRESETS[0]).expect("the first preset exists")
+    }
+}
+
+impl FromStr for Theme {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+fn parse_color(value: &str) -> Result<Color, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::UnknownColor(value.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_theme_overrides_route_color() {
+        let theme: Theme = "# my colors\nroute = light-green\n".parse().unwrap();
+        assert_eq!(theme.route, Color::LightGreen);
+        assert_ne!(Theme::default().route, Color::LightGreen);
+        assert_eq!(
+            Theme {
+                route: Color::LightGreen,
+                ..Theme::default()
+            },
+            theme
+        );
+
+        let theme = Theme::parse("route = #102030\npreset = dark").unwrap();
+        assert_eq!(theme.route, Color::Rgb(0x10, 0x20, 0x30));
+        assert_eq!(theme.beacon, Theme::preset("dark").unwrap().beacon);
+        for preset in PRESETS {
+            assert!(Theme::preset(preset).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_theme_rejects_bad_settings() {
+        assert!(matches!(
+            Theme::parse("route = sparkly"),
+            Err(Error::UnknownColor(c)) if c == "sparkly"
+        ));
+        assert!(matches!(
+            Theme::parse("runway = red"),
+            Err(Error::UnknownThemeKey(k)) if k == "runway"
+        ));
+        assert!(matches!(
+            Theme::parse("preset = neon"),
+            Err(Error::UnknownTheme(_))
+        ));
+        assert!(matches!(
+            Theme::parse("route red"),
+            Err(Error::BadThemeLine(_))
+        ));
+        assert!(matches!(
+            Theme::parse("palette = red, nope"),
+            Err(Error::UnknownColor(_))
+        ));
+        let theme = Theme::parse("palette = red, blue").unwrap();
+        assert_eq!(theme.palette_color(3), Color::Blue);
+    }
+
+    #[test]
+    fn test_theme_plane_colors() {
+        for preset in PRESETS {
+            let theme = Theme::preset(preset).unwrap();
+            assert_ne!(
+                theme.plane_color(PlaneKind::Jet),
+                theme.plane_color(PlaneKind::Small),
+                "{preset}"
+            );
+        }
+        let theme = Theme::parse("plane = red\nsmall = green").unwrap();
+        assert_eq!(theme.plane_color(PlaneKind::Jet), Color::Red);
+        assert_eq!(theme.plane_color(PlaneKind::Small), Color::Green);
+    }
+}
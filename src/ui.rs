@@ -0,0 +1,736 @@
+use std::str::FromStr;
+
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+};
+
+use crate::{
+    error::Error,
+    heatmap::Heatmap,
+    plane::Destination,
+    theme::Theme,
+    world::{
+        column_ruler, DirectionCardinal, DirectionGrid, PlaneKind, Pos, World, WorldTile,
+        RULER_WIDTH,
+    },
+};
+
+/// Which arrows show the heading of a plane
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GlyphSet {
+    /// The four ASCII arrows `^ > v <`, diagonal headings show their north or south part
+    #[default]
+    Cardinal,
+    /// Eight unicode arrows, one for every heading
+    Full,
+}
+
+impl GlyphSet {
+    /// The other glyph set
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Cardinal => Self::Full,
+            Self::Full => Self::Cardinal,
+        }
+    }
+
+    /// The arrow for a plane flying `heading`
+    pub fn heading(self, heading: DirectionCardinal) -> char {
+        use DirectionCardinal::*;
+        match self {
+            Self::Cardinal => match heading {
+                North | NorthEast | NorthWest => '^',
+                South | SouthEast | SouthWest => 'v',
+                East => '>',
+                West => '<',
+            },
+            Self::Full => heading.arrow(),
+        }
+    }
+}
+
+/// Whether the screen gets colors
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Colors unless the terminal does not want them, see [`terminal_has_colors`]
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// Whether to draw with colors
+    pub fn colors(self) -> bool {
+        match self {
+            Self::Auto => terminal_has_colors(),
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
+impl FromStr for ColorMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "always" | "on" => Ok(Self::Always),
+            "never" | "off" => Ok(Self::Never),
+            _ => Err(Error::UnknownColorMode(s.to_string())),
+        }
+    }
+}
+
+/// The terminal shows colors, unless `NO_COLOR` is set or `TERM` is `dumb`
+pub fn terminal_has_colors() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let dumb = std::env::var("TERM").is_ok_and(|term| term == "dumb");
+    !no_color && !dumb
+}
+
+/// Take all colors off what was drawn into `buffer`
+///
+/// Colored text becomes bold and colored backgrounds become reversed, so tiles that were
+/// only told apart by their colors still stand out.
+pub fn strip_colors(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        if cell.fg != Color::Reset {
+            cell.modifier |= Modifier::BOLD;
+        }
+        if cell.bg != Color::Reset {
+            cell.modifier |= Modifier::REVERSED;
+        }
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+        cell.underline_color = Color::Reset;
+    }
+}
+
+/// The style of planes flying to `destination`
+///
+/// Every destination of the world gets its own color of the theme's palette as long as there
+/// are enough colors, after that colors are used again.
+pub fn destination_style(world: &World, theme: &Theme, destination: Destination) -> Style {
+    let idx = world
+        .destinations()
+        .iter()
+        .position(|d| *d == destination)
+        .unwrap_or_default();
+    Style::new().fg(theme.palette_color(idx)).bold()
+}
+
+/// One line per destination in the color of its planes
+pub fn destination_legend(world: &World, theme: &Theme) -> Vec<Line<'static>> {
+    world
+        .destinations()
+        .into_iter()
+        .map(|d| Line::styled(d.to_string(), destination_style(world, theme, d)))
+        .collect()
+}
+
+/// Row and column of the top left tile in a rendered map
+fn map_offsets(world: &World, rulers: bool) -> (usize, usize) {
+    if rulers {
+        (column_ruler(world.width()).len() + 1, RULER_WIDTH + 2)
+    } else {
+        (1, 2)
+    }
+}
+
+/// Style two character wide cells of `rendered`, given by row and column
+///
+/// If a cell is given more than once, the first style wins. Everything else gets `base`.
+fn style_cells(rendered: &str, cells: &[(usize, usize, Style)], base: Style) -> Vec<Line<'static>> {
+    rendered
+        .lines()
+        .enumerate()
+        .map(|(row, line)| {
+            let mut row_styles: Vec<(usize, Style)> = cells
+                .iter()
+                .filter(|(r, _, _)| *r == row)
+                .map(|(_, col, style)| (*col, *style))
+                .collect();
+            if row_styles.is_empty() {
+                return Line::from(line.to_string()).style(base);
+            }
+            row_styles.sort_by_key(|(col, _)| *col);
+
+            let chars: Vec<char> = line.chars().collect();
+            let mut spans = Vec::new();
+            let mut start = 0;
+            for (col, style) in row_styles {
+                if col < start || col + 2 > chars.len() {
+                    continue;
+                }
+                spans.push(Span::raw(chars[start..col].iter().collect::<String>()));
+                spans.push(Span::styled(
+                    chars[col..col + 2].iter().collect::<String>(),
+                    style,
+                ));
+                start = col + 2;
+            }
+            spans.push(Span::raw(chars[start..].iter().collect::<String>()));
+            Line::from(spans).style(base)
+        })
+        .collect()
+}
+
+/// Style single tiles of a rendered map
+///
+/// `rendered` is the output of [`World`]'s `Display` or of
+/// [`World::render_with_rulers`] if `rulers` is set. Every tile is two characters wide, the
+/// tiles in `styles` get their style, everything else stays unstyled.
+pub fn styled_map(
+    world: &World,
+    rendered: &str,
+    rulers: bool,
+    styles: &[(Pos, Style)],
+) -> Vec<Line<'static>> {
+    let (row_offset, col_offset) = map_offsets(world, rulers);
+    let cells: Vec<(usize, usize, Style)> = styles
+        .iter()
+        .map(|(pos, style)| (pos.y + row_offset, col_offset + 2 * pos.x, *style))
+        .collect();
+    style_cells(rendered, &cells, Style::default())
+}
+
+/// How planes of `kind` are shown on the map and in the roster
+pub fn plane_style(theme: &Theme, kind: PlaneKind) -> Style {
+    Style::new().fg(theme.plane_color(kind)).bold()
+}
+
+/// The rendered map in the colors of `theme`
+///
+/// Walls, exits, tiles and planes get their color from the theme, the tiles in `styles` get
+/// their own style instead. See [`styled_map`] for what `rendered` and `rulers` are.
+pub fn themed_map(
+    world: &World,
+    theme: &Theme,
+    rendered: &str,
+    rulers: bool,
+    styles: &[(Pos, Style)],
+) -> Vec<Line<'static>> {
+    let (row_offset, col_offset) = map_offsets(world, rulers);
+    let cell = |pos: Pos| (pos.y + row_offset, col_offset + 2 * pos.x);
+
+    let mut cells: Vec<(usize, usize, Style)> = styles
+        .iter()
+        .map(|(pos, style)| {
+            let (row, col) = cell(*pos);
+            (row, col, *style)
+        })
+        .collect();
+    for plane in world.planes() {
+        let (row, col) = cell(plane.pos);
+        cells.push((row, col, plane_style(theme, plane.kind)));
+    }
+    for y in 0..world.height() {
+        for x in 0..world.width() {
+            let pos = Pos { x, y };
+            let color = match world.tile(pos) {
+                Some(WorldTile::Empty) | None => theme.empty,
+                Some(WorldTile::Route) => theme.route,
+                Some(WorldTile::Airport(..)) => theme.airport,
+                Some(WorldTile::Beacon(_)) => theme.beacon,
+                Some(WorldTile::SlowZone) => theme.slow_zone,
+                Some(WorldTile::Obstacle) => theme.obstacle,
+            };
+            let (row, col) = cell(pos);
+            cells.push((row, col, Style::new().fg(color)));
+        }
+    }
+    for (_, exit) in world.exits() {
+        let (row, col) = match exit.wall_direction {
+            DirectionGrid::Up => (row_offset - 1, col_offset + 2 * exit.wall_pos),
+            DirectionGrid::Down => (row_offset + world.height(), col_offset + 2 * exit.wall_pos),
+            DirectionGrid::Left => (row_offset + exit.wall_pos, col_offset - 2),
+            DirectionGrid::Right => (row_offset + exit.wall_pos, col_offset + 2 * world.width()),
+        };
+        cells.push((row, col, Style::new().fg(theme.exit).bold()));
+    }
+    style_cells(rendered, &cells, Style::new().fg(theme.border))
+}
+
+/// The upper half of a character is the foreground, the lower half the background
+const HALF_BLOCK: &str = "▀";
+
+/// The terminal row and column of the half block that shows the tile at `pos` in a
+/// [`half_block_map`], and whether it is the lower half
+///
+/// The walls take the first row and column of half blocks.
+pub fn half_block_cell(pos: Pos) -> (usize, usize, bool) {
+    (pos.y.div_ceil(2), pos.x + 1, pos.y.is_multiple_of(2))
+}
+
+/// The map as half blocks, two tiles above each other in every character
+///
+/// Terminal characters are about twice as high as wide, so this map has about the same
+/// proportions as the world. With `colors`, every tile is a half block in its color: planes in
+/// the color of their kind, the walls and exits in the colors of the theme. The tiles in
+/// `styles` get their background color, or their foreground color if they have no background.
+/// Without colors, every tile that is not empty is a half block.
+///
+/// The `selected` plane is shown with its id and height, like on the normal map.
+pub fn half_block_map(
+    world: &World,
+    theme: &Theme,
+    styles: &[(Pos, Style)],
+    selected: Option<char>,
+    colors: bool,
+) -> Vec<Line<'static>> {
+    let (width, height) = (world.width() + 2, world.height() + 2);
+    // the color of the tile in column `x` and row `y` of the map with its walls, None if empty
+    let color = |x: usize, y: usize| -> Option<Color> {
+        if x == 0 || y == 0 || x + 1 == width || y + 1 == height {
+            let is_exit = world.exits().any(|(_, exit)| match exit.wall_direction {
+                DirectionGrid::Up => y == 0 && x == exit.wall_pos + 1,
+                DirectionGrid::Down => y + 1 == height && x == exit.wall_pos + 1,
+                DirectionGrid::Left => x == 0 && y == exit.wall_pos + 1,
+                DirectionGrid::Right => x + 1 == width && y == exit.wall_pos + 1,
+            });
+            return Some(if is_exit { theme.exit } else { theme.border });
+        }
+        let pos = Pos { x: x - 1, y: y - 1 };
+        if let Some((_, style)) = styles.iter().find(|(p, _)| *p == pos) {
+            if let Some(color) = style.bg.or(style.fg) {
+                return Some(color);
+            }
+        }
+        if let Some(plane) = world.plane_at(pos) {
+            return Some(theme.plane_color(plane.kind));
+        }
+        match world.tile(pos) {
+            Some(WorldTile::Empty) | None => None,
+            Some(WorldTile::Route) => Some(theme.route),
+            Some(WorldTile::Airport(..)) => Some(theme.airport),
+            Some(WorldTile::Beacon(_)) => Some(theme.beacon),
+            Some(WorldTile::SlowZone) => Some(theme.slow_zone),
+            Some(WorldTile::Obstacle) => Some(theme.obstacle),
+        }
+    };
+    let mut lines: Vec<Vec<Span<'static>>> = (0..height.div_ceil(2))
+        .map(|row| {
+            (0..width)
+                .map(|x| {
+                    let upper = color(x, 2 * row);
+                    let lower = (2 * row + 1 < height)
+                        .then(|| color(x, 2 * row + 1))
+                        .flatten();
+                    if colors {
+                        let style = Style::new().fg(upper.unwrap_or(Color::Reset));
+                        let style = if 2 * row + 1 < height {
+                            style.bg(lower.unwrap_or(Color::Reset))
+                        } else {
+                            style
+                        };
+                        Span::styled(HALF_BLOCK, style)
+                    } else {
+                        Span::raw(match (upper.is_some(), lower.is_some()) {
+                            (false, false) => " ",
+                            (true, false) => HALF_BLOCK,
+                            (false, true) => "▄",
+                            (true, true) => "█",
+                        })
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    if let Some(plane) = selected.and_then(|id| world.plane(id)) {
+        let (row, col, _) = half_block_cell(plane.pos);
+        let style = if colors {
+            plane_style(theme, plane.kind).reversed()
+        } else {
+            Style::new().bold()
+        };
+        for (i, c) in plane.to_string().chars().enumerate() {
+            if let Some(span) = lines[row].get_mut(col + i) {
+                *span = Span::styled(c.to_string(), style);
+            }
+        }
+    }
+    lines.into_iter().map(Line::from).collect()
+}
+
+/// The style of every plane by its destination, for [`styled_map`]
+pub fn destination_styles(world: &World, theme: &Theme) -> Vec<(Pos, Style)> {
+    world
+        .planes()
+        .map(|plane| {
+            (
+                plane.pos,
+                destination_style(world, theme, plane.destination),
+            )
+        })
+        .collect()
+}
+
+/// The style of every plane by the controller in charge of it, for [`styled_map`]
+pub fn controller_styles(world: &World, theme: &Theme) -> Vec<(Pos, Style)> {
+    world
+        .planes()
+        .map(|plane| {
+            let color = theme.palette_color(plane.controller as usize);
+            (plane.pos, Style::new().fg(color).bold())
+        })
+        .collect()
+}
+
+/// The map with every plane colored by its destination
+pub fn map_by_destination(
+    world: &World,
+    theme: &Theme,
+    rendered: &str,
+    rulers: bool,
+) -> Vec<Line<'static>> {
+    styled_map(world, rendered, rulers, &destination_styles(world, theme))
+}
+
+/// Background colors of the heatmap, from the tiles planes were on least to the busiest
+const HEAT_COLORS: [Color; 4] = [Color::Blue, Color::Green, Color::Yellow, Color::Red];
+
+/// The tiles planes were on colored by how busy they were, for [`styled_map`]
+///
+/// Tiles no plane was on are left alone.
+pub fn heatmap_styles(heatmap: &Heatmap) -> Vec<(Pos, Style)> {
+    heatmap
+        .tiles()
+        .filter(|pos| heatmap.count(*pos) > 0)
+        .map(|pos| {
+            let level = (heatmap.heat(pos) * HEAT_COLORS.len() as f64).ceil() as usize;
+            let color = HEAT_COLORS[level.clamp(1, HEAT_COLORS.len()) - 1];
+            (pos, Style::new().bg(color))
+        })
+        .collect()
+}
+
+/// Faint markers on the tiles plane `id` flies over in the next `ticks` ticks, for
+/// [`styled_map`]
+///
+/// See [`World::projected_positions`] for how the path is worked out.
+pub fn ghost_styles(world: &World, theme: &Theme, id: char, ticks: usize) -> Vec<(Pos, Style)> {
+    let style = Style::new().bg(theme.ghost);
+    let mut positions = world.projected_positions(id, ticks);
+    positions.dedup();
+    positions.into_iter().map(|pos| (pos, style)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use ratatui::style::Color;
+
+    use crate::plane::Plane;
+
+    use super::*;
+
+    fn world() -> World {
+        let mut world = World::new(10, 10);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 2, 0)
+            .unwrap()
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 2, 1)
+            .unwrap();
+        for (id, x, destination) in [
+            ('a', 1, Destination::Exit(0)),
+            ('b', 3, Destination::Exit(0)),
+            ('c', 5, Destination::Exit(1)),
+        ] {
+            world.insert_plane(Plane::new(
+                [x, 4].into(),
+                DirectionCardinal::North,
+                PlaneKind::Jet,
+                id,
+                destination,
+            ));
+        }
+        world
+    }
+
+    #[test]
+    fn test_ui_destination_styles() {
+        let world = world();
+        let theme = Theme::default();
+        let exit0 = destination_style(&world, &theme, Destination::Exit(0));
+        assert_eq!(
+            exit0,
+            destination_style(&world, &theme, Destination::Exit(0))
+        );
+        assert_ne!(
+            exit0,
+            destination_style(&world, &theme, Destination::Exit(1))
+        );
+        assert_eq!(destination_legend(&world, &theme).len(), 2);
+
+        for rulers in [false, true] {
+            let rendered = if rulers {
+                world.render_with_rulers()
+            } else {
+                world.to_string()
+            };
+            let lines = map_by_destination(&world, &theme, &rendered, rulers);
+            let styled: Vec<(String, Style)> = lines
+                .iter()
+                .flat_map(|line| line.spans.iter())
+                .filter(|span| span.style != Style::default())
+                .map(|span| (span.content.to_string(), span.style))
+                .collect();
+            assert_eq!(
+                styled,
+                vec![
+                    ("A7".to_string(), exit0),
+                    ("B7".to_string(), exit0),
+                    (
+                        "C7".to_string(),
+                        destination_style(&world, &theme, Destination::Exit(1))
+                    ),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_ui_ghost_styles() {
+        let world = world();
+        let theme = Theme::default();
+        let ghosts = ghost_styles(&world, &theme, 'a', 3);
+        assert_eq!(
+            ghosts.iter().map(|(pos, _)| *pos).collect::<Vec<_>>(),
+            vec![[1, 3].into(), [1, 2].into(), [1, 1].into()]
+        );
+
+        let lines = styled_map(&world, &world.to_string(), false, &ghosts);
+        let ghost_rows: Vec<usize> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.spans.iter().any(|s| s.style == ghosts[0].1))
+            .map(|(row, _)| row)
+            .collect();
+        assert_eq!(ghost_rows, vec![2, 3, 4]);
+        assert!(ghost_styles(&world, &theme, 'x', 3).is_empty());
+    }
+
+    #[test]
+    fn test_ui_half_block_map() {
+        let mut world = World::new(3, 3);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 1, 0)
+            .unwrap()
+            .place_tile(WorldTile::Route, [0, 0])
+            .unwrap()
+            .place_tile(WorldTile::Obstacle, [2, 1])
+            .unwrap();
+        world.insert_plane(Plane::new(
+            [1, 2].into(),
+            DirectionCardinal::North,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        ));
+        let theme = Theme::default();
+        let ghost = Style::new().bg(Color::Magenta);
+        let styles = [([2, 2].into(), ghost)];
+        let lines = half_block_map(&world, &theme, &styles, None, true);
+        let cells: Vec<Vec<(Option<Color>, Option<Color>)>> = lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .inspect(|span| assert_eq!(span.content, HALF_BLOCK))
+                    .map(|span| (span.style.fg, span.style.bg))
+                    .collect()
+            })
+            .collect();
+        let empty = Some(Color::Reset);
+        let wall = Some(theme.border);
+        assert_eq!(
+            cells,
+            vec![
+                vec![
+                    (wall, wall),
+                    (wall, Some(theme.route)),
+                    (Some(theme.exit), empty),
+                    (wall, empty),
+                    (wall, wall),
+                ],
+                vec![
+                    (wall, wall),
+                    (empty, empty),
+                    (empty, Some(theme.jet)),
+                    (Some(theme.obstacle), Some(Color::Magenta)),
+                    (wall, wall),
+                ],
+                vec![(wall, None); 5],
+            ]
+        );
+
+        let text = |lines: Vec<Line>| -> Vec<String> {
+            lines.iter().map(|line| line.to_string()).collect()
+        };
+        assert_eq!(
+            text(half_block_map(&world, &theme, &styles, Some('a'), true))[1],
+            "▀▀A7▀"
+        );
+        assert_eq!(
+            text(half_block_map(&world, &theme, &[], None, false)),
+            vec!["██▀▀█", "█ ▄▀█", "▀▀▀▀▀"]
+        );
+
+        for (pos, cell) in [
+            ([0, 0], (0, 1, true)),
+            ([2, 1], (1, 3, false)),
+            ([1, 2], (1, 2, true)),
+        ] {
+            assert_eq!(half_block_cell(pos.into()), cell);
+        }
+    }
+
+    #[test]
+    fn test_ui_strip_colors() {
+        use ratatui::{layout::Rect, widgets::Paragraph, widgets::Widget};
+
+        let world = world();
+        let theme = Theme::preset("dark").unwrap();
+        let ghosts = ghost_styles(&world, &theme, 'a', 3);
+        let lines = themed_map(&world, &theme, &world.to_string(), false, &ghosts);
+        let area = Rect::new(0, 0, 24, 12);
+        let mut buffer = Buffer::empty(area);
+        Paragraph::new(lines).render(area, &mut buffer);
+        let colored = buffer.clone();
+        assert!(colored.content.iter().any(|cell| cell.bg != Color::Reset));
+        assert!(colored.content.iter().any(|cell| cell.fg != Color::Reset));
+
+        strip_colors(&mut buffer);
+        for (before, after) in colored.content.iter().zip(&buffer.content) {
+            assert_eq!(after.fg, Color::Reset);
+            assert_eq!(after.bg, Color::Reset);
+            assert_eq!(after.symbol(), before.symbol());
+            assert_eq!(
+                after.modifier.contains(Modifier::REVERSED),
+                before.bg != Color::Reset
+            );
+        }
+
+        assert_eq!("never".parse::<ColorMode>().unwrap(), ColorMode::Never);
+        assert!(!ColorMode::Never.colors());
+        assert!(ColorMode::Always.colors());
+        assert!("sometimes".parse::<ColorMode>().is_err());
+    }
+
+    #[test]
+    fn test_ui_heading_glyphs() {
+        use DirectionCardinal::*;
+
+        let cardinal: String = DirectionCardinal::ALL
+            .iter()
+            .map(|d| GlyphSet::Cardinal.heading(*d))
+            .collect();
+        assert_eq!(cardinal, "^^>vvv<^");
+        assert!(cardinal.is_ascii());
+        let full: String = DirectionCardinal::ALL
+            .iter()
+            .map(|d| GlyphSet::Full.heading(*d))
+            .collect();
+        assert_eq!(full, "↑↗→↘↓↙←↖");
+
+        for (diagonal, cardinal) in [
+            (NorthEast, North),
+            (SouthEast, South),
+            (SouthWest, South),
+            (NorthWest, North),
+        ] {
+            assert_eq!(
+                GlyphSet::Cardinal.heading(diagonal),
+                GlyphSet::Cardinal.heading(cardinal)
+            );
+        }
+        assert_eq!(GlyphSet::default().toggled(), GlyphSet::Full);
+    }
+
+    #[test]
+    fn test_ui_plane_kinds_look_different() {
+        let mut world = World::new(10, 10);
+        for (id, x, kind) in [('a', 1, PlaneKind::Jet), ('B', 5, PlaneKind::Small)] {
+            let mut plane = Plane::new(
+                [x, 4].into(),
+                DirectionCardinal::North,
+                kind,
+                id,
+                Destination::Exit(0),
+            );
+            plane.height = 3;
+            world.insert_plane(plane);
+        }
+        let theme = Theme::default();
+        let lines = themed_map(&world, &theme, &world.to_string(), false, &[]);
+        let style_of = |content: &str| {
+            lines
+                .iter()
+                .flat_map(|line| line.spans.iter())
+                .find(|span| span.content == content)
+                .map(|span| span.style)
+        };
+        let jet = style_of(&world.plane('a').unwrap().to_string());
+        let small = style_of(&world.plane('B').unwrap().to_string());
+        assert_eq!(jet, Some(plane_style(&theme, PlaneKind::Jet)));
+        assert_eq!(small, Some(plane_style(&theme, PlaneKind::Small)));
+        assert_ne!(jet, small);
+    }
+
+    #[test]
+    fn test_ui_themed_map() {
+        let mut world = world();
+        world.place_tile(WorldTile::Route, [7, 7]).unwrap();
+        let theme = Theme {
+            route: Color::LightGreen,
+            exit: Color::Magenta,
+            jet: Color::Cyan,
+            ..Theme::preset("dark").unwrap()
+        };
+        let ghost = Style::new().bg(Color::Red);
+        let lines = themed_map(
+            &world,
+            &theme,
+            &world.to_string(),
+            false,
+            &[([1, 4].into(), ghost)],
+        );
+        let style_of = |content: &str| {
+            lines
+                .iter()
+                .flat_map(|line| line.spans.iter())
+                .find(|span| span.content == content)
+                .map(|span| span.style)
+        };
+        assert_eq!(style_of("+ "), Some(Style::new().fg(Color::LightGreen)));
+        assert_eq!(style_of("↓0"), Some(Style::new().fg(Color::Magenta).bold()));
+        assert_eq!(style_of("↑1"), Some(Style::new().fg(Color::Magenta).bold()));
+        assert_eq!(style_of("A7"), Some(ghost));
+        assert_eq!(style_of("B7"), Some(Style::new().fg(Color::Cyan).bold()));
+        assert!(lines.iter().all(|line| line.style.fg == Some(theme.border)));
+    }
+
+    #[test]
+    fn test_ui_heatmap_styles() {
+        let mut world = world();
+        let mut heatmap = Heatmap::new(&world);
+        heatmap.record(&world);
+        world.plane_mut('c').unwrap().pos = [5, 5].into();
+        for _ in 0..3 {
+            heatmap.record(&world);
+        }
+        let styles = heatmap_styles(&heatmap);
+        assert_eq!(styles.len(), 4);
+        let style_at = |pos: Pos| styles.iter().find(|(p, _)| *p == pos).map(|(_, s)| *s);
+        assert_eq!(style_at([1, 4].into()), Some(Style::new().bg(Color::Red)));
+        assert_eq!(style_at([5, 4].into()), Some(Style::new().bg(Color::Blue)));
+        assert_eq!(
+            style_at([5, 5].into()),
+            Some(Style::new().bg(Color::Yellow))
+        );
+        assert_eq!(style_at([0, 0].into()), None);
+    }
+}
@@ -0,0 +1,130 @@
+//! Wind that blows over the whole map
+//!
+//! Wind does not move planes, it changes how much fuel they burn. A plane flying with the wind
+//! saves fuel, a plane flying into it burns more, see
+//! [`World::set_wind`](crate::world::World::set_wind).
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::{error::Error, world::DirectionCardinal};
+
+/// The strongest wind there is
+pub const MAX_WIND_STRENGTH: u8 = 3;
+/// Wind changes the fuel of a plane once every this many ticks, by its strength
+pub const WIND_FUEL_TICKS: usize = 4;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Wind {
+    /// The direction the wind blows to, not where it comes from
+    pub direction: DirectionCardinal,
+    /// From 1 to [`MAX_WIND_STRENGTH`]
+    pub strength: u8,
+}
+
+/// How the wind affects a plane, depending on its heading
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WindEffect {
+    /// The wind blows the way the plane flies, the plane saves fuel
+    Tailwind,
+    /// The plane flies into the wind and burns more fuel
+    Headwind,
+    /// The wind blows across the heading of the plane and changes nothing
+    Crosswind,
+}
+
+impl Wind {
+    /// Wind blowing to `direction`, the strength is kept between 1 and [`MAX_WIND_STRENGTH`]
+    pub fn new(direction: DirectionCardinal, strength: u8) -> Self {
+        Self {
+            direction,
+            strength: strength.clamp(1, MAX_WIND_STRENGTH),
+        }
+    }
+
+    /// How the wind affects a plane flying `heading`
+    ///
+    /// Headings less than a right angle away from the wind have a tailwind, headings less than
+    /// a right angle away from the opposite direction have a headwind.
+    pub fn effect(self, heading: DirectionCardinal) -> WindEffect {
+        let (wx, wy) = self.direction.offset();
+        let (hx, hy) = heading.offset();
+        match (wx * hx + wy * hy).signum() {
+            1 => WindEffect::Tailwind,
+            -1 => WindEffect::Headwind,
+            _ => WindEffect::Crosswind,
+        }
+    }
+}
+
+impl WindEffect {
+    /// A mark for the roster, `+` for a tailwind, `-` for a headwind and nothing otherwise
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Tailwind => "+",
+            Self::Headwind => "-",
+            Self::Crosswind => "",
+        }
+    }
+}
+
+impl Display for Wind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.direction.arrow(), self.strength)
+    }
+}
+
+/// Parses wind like `ne 2` or `northeast 2`, the direction the wind blows to and its strength
+impl FromStr for Wind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = || Error::BadWind(s.to_string());
+        let mut parts = s.split_whitespace();
+        let direction = parts.next().ok_or_else(bad)?.parse()?;
+        let strength: u8 = parts.next().ok_or_else(bad)?.parse().map_err(|_| bad())?;
+        if parts.next().is_some() || strength == 0 || strength > MAX_WIND_STRENGTH {
+            return Err(bad());
+        }
+        Ok(Self::new(direction, strength))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wind_effect_by_heading() {
+        let wind = Wind::new(DirectionCardinal::East, 2);
+        assert_eq!(wind.effect(DirectionCardinal::East), WindEffect::Tailwind);
+        assert_eq!(
+            wind.effect(DirectionCardinal::NorthEast),
+            WindEffect::Tailwind
+        );
+        assert_eq!(wind.effect(DirectionCardinal::West), WindEffect::Headwind);
+        assert_eq!(
+            wind.effect(DirectionCardinal::SouthWest),
+            WindEffect::Headwind
+        );
+        assert_eq!(wind.effect(DirectionCardinal::North), WindEffect::Crosswind);
+    }
+
+    #[test]
+    fn test_wind_parse() {
+        assert_eq!(
+            "ne 2".parse::<Wind>().unwrap(),
+            Wind::new(DirectionCardinal::NorthEast, 2)
+        );
+        assert_eq!("ne 2".parse::<Wind>().unwrap().to_string(), "↗2");
+        for bad in ["ne", "ne 0", "ne 9", "ne two", "ne 2 3"] {
+            assert!(
+                matches!(bad.parse::<Wind>(), Err(Error::BadWind(_))),
+                "{bad}"
+            );
+        }
+        assert!(matches!(
+            "up 2".parse::<Wind>(),
+            Err(Error::UnknownDirection(_))
+        ));
+    }
+}
@@ -1,36 +1,188 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt::Display,
+    str::FromStr,
+};
 
-use tracing::debug;
+use rand::{seq::IndexedRandom, Rng};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
 
 use crate::{
     error::Error,
-    plane::{Destination, Plane},
+    level::builtin,
+    plane::{
+        Destination, FuelReading, FuelUnit, Order, Plane, PlaneSpecs, QueuedOrder, Trigger,
+        MAX_HEIGHT,
+    },
+    wind::{Wind, WindEffect, WIND_FUEL_TICKS},
 };
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum State {
-    Onging,
+    Ongoing,
     PlaneCollision(Plane, Plane),
     WrongExit(Plane, u8),
     WrongAirport(Plane, u8),
     PlaneTouchesWall(Plane, DirectionGrid, usize),
     PlaneCrash(Plane),
     PlaneNoFuel(Plane),
+    /// A plane flew into an [obstacle](WorldTile::Obstacle)
+    PlaneHitObstacle(Plane),
+}
+
+/// How the planes of a game fared, counted since the world was last reset
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+    /// Planes that left through their exit
+    pub exits: u64,
+    /// Planes that landed at their airport
+    pub landings: u64,
+    /// Planes that crashed into the ground, an obstacle or the wall
+    pub crashes: u64,
+    /// Planes that flew into another plane, counted once per collision
+    pub collisions: u64,
+    /// Planes that left or landed at the wrong place
+    pub wrong_destinations: u64,
+    /// Planes that ran out of fuel
+    pub out_of_fuel: u64,
+    /// Fuel the planes that left or landed had left, in ticks
+    #[serde(default)]
+    pub fuel_left: u64,
+}
+
+/// The planes in a world and what they were told, to save a game and pick it up again later
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Traffic {
+    /// Used to name the next plane that comes in
+    pub plane_counter: u64,
+    pub stats: Stats,
+    #[serde(default)]
+    pub planes: Vec<TrafficPlane>,
+}
+
+/// A plane of [`Traffic`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrafficPlane {
+    pub plane: Plane,
+    #[serde(default)]
+    pub orders: Vec<QueuedOrder>,
+    /// The heading the plane had before the [panic hold](World::panic_hold)
+    pub panic_heading: Option<DirectionCardinal>,
 }
 
+/// Ticks a new plane is ignored by collision, wall, exit and landing checks, unless the world
+/// says otherwise
+pub const DEFAULT_SPAWN_IMMUNITY: usize = 2;
+
+/// Worlds need at least this many tiles in each direction
+pub const MIN_WORLD_SIZE: usize = 3;
+
+/// Height a plane climbs back to when it has to abort a landing
+pub const GO_AROUND_HEIGHT: u8 = 2;
+
+/// Extra fuel a diagonal move costs if the world has a diagonal fuel cost
+pub const DIAGONAL_EXTRA_FUEL: usize = 1;
+
+/// Planes closer than this many tiles to each other get a proximity warning
+pub const PROXIMITY_RADIUS: usize = 2;
+
+/// Ticks before a proximity warning for the same two planes is repeated, unless the world says
+/// otherwise
+pub const DEFAULT_PROXIMITY_COOLDOWN: usize = 10;
+
+/// Recoverable problems found during a tick, the game goes on after these
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Warning {
+    /// A plane had a destination that does not exist in the world and got a new one
+    UnknownDestination(char, Destination),
+    /// The plane will run out of fuel on the next tick, with the fuel it has left in the
+    /// world's [`FuelUnit`]
+    FuelCritical(char, FuelReading),
+    /// Both planes will touch down at the airport in the same tick
+    ConvergingLanding(char, char, u8),
+    /// The plane had to abort its landing because another plane landed at the airport first
+    GoAround(char, u8),
+    /// The plane reached its destination without clearance and turned around
+    NotCleared(char, Destination),
+    /// The plane reached a beacon without orders and holds there
+    Holding(char, u8),
+    /// The plane got an order for a beacon that is not ahead on its path, the order will
+    /// likely never be carried out
+    BeaconNotAhead(char, u8),
+    /// The plane reached its exit below the exit height of its kind and turned around
+    TooLowToExit(char, u8),
+    /// The two planes are too close to each other, see [`World::proximity_conflicts`]
+    Proximity(char, char),
+    /// The plane flew over a beacon at a height its [`AltitudeRestriction`] does not allow
+    CrossingRestriction(char, u8, AltitudeRestriction),
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownDestination(pid, dest) => {
+                write!(
+                    f,
+                    "Plane {pid} had an unknown destination ({dest}) and was rerouted"
+                )
+            }
+            Self::FuelCritical(pid, fuel) => {
+                write!(f, "Plane {pid} runs out of fuel next tick! ({fuel} left)")
+            }
+            Self::ConvergingLanding(a, b, aid) => {
+                write!(
+                    f,
+                    "Planes {a} and {b} both land at airport {aid} next tick!"
+                )
+            }
+            Self::GoAround(pid, aid) => {
+                write!(f, "Plane {pid} goes around, airport {aid} is occupied")
+            }
+            Self::NotCleared(pid, dest) => {
+                write!(f, "Plane {pid} was not cleared for {dest} and goes around")
+            }
+            Self::Holding(pid, bid) => {
+                write!(
+                    f,
+                    "Plane {pid} holds at beacon {bid}, waiting for a heading"
+                )
+            }
+            Self::BeaconNotAhead(pid, bid) => {
+                write!(
+                    f,
+                    "Plane {pid} will not pass beacon {bid} on its current path"
+                )
+            }
+            Self::TooLowToExit(pid, eid) => {
+                write!(
+                    f,
+                    "Plane {pid} is too low to leave through exit {eid} and turns around"
+                )
+            }
+            Self::Proximity(a, b) => write!(f, "Planes {a} and {b} are too close!"),
+            Self::CrossingRestriction(pid, bid, restriction) => {
+                write!(f, "Plane {pid} had to cross beacon {bid} {restriction}")
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Pos {
     pub x: usize,
     pub y: usize,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PlaneKind {
     Small,
     Jet,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DirectionGrid {
     Up,
     Down,
@@ -38,7 +190,8 @@ pub enum DirectionGrid {
     Right,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DirectionCardinal {
     North,
     East,
@@ -50,14 +203,97 @@ pub enum DirectionCardinal {
     SouthWest,
 }
 
-#[derive(Debug)]
+/// What a plane does when it reaches a beacon and has no order for that beacon
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BeaconPolicy {
+    /// Fly on straight ahead
+    #[default]
+    Continue,
+    /// Hold at the beacon until it gets a new heading
+    Hold,
+}
+
+/// The height a plane has to be at when it flies over a beacon
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AltitudeRestriction {
+    At(u8),
+    AtOrAbove(u8),
+    AtOrBelow(u8),
+}
+
+impl AltitudeRestriction {
+    /// Whether a plane at `height` keeps to the restriction
+    pub fn allows(self, height: u8) -> bool {
+        match self {
+            Self::At(h) => height == h,
+            Self::AtOrAbove(h) => height >= h,
+            Self::AtOrBelow(h) => height <= h,
+        }
+    }
+}
+
+impl Display for AltitudeRestriction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::At(h) => write!(f, "at {h}"),
+            Self::AtOrAbove(h) => write!(f, "at or above {h}"),
+            Self::AtOrBelow(h) => write!(f, "at or below {h}"),
+        }
+    }
+}
+
+/// Which headings planes can fly in
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum MovementMode {
+    /// All eight directions
+    #[default]
+    EightWay,
+    /// Experimental: only east, west and the diagonals, which feels like a hex grid
+    Hex,
+}
+
+#[derive(Clone, Debug)]
 pub struct World {
     x: usize,
     y: usize,
     tiles: Vec<Vec<WorldTile>>,
     planes: HashMap<char, Plane>,
-    exits: HashMap<u8, Exit>,
-    plane_counter: u8,
+    /// Orders the planes carry out later, by plane id
+    orders: HashMap<char, Vec<QueuedOrder>>,
+    /// Headings of the planes the panic button put into holding, by plane id
+    panic_held: HashMap<char, DirectionCardinal>,
+    exits: BTreeMap<u8, Exit>,
+    /// Planes may only fly along routes, this matters for validating the world
+    routes_mandatory: bool,
+    /// Diagonal moves burn [`DIAGONAL_EXTRA_FUEL`] more fuel than moves along an axis
+    diagonal_fuel_cost: bool,
+    wind: Option<Wind>,
+    movement_mode: MovementMode,
+    /// Planes only leave or land after the player cleared them
+    clearance_required: bool,
+    /// Commands that would fly a plane into a wall or obstacle right away are rejected
+    safety_checks: bool,
+    beacon_policy: BeaconPolicy,
+    /// Heights planes have to cross some beacons at, by beacon id
+    beacon_restrictions: BTreeMap<u8, AltitudeRestriction>,
+    /// Ticks new planes are immune, see [`DEFAULT_SPAWN_IMMUNITY`]
+    spawn_immunity: usize,
+    /// Planes at the same height at most this many tiles apart collide
+    collision_radius: usize,
+    /// Ticks before a proximity warning is repeated, see [`DEFAULT_PROXIMITY_COOLDOWN`]
+    proximity_cooldown: usize,
+    /// Pairs of planes in conflict that were warned about, with the ticks since the warning
+    proximity_warned: HashMap<(char, char), usize>,
+    /// How many controllers share the map, new planes are handed to them in turn
+    controllers: u8,
+    /// How the fuel of planes is shown in warnings and the roster
+    fuel_unit: FuelUnit,
+    specs: PlaneSpecs,
+    /// Planes that came in so far, picks the id and controller of the next one
+    plane_counter: u64,
+    warnings: Vec<Warning>,
+    stats: Stats,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -65,6 +301,14 @@ pub struct Exit {
     pub wall_direction: DirectionGrid,
     pub plane_out_direction: DirectionCardinal,
     pub wall_pos: usize,
+    /// Height at which planes coming in through this exit start, if None the
+    /// [`PlaneSpec`](crate::plane::PlaneSpec) of the plane decides
+    pub start_height: Option<u8>,
+    /// How likely new planes come in through this exit compared to the others
+    pub spawn_weight: u32,
+    /// The exit has corridors at different heights: planes at least this many levels apart
+    /// can use it at the same time. If None, only one plane at a time can be at the exit.
+    pub lane_separation: Option<u8>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -73,18 +317,70 @@ pub enum WorldTile {
     Route,
     Airport(DirectionGrid, u8),
     Beacon(u8),
+    /// Approach zone, planes in here fly at half their usual speed
+    SlowZone,
+    /// Mountains or a no-fly zone, a plane flying in here is lost
+    Obstacle,
+}
+
+impl Default for World {
+    /// An empty world as large as the [built in level](crate::level::builtin)
+    fn default() -> Self {
+        Self::new(builtin::X, builtin::Y)
+    }
 }
 
 impl World {
+    /// An empty world of `x` by `y` tiles
+    ///
+    /// # Panics
+    ///
+    /// If the world would be smaller than [`MIN_WORLD_SIZE`] in either direction, use
+    /// [`try_new`](Self::try_new) to handle that.
     pub fn new(x: usize, y: usize) -> Self {
-        World {
+        Self::try_new(x, y).expect("world is too small")
+    }
+
+    /// Like [`new`](Self::new), but errors for worlds smaller than [`MIN_WORLD_SIZE`]
+    pub fn try_new(x: usize, y: usize) -> Result<Self, Error> {
+        if x < MIN_WORLD_SIZE || y < MIN_WORLD_SIZE {
+            return Err(Error::WorldTooSmall(x, y, MIN_WORLD_SIZE));
+        }
+        Ok(World {
             tiles: vec![vec![WorldTile::Empty; x]; y],
             planes: HashMap::new(),
-            exits: HashMap::new(),
+            orders: HashMap::new(),
+            panic_held: HashMap::new(),
+            exits: BTreeMap::new(),
+            routes_mandatory: false,
+            diagonal_fuel_cost: false,
+            wind: None,
+            movement_mode: MovementMode::default(),
+            clearance_required: false,
+            safety_checks: false,
+            beacon_policy: BeaconPolicy::default(),
+            beacon_restrictions: BTreeMap::new(),
+            spawn_immunity: DEFAULT_SPAWN_IMMUNITY,
+            collision_radius: 0,
+            proximity_cooldown: DEFAULT_PROXIMITY_COOLDOWN,
+            proximity_warned: HashMap::new(),
+            controllers: 1,
+            fuel_unit: FuelUnit::default(),
+            specs: PlaneSpecs::default(),
             x,
             y,
             plane_counter: 0,
-        }
+            warnings: Vec::new(),
+            stats: Stats::default(),
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.x
+    }
+
+    pub fn height(&self) -> usize {
+        self.y
     }
 
     pub fn place_exit(
@@ -93,16 +389,49 @@ impl World {
         plane_out_direction: DirectionCardinal,
         wall_pos: usize,
         idx: u8,
+    ) -> Result<&mut Self, Error> {
+        self.place_exit_inner(where_on_wall, plane_out_direction, wall_pos, idx, None)
+    }
+
+    /// Like [`place_exit`](Self::place_exit), but planes spawn at `start_height` instead of
+    /// the start height of their [`PlaneSpec`](crate::plane::PlaneSpec)
+    pub fn place_exit_at_height(
+        &mut self,
+        where_on_wall: DirectionGrid,
+        plane_out_direction: DirectionCardinal,
+        wall_pos: usize,
+        idx: u8,
+        start_height: u8,
+    ) -> Result<&mut Self, Error> {
+        if start_height > MAX_HEIGHT {
+            return Err(Error::HeightTooHigh(start_height, MAX_HEIGHT));
+        }
+        self.place_exit_inner(
+            where_on_wall,
+            plane_out_direction,
+            wall_pos,
+            idx,
+            Some(start_height),
+        )
+    }
+
+    fn place_exit_inner(
+        &mut self,
+        where_on_wall: DirectionGrid,
+        plane_out_direction: DirectionCardinal,
+        wall_pos: usize,
+        idx: u8,
+        start_height: Option<u8>,
     ) -> Result<&mut Self, Error> {
         match where_on_wall {
             DirectionGrid::Up | DirectionGrid::Down => {
-                if !wall_pos < self.y {
-                    return Err(Error::ExitPosOutOfBounds(wall_pos, self.y));
+                if wall_pos >= self.x {
+                    return Err(Error::ExitPosOutOfBounds(wall_pos, self.x));
                 }
             }
             DirectionGrid::Left | DirectionGrid::Right => {
-                if !wall_pos < self.x {
-                    return Err(Error::ExitPosOutOfBounds(wall_pos, self.x));
+                if wall_pos >= self.y {
+                    return Err(Error::ExitPosOutOfBounds(wall_pos, self.y));
                 }
             }
         }
@@ -111,6 +440,9 @@ impl World {
             wall_direction: where_on_wall,
             plane_out_direction,
             wall_pos,
+            start_height,
+            spawn_weight: 1,
+            lane_separation: None,
         };
 
         self.exits.insert(idx, exit);
@@ -118,13 +450,67 @@ impl World {
         Ok(self)
     }
 
+    /// Make planes come in through the exit more or less often, the default weight is 1
+    pub fn set_exit_spawn_weight(&mut self, id: u8, weight: u32) -> Result<&mut Self, Error> {
+        match self.exits.get_mut(&id) {
+            Some(exit) => exit.spawn_weight = weight,
+            None => return Err(Error::NoExitForID(id)),
+        }
+        Ok(self)
+    }
+
+    /// Give the exit corridors at different heights, planes `separation` levels apart or more
+    /// can use it at the same time
+    pub fn set_exit_lanes(&mut self, id: u8, separation: u8) -> Result<&mut Self, Error> {
+        match self.exits.get_mut(&id) {
+            Some(exit) => exit.lane_separation = Some(separation.max(1)),
+            None => return Err(Error::NoExitForID(id)),
+        }
+        Ok(self)
+    }
+
+    /// Planes at `pos` flying at `a` and `b` use different lanes of an exit there
+    ///
+    /// Such planes do not get in each other's way, see [`Exit::lane_separation`].
+    pub fn in_separate_lanes(&self, pos: Pos, a: u8, b: u8) -> bool {
+        self.exits.values().any(|exit| {
+            self.exit_pos(exit) == pos
+                && exit
+                    .lane_separation
+                    .is_some_and(|separation| a.abs_diff(b) >= separation)
+        })
+    }
+
+    /// Put `tile` at `pos`, replacing what was there
+    ///
+    /// Obstacles can only be replaced by [`WorldTile::Empty`], nothing else can be built on them.
     pub fn place_tile(&mut self, tile: WorldTile, pos: impl Into<Pos>) -> Result<&mut Self, Error> {
         let pos: Pos = pos.into();
         self.check_pos_bounds(pos)?;
+        if self.tiles[pos.y][pos.x] == WorldTile::Obstacle
+            && !matches!(tile, WorldTile::Empty | WorldTile::Obstacle)
+        {
+            return Err(Error::CellOccupied(pos));
+        }
         self.tiles[pos.y][pos.x] = tile;
         Ok(self)
     }
 
+    /// Like [`place_tile`](Self::place_tile), but errors instead of replacing a tile that is not
+    /// [`WorldTile::Empty`]
+    pub fn place_tile_if_empty(
+        &mut self,
+        tile: WorldTile,
+        pos: impl Into<Pos>,
+    ) -> Result<&mut Self, Error> {
+        let pos: Pos = pos.into();
+        self.check_pos_bounds(pos)?;
+        if self.tiles[pos.y][pos.x] != WorldTile::Empty {
+            return Err(Error::CellOccupied(pos));
+        }
+        self.place_tile(tile, pos)
+    }
+
     fn check_pos_bounds(&self, pos: impl Into<Pos>) -> Result<(), Error> {
         let pos = pos.into();
         if pos.x + 1 > self.x {
@@ -137,6 +523,8 @@ impl World {
     }
 
     /// Uses Bresenham's line algorithm to place route tiles between tiles
+    ///
+    /// If `a` and `b` are the same, only that one tile becomes a route.
     pub fn place_route_in_line(
         &mut self,
         a: impl Into<Pos>,
@@ -148,6 +536,27 @@ impl World {
         self.check_pos_bounds(a)?;
         self.check_pos_bounds(b)?;
 
+        let line = if a == b { vec![a] } else { Self::line(a, b)? };
+
+        // don't leave half a route behind if it runs into an obstacle
+        if let Some(pos) = line
+            .iter()
+            .find(|pos| self.tiles[pos.y][pos.x] == WorldTile::Obstacle)
+        {
+            return Err(Error::CellOccupied(*pos));
+        }
+        for pos in line {
+            self.place_tile(WorldTile::Route, pos)?;
+        }
+
+        Ok(self)
+    }
+
+    /// The tiles on the line from `a` to `b` by Bresenham's line algorithm, both ends included
+    ///
+    /// `a` and `b` must differ, the setup needs a main axis that is at least one step long.
+    fn line(a: Pos, b: Pos) -> Result<Vec<Pos>, Error> {
+        debug_assert_ne!(a, b);
         let dx: i32 = b.x as i32 - a.x as i32;
         let dy: i32 = b.y as i32 - a.y as i32;
 
@@ -170,23 +579,23 @@ impl World {
 
         let mut d: i32 = 2 * dy as i32 - dx as i32;
         let mut y = 0;
-        let mut pos: Pos;
+        let mut line: Vec<Pos> = Vec::with_capacity(dx + 1);
 
         for x in 0..dx + 1 {
-            pos = (
-                a.x as i32 + x as i32 * xx + y * yx,
-                a.y as i32 + x as i32 * xy + y * yy,
-            )
-                .try_into()?;
-            self.place_tile(WorldTile::Route, pos)?;
+            line.push(
+                (
+                    a.x as i32 + x as i32 * xx + y * yx,
+                    a.y as i32 + x as i32 * xy + y * yy,
+                )
+                    .try_into()?,
+            );
             if d >= 0 {
                 y += 1;
                 d -= 2 * dx as i32;
             }
             d += 2 * dy as i32;
         }
-
-        Ok(self)
+        Ok(line)
     }
 
     fn get_wall(&self, pos: usize, dir: DirectionGrid) -> String {
@@ -198,10 +607,7 @@ impl World {
         }
 
         match maybe_exit_idx {
-            Some(idx) => match dir {
-                DirectionGrid::Up | DirectionGrid::Down => format!("{idx}─"),
-                DirectionGrid::Left | DirectionGrid::Right => format!("{idx} "),
-            },
+            Some(idx) => format!("{}{idx}", self.exits[idx].plane_out_direction.arrow()),
             None => match dir {
                 DirectionGrid::Up => "──",
                 DirectionGrid::Down => "──",
@@ -212,402 +618,4081 @@ impl World {
         }
     }
 
-    fn next_plane_idx(&mut self) -> char {
+    /// The next id no plane in the air has, ids of small and jet planes only differ in case
+    fn next_plane_idx(&mut self) -> Result<char, Error> {
         const ORDER: [char; 25] = [
             'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'p', 'q', 'r',
             's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
         ];
-        let out = ORDER[self.plane_counter as usize % 25];
-        self.plane_counter += 1;
-        out
+        for _ in 0..ORDER.len() {
+            let id = ORDER[(self.plane_counter % ORDER.len() as u64) as usize];
+            self.plane_counter += 1;
+            if !self
+                .planes
+                .keys()
+                .any(|other| other.eq_ignore_ascii_case(&id))
+            {
+                return Ok(id);
+            }
+        }
+        Err(Error::NoFreePlaneID(ORDER.len()))
     }
 
-    pub fn spawn_plane_at_exit(&mut self, exit_id: u8, kind: PlaneKind) -> Result<(), Error> {
-        let exit = match self.exits.get(&exit_id) {
-            Some(e) => *e,
-            None => return Err(Error::NoExitForID(exit_id)),
-        };
-        let pos = match exit.plane_out_direction {
-            DirectionCardinal::North => [exit.wall_pos, 0].into(),
-            // DirectionCardinal::NorthEast => [(exit.wall_pos -1).clamp(0, self.x), 0].into(),
-            // DirectionCardinal::NorthWest => [(exit.wall_pos +1).clamp(0, self.x), 0].into(),
-            DirectionCardinal::South => [exit.wall_pos, self.y - 1].into(),
-            // DirectionCardinal::SouthEast => [(exit.wall_pos -1).clamp(0, self.y), 0].into(),
-            // DirectionCardinal::SouthWest => [(exit.wall_pos +1).clamp(0, self.y), 0].into(),
-            DirectionCardinal::West => [0, exit.wall_pos].into(),
-            DirectionCardinal::East => [self.x - 1, exit.wall_pos].into(),
-            _ => todo!(),
+    /// All places a plane could be sent to
+    pub fn destinations(&self) -> Vec<Destination> {
+        let mut destinations: Vec<Destination> = self
+            .exits
+            .keys()
+            .map(|eid| Destination::Exit(*eid))
+            .collect();
+        for row in &self.tiles {
+            for tile in row {
+                if let WorldTile::Airport(_, aid) = tile {
+                    destinations.push(Destination::Airport(*aid));
+                }
+            }
+        }
+        destinations.sort_by_key(|d| match d {
+            Destination::Exit(id) => (0, *id),
+            Destination::Airport(id) => (1, *id),
+        });
+        destinations.dedup();
+        destinations
+    }
+
+    pub fn has_destination(&self, destination: Destination) -> bool {
+        self.destinations().contains(&destination)
+    }
+
+    /// The destinations a plane coming from `origin` can fly to, every one but the exit or
+    /// airport it came from
+    fn destinations_from(&self, origin: Destination) -> Vec<Destination> {
+        self.destinations()
+            .into_iter()
+            .filter(|d| *d != origin)
+            .collect()
+    }
+
+    /// Picks a destination for a plane, the exit or airport it came from is never chosen
+    fn random_destination(&self, origin: Destination, rng: &mut impl Rng) -> Option<Destination> {
+        self.destinations_from(origin).choose(rng).copied()
+    }
+
+    /// All exits, ordered by their id
+    pub fn exits(&self) -> impl Iterator<Item = (u8, &Exit)> {
+        self.exits.iter().map(|(id, exit)| (*id, exit))
+    }
+
+    /// The tile at `pos`, None if that is outside the map
+    pub fn tile(&self, pos: Pos) -> Option<WorldTile> {
+        self.tiles
+            .get(pos.y)
+            .and_then(|row| row.get(pos.x))
+            .copied()
+    }
+
+    /// The id of an exit planes come in through, busier exits are picked more often
+    ///
+    /// None if no exit has a [spawn weight](Exit::spawn_weight) above 0.
+    pub fn random_exit_id(&self, rng: &mut impl Rng) -> Option<u8> {
+        let exits: Vec<(u8, u32)> = self
+            .exits()
+            .map(|(id, exit)| (id, exit.spawn_weight))
+            .collect();
+        exits
+            .choose_weighted(rng, |(_, weight)| *weight)
+            .ok()
+            .map(|(id, _)| *id)
+    }
+
+    pub fn exit(&self, id: u8) -> Option<&Exit> {
+        self.exits.get(&id)
+    }
+
+    pub fn planes(&self) -> impl Iterator<Item = &Plane> {
+        self.planes.values()
+    }
+
+    /// Put a plane into the world, replacing any plane with the same id
+    pub fn insert_plane(&mut self, plane: Plane) {
+        self.planes.insert(plane.id, plane);
+    }
+
+    /// Let the plane carry out `order` once `trigger` happens
+    ///
+    /// An order for a beacon the plane will not fly over before its fuel runs out is still
+    /// queued, but gives a [`Warning::BeaconNotAhead`].
+    pub fn queue_order(&mut self, id: char, trigger: Trigger, order: Order) -> Result<(), Error> {
+        let Some(plane) = self.planes.get(&id) else {
+            return Err(Error::NoPlaneForID(id));
         };
-        let id: char = self.next_plane_idx();
-        let plane = Plane::new(
-            pos,
-            exit.plane_out_direction.opposite(),
-            kind,
-            id,
-            Destination::Exit(1),
-        );
-        self.planes.insert(id, plane);
+        if let Order::Heading(direction) = order {
+            if !self.movement_mode.allows(direction) {
+                return Err(Error::HeadingNotAllowed(direction));
+            }
+        }
+        if let Trigger::AtBeacon(bid) = trigger {
+            let reachable = self.beacon_pos(bid).is_some_and(|beacon| {
+                plane.pos == beacon
+                    || self
+                        .projected_positions(id, plane.fuel_left())
+                        .contains(&beacon)
+            });
+            if !reachable {
+                warn!(plane = %id, beacon = bid, "plane got an order for a beacon that is not ahead");
+                self.warnings.push(Warning::BeaconNotAhead(id, bid));
+            }
+        }
+        self.orders
+            .entry(id)
+            .or_default()
+            .push(QueuedOrder { trigger, order });
         Ok(())
     }
 
-    fn collision_check(&self) -> Option<(Plane, Plane)> {
-        None // TODO: add collision
+    /// Let plane `id` fly over the beacons in `beacons` one after the other and then on to its
+    /// destination
+    ///
+    /// The plane turns towards the first beacon right away. Once it is over a beacon, it
+    /// turns towards the next one, see [`Order::DirectTo`].
+    pub fn fly_via(&mut self, id: char, beacons: &[u8]) -> Result<(), Error> {
+        let plane = self.planes.get(&id).ok_or(Error::NoPlaneForID(id))?;
+        let mut targets = beacons
+            .iter()
+            .map(|bid| self.beacon_pos(*bid).ok_or(Error::NoBeaconForID(*bid)))
+            .collect::<Result<Vec<Pos>, Error>>()?;
+        if let Some(destination) = self.destination_pos(plane.destination) {
+            targets.push(destination);
+        }
+        let queue = self.orders.entry(id).or_default();
+        for (bid, next) in beacons.iter().zip(targets.iter().skip(1)) {
+            queue.push(QueuedOrder {
+                trigger: Trigger::AtBeacon(*bid),
+                order: Order::DirectTo(*next),
+            });
+        }
+        let mode = self.movement_mode;
+        let plane = self.planes.get_mut(&id).ok_or(Error::NoPlaneForID(id))?;
+        if let Some(first) = targets.first() {
+            plane.apply(Order::DirectTo(*first))?;
+            Self::steer(plane, mode);
+        }
+        Ok(())
     }
 
-    fn wall_collision_check(&self) -> Option<(Plane, DirectionGrid, usize)> {
-        None // TODO: add collision
+    /// Let plane `id` land at the airport it is flying over, it gets that airport as its
+    /// destination, is cleared and descends while it holds over the runway, until it touches down
+    ///
+    /// The plane has to fly in the direction of the runway, otherwise it has to come around and
+    /// approach the airport again. Returns the id of the airport.
+    pub fn land_here(&mut self, id: char) -> Result<u8, Error> {
+        let plane = self.planes.get(&id).ok_or(Error::NoPlaneForID(id))?;
+        let Some(WorldTile::Airport(runway, aid)) = self.tile(plane.pos) else {
+            return Err(Error::NotOverAirport(id));
+        };
+        let runway: DirectionCardinal = runway.into();
+        if plane.direction != runway {
+            return Err(Error::NotLinedUp(id, aid, runway));
+        }
+        let plane = self.planes.get_mut(&id).ok_or(Error::NoPlaneForID(id))?;
+        plane.destination = Destination::Airport(aid);
+        plane.cleared = true;
+        plane.direct_to = None;
+        plane.set_target_height(0)?;
+        // flying on would take it past the runway before it is down
+        plane.holding = true;
+        Ok(aid)
     }
 
-    fn plane_exit_check_inner(
-        &mut self,
-        plane: &Plane,
-        wall_dir: DirectionGrid,
-        plane_pos: usize,
-    ) -> Option<(Plane, u8)> {
-        for (eid, exit) in self
-            .exits
-            .iter()
-            .filter(|(_id, e)| e.wall_direction == wall_dir)
+    /// Turn a plane with a [`direct_to`](Plane::direct_to) tile towards it
+    ///
+    /// The plane takes the heading that brings it closest, keeping its heading if that is as
+    /// good as any other. Once it is there, it flies on straight.
+    fn steer(plane: &mut Plane, mode: MovementMode) {
+        let Some(target) = plane.direct_to else {
+            return;
+        };
+        if plane.pos == target {
+            plane.direct_to = None;
+            return;
+        }
+        if plane.holding {
+            return;
+        }
+        let distance = |pos: Pos| {
+            let (dx, dy) = (pos.x.abs_diff(target.x), pos.y.abs_diff(target.y));
+            // moves left first, then the straightest line
+            (dx.max(dy), dx * dx + dy * dy)
+        };
+        let mut headings = mode.headings();
+        headings.sort_by_key(|heading| *heading != plane.direction);
+        if let Some(heading) = headings
+            .into_iter()
+            .filter_map(|heading| Some((heading, distance(plane.pos.step(heading)?))))
+            .min_by_key(|(_, distance)| *distance)
+            .map(|(heading, _)| heading)
         {
-            if exit.wall_pos == plane_pos {
-                // plane takes this exit
-                if matches!(plane.destination, Destination::Exit(dest_eid) if dest_eid == *eid) {
-                    // right exit
-                    self.planes.remove(&plane.id);
-                } else {
-                    // wrong exit
-                    return Some((*plane, *eid));
-                }
-            }
+            plane.direction = heading;
         }
-        None
     }
 
-    /// Removes planes that exit and returns Some if a plane took the wrong exit
+    /// The orders the plane still has to carry out, oldest first
+    pub fn orders(&self, id: char) -> &[QueuedOrder] {
+        self.orders.get(&id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn beacon_pos(&self, id: u8) -> Option<Pos> {
+        self.tiles.iter().enumerate().find_map(|(y, row)| {
+            row.iter()
+                .position(|t| *t == WorldTile::Beacon(id))
+                .map(|x| Pos { x, y })
+        })
+    }
+
+    /// Let planes that reached a beacon without an order for it hold there, if the
+    /// [`BeaconPolicy`] says so
     ///
-    /// None if everything is ok, some only if a plane took the wrong exit
-    fn planes_take_exits(&mut self) -> Option<(Plane, u8)> {
-        // TODO: add height check
-        for (pid, plane) in self.planes.clone() {
-            if plane.just_spawned {
-                debug!("Plane {pid} is too new, skipping for exit check");
+    /// Only planes that moved onto the beacon this tick are held. A plane that was released
+    /// and waits for its next move is still on the beacon, and must not be caught again.
+    fn hold_at_beacons(&mut self) {
+        if self.beacon_policy != BeaconPolicy::Hold {
+            return;
+        }
+        for plane in self
+            .planes
+            .values_mut()
+            .filter(|p| !p.holding && p.moves_this_tick())
+        {
+            let Some(WorldTile::Beacon(bid)) = self
+                .tiles
+                .get(plane.pos.y)
+                .and_then(|row| row.get(plane.pos.x))
+                .copied()
+            else {
                 continue;
+            };
+            let has_order = self
+                .orders
+                .get(&plane.id)
+                .is_some_and(|q| q.iter().any(|o| o.trigger == Trigger::AtBeacon(bid)));
+            if !has_order {
+                info!(plane = %plane.id, beacon = bid, "plane holds at beacon");
+                plane.holding = true;
+                self.warnings.push(Warning::Holding(plane.id, bid));
             }
-            if plane.pos.y == 0 {
-                if let Some(v) = self.plane_exit_check_inner(&plane, DirectionGrid::Up, plane.pos.x)
-                {
-                    return Some(v);
-                }
-            }
-            if plane.pos.y == self.y {
-                if let Some(v) =
-                    self.plane_exit_check_inner(&plane, DirectionGrid::Down, plane.pos.x)
-                {
-                    return Some(v);
-                }
-            }
-            if plane.pos.x == 0 {
-                if let Some(v) =
-                    self.plane_exit_check_inner(&plane, DirectionGrid::Left, plane.pos.y)
-                {
-                    return Some(v);
+        }
+    }
+
+    /// Carry out the queued orders whose trigger happened
+    fn process_orders(&mut self) {
+        self.orders.retain(|id, _| self.planes.contains_key(id));
+        for (id, queue) in self.orders.iter_mut() {
+            let Some(plane) = self.planes.get_mut(id) else {
+                continue;
+            };
+            // off the map, the plane is about to be reported for flying into the wall
+            let Some(tile) = self
+                .tiles
+                .get(plane.pos.y)
+                .and_then(|row| row.get(plane.pos.x))
+                .copied()
+            else {
+                continue;
+            };
+            for order in Self::take_due_orders(queue, tile) {
+                info!(plane = %id, ?order, x = plane.pos.x, y = plane.pos.y, "order applied");
+                if let Err(e) = plane.apply(order) {
+                    warn!(plane = %id, ?order, "could not apply order: {e}");
                 }
             }
-            if plane.pos.x == self.x {
-                if let Some(v) =
-                    self.plane_exit_check_inner(&plane, DirectionGrid::Right, plane.pos.y)
-                {
-                    return Some(v);
+        }
+        self.orders.retain(|_, queue| !queue.is_empty());
+    }
+
+    /// Remove the orders from `queue` that are due for a plane on `tile`, counting down the
+    /// tick triggers
+    fn take_due_orders(queue: &mut Vec<QueuedOrder>, tile: WorldTile) -> Vec<Order> {
+        let mut due_orders = Vec::new();
+        let mut i = 0;
+        while i < queue.len() {
+            let due = match &mut queue[i].trigger {
+                Trigger::AtBeacon(bid) => tile == WorldTile::Beacon(*bid),
+                Trigger::InTicks(n) => {
+                    *n = n.saturating_sub(1);
+                    *n == 0
                 }
+            };
+            if due {
+                due_orders.push(queue.remove(i).order);
+            } else {
+                i += 1;
             }
         }
-        None
+        due_orders
     }
 
-    /// Removes planes that exit and returns Some if a plane took the wrong exit
+    /// Where plane `id` will be after each of the next `ticks` ticks if it gets no new commands
     ///
-    /// Returns:
+    /// Queued orders, slow zones and holding at beacons are taken into account like in
+    /// [`World::tick_planes`], other planes are not. The projection ends early when the plane
+    /// reaches the edge of the map or runs out of fuel.
+    pub fn projected_positions(&self, id: char, ticks: usize) -> Vec<Pos> {
+        let Some(plane) = self.planes.get(&id).copied() else {
+            return Vec::new();
+        };
+        self.project(plane, ticks).iter().map(|p| p.pos).collect()
+    }
+
+    /// Where plane `id` would touch the ground if it was told to turn to `heading` and go down
+    /// now
     ///
-    /// - None: Everything is okay. Maybe a plane landed at the correct airport and was removed
-    /// - Some(Plane, None): A plane crashed on the ground (height 0)
-    /// - Some(Plane, Some(airport_id)): A plane landed in the wrong airport
-    fn planes_land(&mut self) -> Option<(Plane, Option<u8>)> {
-        for (y, row) in self.tiles.iter().enumerate() {
-            for (x, airport) in row
+    /// The plane goes down one level per move and keeps its queued orders, see
+    /// [`World::projected_positions`]. None if it reaches the edge of the map or runs out of
+    /// fuel first.
+    pub fn touchdown_pos(&self, id: char, heading: DirectionCardinal) -> Option<Pos> {
+        let mut plane = self.planes.get(&id).copied()?;
+        if plane.height == 0 {
+            return Some(plane.pos);
+        }
+        plane.apply(Order::Heading(heading)).ok()?;
+        plane.target_height = 0;
+        self.project(plane, plane.fuel_left())
+            .iter()
+            .find(|p| p.height == 0)
+            .map(|p| p.pos)
+    }
+
+    /// The plane after each of the next `ticks` ticks, see [`World::projected_positions`]
+    fn project(&self, mut plane: Plane, ticks: usize) -> Vec<Plane> {
+        let mut queue = self.orders(plane.id).to_vec();
+        let mut projected = Vec::with_capacity(ticks);
+        for _ in 0..ticks {
+            if plane.tick().is_err() {
+                break;
+            }
+            let Some(tile) = self
+                .tiles
+                .get(plane.pos.y)
+                .and_then(|row| row.get(plane.pos.x))
+                .copied()
+            else {
+                break;
+            };
+            plane.slowed = tile == WorldTile::SlowZone;
+            if let WorldTile::Beacon(bid) = tile {
+                if self.beacon_policy == BeaconPolicy::Hold
+                    && plane.moves_this_tick()
+                    && !queue.iter().any(|o| o.trigger == Trigger::AtBeacon(bid))
+                {
+                    plane.holding = true;
+                }
+            }
+            for order in Self::take_due_orders(&mut queue, tile) {
+                let _ = plane.apply(order);
+            }
+            Self::steer(&mut plane, self.movement_mode);
+            projected.push(plane);
+            if plane.pos.x == 0
+                || plane.pos.y == 0
+                || plane.pos.x + 1 == self.x
+                || plane.pos.y + 1 == self.y
+            {
+                break;
+            }
+        }
+        projected
+    }
+
+    /// Pairs of planes that are dangerously close to each other
+    ///
+    /// Two planes are in conflict if they are at most [`PROXIMITY_RADIUS`] tiles apart and at
+    /// most one level above or below each other. The pairs are sorted, and the smaller id comes
+    /// first in each pair.
+    pub fn proximity_conflicts(&self) -> Vec<(char, char)> {
+        let mut ids: Vec<char> = self.planes.keys().copied().collect();
+        ids.sort();
+        ids.into_iter()
+            .flat_map(|a| {
+                self.planes_near_in_band(a, PROXIMITY_RADIUS, 1)
+                    .into_iter()
+                    .filter(move |b| a < *b)
+                    .map(move |b| (a, b))
+            })
+            .collect()
+    }
+
+    /// Ids of the other planes at most `radius` tiles away from plane `id` in both directions,
+    /// at any height, sorted
+    ///
+    /// Empty if there is no plane `id`.
+    pub fn planes_near(&self, id: char, radius: usize) -> Vec<char> {
+        self.planes_near_in_band(id, radius, MAX_HEIGHT)
+    }
+
+    /// Like [`planes_near`](Self::planes_near), but only planes at most `levels` above or below
+    /// plane `id`
+    pub fn planes_near_in_band(&self, id: char, radius: usize, levels: u8) -> Vec<char> {
+        let Some(plane) = self.planes.get(&id) else {
+            return Vec::new();
+        };
+        let mut near: Vec<char> = self
+            .planes
+            .values()
+            .filter(|other| {
+                other.id != id
+                    && plane.pos.x.abs_diff(other.pos.x) <= radius
+                    && plane.pos.y.abs_diff(other.pos.y) <= radius
+                    && plane.height.abs_diff(other.height) <= levels
+            })
+            .map(|other| other.id)
+            .collect();
+        near.sort();
+        near
+    }
+
+    /// The planes flying at `height`, by id with their positions
+    pub fn occupancy_at_altitude(&self, height: u8) -> BTreeMap<char, Pos> {
+        self.planes
+            .values()
+            .filter(|plane| plane.height == height)
+            .map(|plane| (plane.id, plane.pos))
+            .collect()
+    }
+
+    pub fn plane(&self, id: char) -> Option<&Plane> {
+        self.planes.get(&id)
+    }
+
+    pub fn plane_mut(&mut self, id: char) -> Option<&mut Plane> {
+        self.planes.get_mut(&id)
+    }
+
+    /// The plane at `pos`, if there is one
+    pub fn plane_at(&self, pos: Pos) -> Option<&Plane> {
+        self.planes.values().find(|plane| plane.pos == pos)
+    }
+
+    /// Remove all planes and their orders, the next plane gets the first id again
+    pub fn clear_planes(&mut self) {
+        self.planes.clear();
+        self.orders.clear();
+        self.panic_held.clear();
+        self.proximity_warned.clear();
+        self.plane_counter = 0;
+    }
+
+    /// Let every airborne plane that is not holding yet circle where it is
+    ///
+    /// Their headings are remembered, [`release_panic_hold`](Self::release_panic_hold) lets
+    /// them fly on.
+    pub fn panic_hold(&mut self) {
+        for plane in self
+            .planes
+            .values_mut()
+            .filter(|p| p.height > 0 && !p.holding)
+        {
+            plane.holding = true;
+            self.panic_held.insert(plane.id, plane.direction);
+        }
+        info!(planes = self.panic_held.len(), "all planes holding");
+    }
+
+    /// Let the planes held by [`panic_hold`](Self::panic_hold) fly on in their old headings
+    ///
+    /// Planes that got a new heading in the meantime keep it.
+    pub fn release_panic_hold(&mut self) {
+        for (id, direction) in self.panic_held.drain() {
+            if let Some(plane) = self.planes.get_mut(&id).filter(|p| p.holding) {
+                plane.holding = false;
+                plane.direction = direction;
+            }
+        }
+        info!("panic hold released");
+    }
+
+    /// The panic button is pressed, see [`panic_hold`](Self::panic_hold)
+    pub fn panic_holding(&self) -> bool {
+        !self.panic_held.is_empty()
+    }
+
+    /// The planes with their orders and the [`Stats`], ordered by plane id
+    pub fn traffic(&self) -> Traffic {
+        let mut planes: Vec<TrafficPlane> = self
+            .planes
+            .values()
+            .map(|plane| TrafficPlane {
+                plane: *plane,
+                orders: self.orders(plane.id).to_vec(),
+                panic_heading: self.panic_held.get(&plane.id).copied(),
+            })
+            .collect();
+        planes.sort_by_key(|p| p.plane.id);
+        Traffic {
+            plane_counter: self.plane_counter,
+            stats: self.stats,
+            planes,
+        }
+    }
+
+    /// Replace the planes, their orders and the [`Stats`] with `traffic`
+    pub fn restore_traffic(&mut self, traffic: Traffic) {
+        self.clear_planes();
+        self.plane_counter = traffic.plane_counter;
+        self.stats = traffic.stats;
+        for entry in traffic.planes {
+            let id = entry.plane.id;
+            self.planes.insert(id, entry.plane);
+            if !entry.orders.is_empty() {
+                self.orders.insert(id, entry.orders);
+            }
+            if let Some(heading) = entry.panic_heading {
+                self.panic_held.insert(id, heading);
+            }
+        }
+    }
+
+    /// Put the world back into the state it was in before the first plane came in
+    ///
+    /// The layout stays as it is, but planes, warnings and the [`Stats`] are gone.
+    pub fn reset(&mut self) {
+        self.clear_planes();
+        self.warnings.clear();
+        self.stats = Stats::default();
+    }
+
+    /// The id of the plane a player means when typing `id`
+    ///
+    /// The map shows the ids of small planes in lowercase and of jets in uppercase, but players
+    /// should not have to care about that, so the case is ignored.
+    pub fn resolve_plane_id(&self, id: char) -> Result<char, Error> {
+        [id, id.to_ascii_uppercase(), id.to_ascii_lowercase()]
+            .into_iter()
+            .find(|candidate| self.planes.contains_key(candidate))
+            .ok_or(Error::NoPlaneForID(id))
+    }
+
+    /// Planes that left through the right exit or landed at the right airport
+    pub fn planes_handled(&self) -> u64 {
+        self.stats.handled()
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Returns the warnings collected since the last call
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Gives planes with a destination that does not exist a new one
+    ///
+    /// This should never happen, as [`spawn_plane_at_exit`](Self::spawn_plane_at_exit) only hands
+    /// out valid destinations, but a broken plane should not bring down the whole game.
+    fn check_destinations(&mut self) {
+        let destinations = self.destinations();
+        for plane in self.planes.values_mut() {
+            if destinations.contains(&plane.destination) {
+                continue;
+            }
+            warn!(
+                "Plane {} has a destination that does not exist: {}",
+                plane.id, plane.destination
+            );
+            self.warnings
+                .push(Warning::UnknownDestination(plane.id, plane.destination));
+            if let Some(d) = destinations
                 .iter()
-                .enumerate()
-                .filter(|(_, tile)| matches!(tile, WorldTile::Airport(_, _)))
+                .find(|d| matches!(d, Destination::Exit(_)))
+                .or(destinations.first())
             {
-                for (pid, plane) in self
-                    .planes
-                    .clone()
-                    .iter()
-                    .filter(|(_, plane)| plane.height == 0)
+                plane.destination = *d;
+            }
+        }
+    }
+
+    /// Explain where a destination is, for players who can't tell from the map
+    ///
+    /// Exits tell which wall they are on, where on that wall and which way new planes fly in.
+    /// None if the world has no such destination.
+    pub fn describe_destination(&self, destination: Destination) -> Option<String> {
+        let pos = self.destination_pos(destination)?;
+        Some(match destination {
+            Destination::Exit(eid) => format!("{destination}: {}", self.exits.get(&eid)?),
+            Destination::Airport(_) => match self.tiles[pos.y][pos.x] {
+                WorldTile::Airport(dir, _) => {
+                    format!("{destination}: at {},{}, runway {dir}", pos.x, pos.y)
+                }
+                _ => unreachable!("destination_pos found an airport here"),
+            },
+        })
+    }
+
+    /// The tile next to the exit, where planes come in and leave
+    pub fn exit_pos(&self, exit: &Exit) -> Pos {
+        match exit.wall_direction {
+            DirectionGrid::Up => [exit.wall_pos, 0].into(),
+            DirectionGrid::Down => [exit.wall_pos, self.y.saturating_sub(1)].into(),
+            DirectionGrid::Left => [0, exit.wall_pos].into(),
+            DirectionGrid::Right => [self.x.saturating_sub(1), exit.wall_pos].into(),
+        }
+    }
+
+    /// How the different kinds of planes behave in this world
+    pub fn plane_specs(&self) -> &PlaneSpecs {
+        &self.specs
+    }
+
+    /// Change how new planes behave, planes already in the world keep their spec
+    pub fn plane_specs_mut(&mut self) -> &mut PlaneSpecs {
+        &mut self.specs
+    }
+
+    pub fn set_routes_mandatory(&mut self, mandatory: bool) -> &mut Self {
+        self.routes_mandatory = mandatory;
+        self
+    }
+
+    pub fn movement_mode(&self) -> MovementMode {
+        self.movement_mode
+    }
+
+    pub fn set_movement_mode(&mut self, mode: MovementMode) -> &mut Self {
+        self.movement_mode = mode;
+        self
+    }
+
+    pub fn set_fuel_unit(&mut self, unit: FuelUnit) -> &mut Self {
+        self.fuel_unit = unit;
+        self
+    }
+
+    pub fn fuel_unit(&self) -> FuelUnit {
+        self.fuel_unit
+    }
+
+    pub fn set_beacon_policy(&mut self, policy: BeaconPolicy) -> &mut Self {
+        self.beacon_policy = policy;
+        self
+    }
+
+    /// Planes have to fly over beacon `id` as `restriction` says, or they get a
+    /// [warning](Warning::CrossingRestriction)
+    pub fn restrict_beacon(
+        &mut self,
+        id: u8,
+        restriction: AltitudeRestriction,
+    ) -> Result<&mut Self, Error> {
+        if self.beacon_pos(id).is_none() {
+            return Err(Error::NoBeaconForID(id));
+        }
+        self.beacon_restrictions.insert(id, restriction);
+        Ok(self)
+    }
+
+    pub fn beacon_restriction(&self, id: u8) -> Option<AltitudeRestriction> {
+        self.beacon_restrictions.get(&id).copied()
+    }
+
+    /// Planes at the same height collide when they are at most `radius` tiles apart in both
+    /// directions
+    ///
+    /// The default of 0 only lets planes on the same tile collide, 1 is the rule of the classic
+    /// game, where planes next to each other collide too.
+    pub fn set_collision_radius(&mut self, radius: usize) -> &mut Self {
+        self.collision_radius = radius;
+        self
+    }
+
+    pub fn collision_radius(&self) -> usize {
+        self.collision_radius
+    }
+
+    /// For how many ticks new planes are ignored by collision, wall, exit and landing checks
+    ///
+    /// A plane is immune while it has flown fewer than `ticks` ticks, so it can get out of the
+    /// way at a busy exit.
+    pub fn set_spawn_immunity(&mut self, ticks: usize) -> &mut Self {
+        self.spawn_immunity = ticks;
+        self
+    }
+
+    /// For how many ticks two planes that stay too close to each other get no new
+    /// [`Warning::Proximity`]
+    ///
+    /// Planes that move apart and come close again are warned about right away.
+    pub fn set_proximity_cooldown(&mut self, ticks: usize) -> &mut Self {
+        self.proximity_cooldown = ticks;
+        self
+    }
+
+    /// Share the planes between `controllers` players, at least one
+    ///
+    /// New planes are handed to the controllers in turn, see [`Plane::controller`].
+    pub fn set_controllers(&mut self, controllers: u8) -> &mut Self {
+        self.controllers = controllers.max(1);
+        self
+    }
+
+    /// How many controllers share the planes, 1 if a single player has them all
+    pub fn controllers(&self) -> u8 {
+        self.controllers
+    }
+
+    /// Reject commands that would fly a plane into a wall or obstacle on its next move, for
+    /// new players
+    pub fn set_safety_checks(&mut self, enabled: bool) -> &mut Self {
+        self.safety_checks = enabled;
+        self
+    }
+
+    pub fn safety_checks(&self) -> bool {
+        self.safety_checks
+    }
+
+    /// Err if safety checks are on and plane `id` would crash on its next move when flying
+    /// `heading`
+    ///
+    /// The plane crashes if it would leave the map, reach the edge of the map away from an exit
+    /// or fly into an obstacle.
+    pub fn check_heading_safe(&self, id: char, heading: DirectionCardinal) -> Result<(), Error> {
+        if !self.safety_checks {
+            return Ok(());
+        }
+        let plane = self.planes.get(&id).ok_or(Error::NoPlaneForID(id))?;
+        let crashes = match plane.pos.step(heading) {
+            Some(next) if next.x < self.x && next.y < self.y => {
+                let on_edge =
+                    next.x == 0 || next.y == 0 || next.x + 1 == self.x || next.y + 1 == self.y;
+                self.tiles[next.y][next.x] == WorldTile::Obstacle
+                    || (on_edge && !self.exits.values().any(|e| self.exit_pos(e) == next))
+            }
+            _ => true,
+        };
+        if crashes {
+            return Err(Error::UnsafeHeading(id, heading));
+        }
+        Ok(())
+    }
+
+    /// Make planes wait for clearance before they leave through their exit or land
+    pub fn set_clearance_required(&mut self, required: bool) -> &mut Self {
+        self.clearance_required = required;
+        self
+    }
+
+    /// Make diagonal moves cost more fuel, they cover more ground than moves along an axis
+    pub fn set_diagonal_fuel_cost(&mut self, enabled: bool) -> &mut Self {
+        self.diagonal_fuel_cost = enabled;
+        self
+    }
+
+    /// Let wind blow over the map, None for calm air
+    ///
+    /// Every [`WIND_FUEL_TICKS`] ticks, a plane that moves with a tailwind gets back as much
+    /// fuel as the wind is strong and a plane that moves into a headwind burns that much more.
+    pub fn set_wind(&mut self, wind: Option<Wind>) -> &mut Self {
+        self.wind = wind;
+        self
+    }
+
+    pub fn wind(&self) -> Option<Wind> {
+        self.wind
+    }
+
+    /// Where a destination is on the map
+    pub fn destination_pos(&self, destination: Destination) -> Option<Pos> {
+        match destination {
+            Destination::Exit(eid) => self.exits.get(&eid).map(|e| self.exit_pos(e)),
+            Destination::Airport(aid) => self.tiles.iter().enumerate().find_map(|(y, row)| {
+                row.iter()
+                    .position(|t| matches!(t, WorldTile::Airport(_, id) if *id == aid))
+                    .map(|x| Pos { x, y })
+            }),
+        }
+    }
+
+    /// All positions a plane starting at `from` could fly to
+    ///
+    /// If routes are mandatory, only tiles of routes, beacons and airports can be flown over.
+    /// The start itself is always reachable.
+    pub fn reachable_from(&self, from: Pos) -> HashSet<Pos> {
+        let exit_positions: Vec<Pos> = self.exits.values().map(|e| self.exit_pos(e)).collect();
+        let passable = |pos: Pos| {
+            self.tiles[pos.y][pos.x] != WorldTile::Obstacle
+                && (!self.routes_mandatory
+                    || exit_positions.contains(&pos)
+                    || matches!(
+                        self.tiles[pos.y][pos.x],
+                        WorldTile::Route | WorldTile::Beacon(_) | WorldTile::Airport(_, _)
+                    ))
+        };
+
+        let mut seen = HashSet::from([from]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(pos) = queue.pop_front() {
+            for dir in DirectionCardinal::ALL {
+                let Some(next) = pos.step(dir) else { continue };
+                if next.x >= self.x || next.y >= self.y || seen.contains(&next) || !passable(next) {
+                    continue;
+                }
+                seen.insert(next);
+                queue.push_back(next);
+            }
+        }
+        seen
+    }
+
+    /// Check that the world is playable
+    ///
+    /// Planes have to be able to come in through at least one exit, and every such exit needs a
+    /// destination other than itself. Every destination has to be reachable from at least one
+    /// exit where planes come in.
+    pub fn validate(&self) -> Result<(), Error> {
+        let spawning: Vec<u8> = self
+            .exits()
+            .filter(|(_, exit)| exit.spawn_weight > 0)
+            .map(|(id, _)| id)
+            .collect();
+        if spawning.is_empty() {
+            return Err(Error::NoSpawnPoints);
+        }
+        if spawning
+            .iter()
+            .any(|id| self.destinations_from(Destination::Exit(*id)).is_empty())
+        {
+            return Err(Error::NoDestinations);
+        }
+        let reachable: HashSet<Pos> = self
+            .exits
+            .values()
+            .flat_map(|e| self.reachable_from(self.exit_pos(e)))
+            .collect();
+        for destination in self.destinations() {
+            let reached = self
+                .destination_pos(destination)
+                .is_some_and(|pos| reachable.contains(&pos));
+            if !reached {
+                let id = match destination {
+                    Destination::Exit(id) | Destination::Airport(id) => id,
+                };
+                return Err(Error::UnreachableDestination(id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Spawns a new plane at the exit and returns its id
+    ///
+    /// The destination of the plane is picked with `rng`, pass the one of the level so that
+    /// games with the same seed play the same.
+    pub fn spawn_plane_at_exit(
+        &mut self,
+        exit_id: u8,
+        kind: PlaneKind,
+        rng: &mut impl Rng,
+    ) -> Result<char, Error> {
+        let destination = self
+            .random_destination(Destination::Exit(exit_id), rng)
+            .ok_or(Error::NoDestinations)?;
+        self.spawn_plane_to(exit_id, kind, destination)
+    }
+
+    /// Like [`World::spawn_plane_at_exit`], but the plane flies to `destination` instead of a
+    /// random one
+    pub fn spawn_plane_to(
+        &mut self,
+        exit_id: u8,
+        kind: PlaneKind,
+        destination: Destination,
+    ) -> Result<char, Error> {
+        let exit = match self.exits.get(&exit_id) {
+            Some(e) => *e,
+            None => return Err(Error::NoExitForID(exit_id)),
+        };
+        let pos = self.exit_pos(&exit);
+        let height = exit
+            .start_height
+            .unwrap_or(self.specs.get(kind).start_height);
+        if self
+            .planes
+            .values()
+            .any(|p| p.pos == pos && !self.in_separate_lanes(pos, p.height, height))
+        {
+            return Err(Error::ExitOccupied(exit_id));
+        }
+        if destination == Destination::Exit(exit_id) || !self.has_destination(destination) {
+            return Err(Error::BadDestination(exit_id, destination));
+        }
+        let mut plane = self.new_plane(pos, exit.plane_out_direction, kind, destination)?;
+        let controller = plane.controller;
+        if let Some(height) = exit.start_height {
+            plane.height = height;
+            plane.target_height = height;
+        }
+        info!(
+            plane = %plane.id,
+            exit = exit_id,
+            controller,
+            x = pos.x,
+            y = pos.y,
+            height = plane.height,
+            destination = %destination,
+            "plane spawned"
+        );
+        self.planes.insert(plane.id, plane);
+        Ok(plane.id)
+    }
+
+    /// Spawns a new plane on the ground at the airport and returns its id
+    ///
+    /// The plane waits on the runway, facing the way the runway points, until it gets a
+    /// heading. It flies to an exit or another airport, and has to climb to the exit height of
+    /// its kind before it can leave through an exit. Its destination is picked with `rng`, like
+    /// for [`World::spawn_plane_at_exit`].
+    pub fn spawn_plane_at_airport(
+        &mut self,
+        airport_id: u8,
+        kind: PlaneKind,
+        rng: &mut impl Rng,
+    ) -> Result<char, Error> {
+        let pos = self
+            .destination_pos(Destination::Airport(airport_id))
+            .ok_or(Error::NoAirportForID(airport_id))?;
+        let Some(WorldTile::Airport(runway, _)) = self.tile(pos) else {
+            return Err(Error::NoAirportForID(airport_id));
+        };
+        if self.planes.values().any(|p| p.pos == pos && p.height == 0) {
+            return Err(Error::CellOccupied(pos));
+        }
+        let destination = self
+            .random_destination(Destination::Airport(airport_id), rng)
+            .ok_or(Error::NoDestinations)?;
+        let mut plane = self.new_plane(pos, runway.into(), kind, destination)?;
+        plane.height = 0;
+        plane.target_height = 0;
+        plane.holding = true;
+        plane.taking_off = true;
+        info!(
+            plane = %plane.id,
+            airport = airport_id,
+            controller = plane.controller,
+            x = pos.x,
+            y = pos.y,
+            destination = %destination,
+            "plane waiting for takeoff"
+        );
+        self.planes.insert(plane.id, plane);
+        Ok(plane.id)
+    }
+
+    /// A plane with the next free id, handed to the next controller in turn
+    fn new_plane(
+        &mut self,
+        pos: Pos,
+        direction: DirectionCardinal,
+        kind: PlaneKind,
+        destination: Destination,
+    ) -> Result<Plane, Error> {
+        let controller = (self.plane_counter % self.controllers as u64) as u8;
+        let id: char = self.next_plane_idx()?;
+        let mut plane =
+            Plane::with_spec(pos, direction, kind, *self.specs.get(kind), id, destination);
+        plane.controller = controller;
+        Ok(plane)
+    }
+
+    /// The first plane that flew into an obstacle
+    fn obstacle_check(&self) -> Option<Plane> {
+        self.planes
+            .values()
+            .filter(|plane| !plane.just_spawned)
+            .find(|plane| {
+                self.tiles
+                    .get(plane.pos.y)
+                    .and_then(|row| row.get(plane.pos.x))
+                    == Some(&WorldTile::Obstacle)
+            })
+            .copied()
+    }
+
+    /// Planes that just spawned must not be reported, see [`World::set_spawn_immunity`]
+    ///
+    /// Planes in different lanes of an exit do not collide, see [`World::in_separate_lanes`]
+    ///
+    /// Planes on the ground at an airport are left out, so planes can take off and land next to
+    /// each other. Of the colliding planes, the first two by id are returned, in that order.
+    fn collision_check(&self) -> Option<(Plane, Plane)> {
+        let mut planes: Vec<&Plane> = self
+            .planes
+            .values()
+            .filter(|plane| !plane.just_spawned && !self.on_airport_ground(plane))
+            .collect();
+        planes.sort_by_key(|plane| plane.id);
+        for (i, a) in planes.iter().enumerate() {
+            for b in &planes[i + 1..] {
+                if a.height == b.height
+                    && a.pos.x.abs_diff(b.pos.x) <= self.collision_radius
+                    && a.pos.y.abs_diff(b.pos.y) <= self.collision_radius
+                    && !(a.pos == b.pos && self.in_separate_lanes(a.pos, a.height, b.height))
                 {
-                    if plane.pos == [x, y].into() {
-                        // plane lands at this airport
-                        if let Destination::Airport(dest_aid) = plane.destination {
-                            match airport {
-                                WorldTile::Airport(airdir, actual_aid) => {
-                                    if Into::<DirectionCardinal>::into(*airdir) != plane.direction {
-                                        panic!("Plane landed in the wrong direction");
-                                    }
-                                    if dest_aid != *actual_aid {
-                                        // right airport, right direction
-                                        self.planes.remove(pid);
-                                    }
-                                }
-                                _ => unreachable!(),
-                            }
-                        } else {
-                            panic!("Landed at airport but should have used an exit")
+                    return Some((**a, **b));
+                }
+            }
+        }
+        None
+    }
+
+    /// The plane is on the ground at an airport, after landing or before taking off
+    fn on_airport_ground(&self, plane: &Plane) -> bool {
+        plane.height == 0 && matches!(self.tile(plane.pos), Some(WorldTile::Airport(..)))
+    }
+
+    /// Planes that just spawned must not be reported, see [`World::set_spawn_immunity`]
+    ///
+    /// A plane on the border of the map ran into the wall unless it is on an exit. Planes that
+    /// leave through the right exit are gone already, see
+    /// [`planes_take_exits`](Self::planes_take_exits), the others on an exit turn around there.
+    /// A plane past the border ran into the wall, even in the row or column of an exit. A plane
+    /// in a corner ran into the wall it was heading for.
+    fn wall_collision_check(&self) -> Option<(Plane, DirectionGrid, usize)> {
+        let mut planes: Vec<&Plane> = self
+            .planes
+            .values()
+            .filter(|plane| !plane.just_spawned)
+            .collect();
+        planes.sort_by_key(|plane| plane.id);
+        for plane in planes {
+            let Pos { x, y } = plane.pos;
+            let walls = [
+                (DirectionGrid::Up, y == 0, x),
+                (DirectionGrid::Down, y + 1 >= self.y, x),
+                (DirectionGrid::Left, x == 0, y),
+                (DirectionGrid::Right, x + 1 >= self.x, y),
+            ];
+            let touched: Vec<(DirectionGrid, usize)> = walls
+                .into_iter()
+                .filter(|(_, touches, _)| *touches)
+                .map(|(wall, _, wall_pos)| (wall, wall_pos))
+                .collect();
+            let outside = x >= self.x || y >= self.y;
+            let at_exit = !outside
+                && touched.iter().any(|(wall, wall_pos)| {
+                    self.exits
+                        .values()
+                        .any(|exit| exit.wall_direction == *wall && exit.wall_pos == *wall_pos)
+                });
+            if at_exit {
+                continue;
+            }
+            let (dx, dy) = plane.direction.offset();
+            let heading_into = |wall: &DirectionGrid| match wall {
+                DirectionGrid::Up => dy < 0,
+                DirectionGrid::Down => dy > 0,
+                DirectionGrid::Left => dx < 0,
+                DirectionGrid::Right => dx > 0,
+            };
+            if let Some((wall, wall_pos)) = touched
+                .iter()
+                .find(|(wall, _)| heading_into(wall))
+                .or(touched.first())
+            {
+                return Some((*plane, *wall, *wall_pos));
+            }
+        }
+        None
+    }
+
+    fn plane_exit_check_inner(
+        &mut self,
+        plane: &Plane,
+        wall_dir: DirectionGrid,
+        plane_pos: usize,
+    ) -> Option<(Plane, u8)> {
+        for (eid, exit) in self
+            .exits
+            .iter()
+            .filter(|(_id, e)| e.wall_direction == wall_dir)
+        {
+            if exit.wall_pos == plane_pos {
+                // plane takes this exit
+                if matches!(plane.destination, Destination::Exit(dest_eid) if dest_eid == *eid) {
+                    if self.clearance_required && !plane.cleared {
+                        // holding, see hold_uncleared_planes
+                        continue;
+                    }
+                    if plane.height < plane.spec.exit_height {
+                        // only when it got here, a plane that waits for its next move already
+                        // turned around
+                        if !plane.moves_this_tick() {
+                            continue;
                         }
+                        info!(plane = %plane.id, exit = eid, height = plane.height, "plane too low to exit, going around");
+                        if let Some(p) = self.planes.get_mut(&plane.id) {
+                            p.direction = p.direction.opposite();
+                            p.turning_to = None;
+                        }
+                        self.warnings.push(Warning::TooLowToExit(plane.id, *eid));
+                        continue;
                     }
-                    // TODO: detect crashing plane
+                    // right exit
+                    info!(plane = %plane.id, exit = eid, "plane left through its exit");
+                    self.planes.remove(&plane.id);
+                    self.stats.exits += 1;
+                    self.stats.fuel_left += plane.fuel_left() as u64;
+                } else {
+                    // wrong exit
+                    warn!(plane = %plane.id, exit = eid, destination = %plane.destination, "plane took the wrong exit");
+                    return Some((*plane, *eid));
                 }
             }
         }
         None
     }
 
-    pub fn tick_planes(&mut self) -> State {
-        for plane in self.planes.values_mut() {
-            if let Err(()) = plane.tick() {
-                return State::PlaneNoFuel(*plane);
+    /// Airports and the planes on the ground there, ordered by airport and plane id
+    fn planes_on_airports(&self) -> BTreeMap<u8, Vec<char>> {
+        let mut on_airports: BTreeMap<u8, Vec<char>> = BTreeMap::new();
+        for plane in self
+            .planes
+            .values()
+            .filter(|p| p.height == 0 && !p.just_spawned && !p.taking_off)
+        {
+            if let Some(WorldTile::Airport(_, aid)) = self
+                .tiles
+                .get(plane.pos.y)
+                .and_then(|row| row.get(plane.pos.x))
+            {
+                on_airports.entry(*aid).or_default().push(plane.id);
+            }
+        }
+        for planes in on_airports.values_mut() {
+            planes.sort();
+        }
+        on_airports
+    }
+
+    /// Send planes around that reached their destination without clearance
+    ///
+    /// Planes at their exit turn around, planes that touched down at their airport climb back
+    /// to [`GO_AROUND_HEIGHT`]. Does nothing unless the world requires clearance.
+    fn hold_uncleared_planes(&mut self) {
+        if !self.clearance_required {
+            return;
+        }
+        let exit_positions: HashMap<u8, Pos> = self
+            .exits
+            .iter()
+            .map(|(id, exit)| (*id, self.exit_pos(exit)))
+            .collect();
+        for plane in self
+            .planes
+            .values_mut()
+            .filter(|p| !p.cleared && !p.just_spawned)
+        {
+            match plane.destination {
+                // only when it got there, a plane that waits for its next move already turned
+                Destination::Exit(eid)
+                    if exit_positions.get(&eid) == Some(&plane.pos) && plane.moves_this_tick() =>
+                {
+                    plane.direction = plane.direction.opposite();
+                    plane.turning_to = None;
+                }
+                Destination::Airport(aid)
+                    if plane.height == 0
+                        && matches!(
+                            self.tiles.get(plane.pos.y).and_then(|row| row.get(plane.pos.x)),
+                            Some(WorldTile::Airport(_, id)) if *id == aid
+                        ) =>
+                {
+                    plane.height = 1;
+                    plane.target_height = GO_AROUND_HEIGHT;
+                }
+                _ => continue,
+            }
+            info!(plane = %plane.id, destination = %plane.destination, "plane not cleared, going around");
+            self.warnings
+                .push(Warning::NotCleared(plane.id, plane.destination));
+        }
+    }
+
+    /// Only one plane can land at an airport at a time
+    ///
+    /// If several planes touched down at the same airport, the one with the lowest id lands and
+    /// the others go around, climbing back to [`GO_AROUND_HEIGHT`].
+    fn resolve_simultaneous_landings(&mut self) {
+        for (aid, planes) in self.planes_on_airports() {
+            for pid in planes.into_iter().skip(1) {
+                let Some(plane) = self.planes.get_mut(&pid) else {
+                    continue;
+                };
+                plane.height = 1;
+                plane.target_height = GO_AROUND_HEIGHT;
+                info!(plane = %pid, airport = aid, "plane goes around");
+                self.warnings.push(Warning::GoAround(pid, aid));
+            }
+        }
+    }
+
+    /// Warn about planes that came too close to each other, or stayed too close for the
+    /// [cooldown](Self::set_proximity_cooldown)
+    fn check_proximity(&mut self) {
+        let conflicts = self.proximity_conflicts();
+        self.proximity_warned
+            .retain(|pair, _| conflicts.contains(pair));
+        for pair in conflicts {
+            match self.proximity_warned.get_mut(&pair) {
+                Some(age) if *age + 1 < self.proximity_cooldown => *age += 1,
+                _ => {
+                    debug!(a = %pair.0, b = %pair.1, "planes too close");
+                    self.warnings.push(Warning::Proximity(pair.0, pair.1));
+                    self.proximity_warned.insert(pair, 0);
+                }
+            }
+        }
+    }
+
+    /// Warn about planes that will touch down at the same airport in the next move
+    fn check_converging_landings(&mut self) {
+        let mut landing: BTreeMap<u8, Vec<char>> = BTreeMap::new();
+        for plane in self.planes.values() {
+            let Destination::Airport(aid) = plane.destination else {
+                continue;
+            };
+            if plane.height > 1 || plane.target_height != 0 {
+                continue;
+            }
+            let next = plane.pos.step(plane.direction);
+            if next.is_some() && next == self.destination_pos(plane.destination) {
+                landing.entry(aid).or_default().push(plane.id);
+            }
+        }
+        for (aid, mut planes) in landing {
+            planes.sort();
+            if let [a, b, ..] = planes[..] {
+                self.warnings.push(Warning::ConvergingLanding(a, b, aid));
+            }
+        }
+    }
+
+    /// Removes planes that exit and returns Some if a plane took the wrong exit
+    ///
+    /// None if everything is ok, some only if a plane took the wrong exit
+    ///
+    /// Planes below the exit height of their kind don't leave, they turn around at their exit.
+    fn planes_take_exits(&mut self) -> Option<(Plane, u8)> {
+        for (pid, plane) in self.planes.clone() {
+            if plane.just_spawned {
+                debug!("Plane {pid} is too new, skipping for exit check");
+                continue;
+            }
+            if plane.pos.y == 0 {
+                if let Some(v) = self.plane_exit_check_inner(&plane, DirectionGrid::Up, plane.pos.x)
+                {
+                    return Some(v);
+                }
+            }
+            if plane.pos.y + 1 == self.y {
+                if let Some(v) =
+                    self.plane_exit_check_inner(&plane, DirectionGrid::Down, plane.pos.x)
+                {
+                    return Some(v);
+                }
+            }
+            if plane.pos.x == 0 {
+                if let Some(v) =
+                    self.plane_exit_check_inner(&plane, DirectionGrid::Left, plane.pos.y)
+                {
+                    return Some(v);
+                }
+            }
+            if plane.pos.x + 1 == self.x {
+                if let Some(v) =
+                    self.plane_exit_check_inner(&plane, DirectionGrid::Right, plane.pos.y)
+                {
+                    return Some(v);
+                }
+            }
+        }
+        None
+    }
+
+    /// Removes planes that exit and returns Some if a plane took the wrong exit
+    ///
+    /// Returns:
+    ///
+    /// - None: Everything is okay. Maybe a plane landed at the correct airport and was removed
+    /// - Some(Plane, None): A plane crashed on the ground (height 0)
+    /// - Some(Plane, Some(airport_id)): A plane landed in the wrong airport
+    fn planes_land(&mut self) -> Option<(Plane, Option<u8>)> {
+        let mut on_ground: Vec<Plane> = self
+            .planes
+            .values()
+            .filter(|plane| plane.height == 0 && !plane.just_spawned && !plane.taking_off)
+            .copied()
+            .collect();
+        on_ground.sort_by_key(|plane| plane.id);
+        for plane in on_ground {
+            let Some(WorldTile::Airport(airdir, actual_aid)) = self.tile(plane.pos) else {
+                // touched down away from any airport
+                return Some((plane, None));
+            };
+            let Destination::Airport(dest_aid) = plane.destination else {
+                // planes flying to an exit have no business on a runway
+                return Some((plane, None));
+            };
+            if DirectionCardinal::from(airdir) != plane.direction {
+                // landed across the runway
+                return Some((plane, None));
+            }
+            if dest_aid != actual_aid {
+                return Some((plane, Some(actual_aid)));
+            }
+            // right airport, right direction
+            info!(plane = %plane.id, airport = actual_aid, "plane landed");
+            self.planes.remove(&plane.id);
+            self.stats.landings += 1;
+            self.stats.fuel_left += plane.fuel_left() as u64;
+        }
+        None
+    }
+
+    /// Slow down planes in a slow zone and let the others go back to their normal speed
+    fn update_slowed_planes(&mut self) {
+        for plane in self.planes.values_mut() {
+            let slowed = matches!(
+                self.tiles
+                    .get(plane.pos.y)
+                    .and_then(|row| row.get(plane.pos.x)),
+                Some(WorldTile::SlowZone)
+            );
+            if slowed != plane.slowed {
+                debug!("Plane {} slowed: {slowed}", plane.id);
+                plane.slowed = slowed;
+            }
+        }
+    }
+
+    /// Move every plane by one tick and check what happened to them
+    ///
+    /// The outcome is counted in the [`Stats`].
+    pub fn tick_planes(&mut self) -> State {
+        let state = self.advance_planes();
+        self.stats.record(&state);
+        state
+    }
+
+    fn advance_planes(&mut self) -> State {
+        self.check_destinations();
+
+        for plane in self.planes.values_mut() {
+            let before = plane.pos;
+            if plane.tick().is_err() {
+                warn!(plane = %plane.id, x = plane.pos.x, y = plane.pos.y, "plane ran out of fuel");
+                return State::PlaneNoFuel(*plane);
+            }
+            plane.just_spawned &= plane.ticks < self.spawn_immunity;
+            if self.diagonal_fuel_cost && plane.pos.x != before.x && plane.pos.y != before.y {
+                plane.extra_fuel_burned += DIAGONAL_EXTRA_FUEL;
+            }
+            if let Some(wind) = self.wind {
+                if plane.pos != before && plane.ticks % WIND_FUEL_TICKS == 0 {
+                    match wind.effect(plane.direction) {
+                        WindEffect::Tailwind => plane.fuel_saved += wind.strength as usize,
+                        WindEffect::Headwind => plane.extra_fuel_burned += wind.strength as usize,
+                        WindEffect::Crosswind => (),
+                    }
+                }
+            }
+            if plane.pos != before {
+                let tile = self
+                    .tiles
+                    .get(plane.pos.y)
+                    .and_then(|row| row.get(plane.pos.x));
+                if let Some(WorldTile::Beacon(bid)) = tile {
+                    match self.beacon_restrictions.get(bid) {
+                        Some(r) if !r.allows(plane.height) => {
+                            debug!(plane = %plane.id, beacon = bid, height = plane.height, "plane broke a crossing restriction");
+                            self.warnings
+                                .push(Warning::CrossingRestriction(plane.id, *bid, *r));
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            if plane.fuel_critical() {
+                debug!(plane = %plane.id, "plane has one tick of fuel left");
+                self.warnings.push(Warning::FuelCritical(
+                    plane.id,
+                    plane.fuel_reading(self.fuel_unit),
+                ));
+            }
+        }
+        self.update_slowed_planes();
+        self.hold_at_beacons();
+        self.process_orders();
+        for plane in self.planes.values_mut() {
+            Self::steer(plane, self.movement_mode);
+        }
+
+        if let Some(plane) = self.obstacle_check() {
+            warn!(plane = %plane.id, x = plane.pos.x, y = plane.pos.y, "plane flew into an obstacle");
+            return State::PlaneHitObstacle(plane);
+        }
+
+        self.hold_uncleared_planes();
+        if let Some((plane, exit_id)) = self.planes_take_exits() {
+            return State::WrongExit(plane, exit_id);
+        }
+        self.resolve_simultaneous_landings();
+        if let Some((plane, id_of_wrong_airport)) = self.planes_land() {
+            if let Some(airport_id) = id_of_wrong_airport {
+                warn!(plane = %plane.id, airport = airport_id, "plane landed at the wrong airport");
+                return State::WrongAirport(plane, airport_id);
+            } else {
+                warn!(plane = %plane.id, x = plane.pos.x, y = plane.pos.y, "plane crashed");
+                return State::PlaneCrash(plane);
+            }
+        }
+        if let Some((plane_a, plane_b)) = self.collision_check() {
+            warn!(a = %plane_a.id, b = %plane_b.id, x = plane_a.pos.x, y = plane_a.pos.y, "planes collided");
+            return State::PlaneCollision(plane_a, plane_b);
+        }
+        if let Some((plane, direction, wall_pos)) = self.wall_collision_check() {
+            warn!(plane = %plane.id, wall = ?direction, wall_pos, "plane flew into the wall");
+            return State::PlaneTouchesWall(plane, direction, wall_pos);
+        }
+        self.check_converging_landings();
+        self.check_proximity();
+
+        State::Ongoing
+    }
+}
+
+impl Stats {
+    /// Planes that left through their exit or landed at their airport
+    pub fn handled(&self) -> u64 {
+        self.exits + self.landings
+    }
+
+    /// Planes that did not make it to their destination
+    pub fn lost(&self) -> u64 {
+        self.crashes + self.collisions + self.wrong_destinations + self.out_of_fuel
+    }
+
+    /// Count the loss `state` describes, if any
+    fn record(&mut self, state: &State) {
+        match state {
+            State::Ongoing => (),
+            State::PlaneCrash(_) | State::PlaneHitObstacle(_) | State::PlaneTouchesWall(..) => {
+                self.crashes += 1
+            }
+            State::PlaneCollision(..) => self.collisions += 1,
+            State::WrongExit(..) | State::WrongAirport(..) => self.wrong_destinations += 1,
+            State::PlaneNoFuel(_) => self.out_of_fuel += 1,
+        }
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Exits {} | Landings {} | Crashes {} | Collisions {} | Wrong destination {} | Out of fuel {}",
+            self.exits,
+            self.landings,
+            self.crashes,
+            self.collisions,
+            self.wrong_destinations,
+            self.out_of_fuel
+        )
+    }
+}
+
+impl Pos {
+    /// The position one tile further in `direction`, None if that would leave the positive range
+    pub fn step(self, direction: DirectionCardinal) -> Option<Pos> {
+        let (dx, dy) = direction.offset();
+        Some(Pos {
+            x: self.x.checked_add_signed(dx)?,
+            y: self.y.checked_add_signed(dy)?,
+        })
+    }
+}
+
+impl MovementMode {
+    pub fn allows(self, direction: DirectionCardinal) -> bool {
+        match self {
+            Self::EightWay => true,
+            Self::Hex => !matches!(
+                direction,
+                DirectionCardinal::North | DirectionCardinal::South
+            ),
+        }
+    }
+
+    /// Every allowed heading, clockwise starting at north
+    pub fn headings(self) -> Vec<DirectionCardinal> {
+        DirectionCardinal::ALL
+            .into_iter()
+            .filter(|d| self.allows(*d))
+            .collect()
+    }
+
+    /// The heading after turning `steps` allowed headings clockwise, negative steps turn
+    /// counterclockwise
+    ///
+    /// Turning from a heading that is not allowed ends on the nearest allowed heading in that
+    /// direction after the first step.
+    pub fn turn(self, from: DirectionCardinal, steps: isize) -> DirectionCardinal {
+        let mut direction = from;
+        for _ in 0..steps.unsigned_abs() {
+            loop {
+                direction = direction.rotate(steps.signum());
+                if self.allows(direction) {
+                    break;
+                }
+            }
+        }
+        direction
+    }
+}
+
+impl DirectionCardinal {
+    /// Every direction, clockwise starting at north
+    pub const ALL: [DirectionCardinal; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+
+    /// How x and y change when moving one tile in this direction, y grows to the south
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Self::North => (0, -1),
+            Self::East => (1, 0),
+            Self::South => (0, 1),
+            Self::West => (-1, 0),
+            Self::NorthEast => (1, -1),
+            Self::NorthWest => (-1, -1),
+            Self::SouthEast => (1, 1),
+            Self::SouthWest => (-1, 1),
+        }
+    }
+
+    /// A unicode arrow pointing this way
+    pub fn arrow(self) -> char {
+        match self {
+            Self::North => '↑',
+            Self::NorthEast => '↗',
+            Self::East => '→',
+            Self::SouthEast => '↘',
+            Self::South => '↓',
+            Self::SouthWest => '↙',
+            Self::West => '←',
+            Self::NorthWest => '↖',
+        }
+    }
+
+    /// Turn by `eighths` of a full circle, clockwise for positive values
+    pub fn rotate(self, eighths: isize) -> Self {
+        Self::ALL[(self.index() as isize + eighths).rem_euclid(8) as usize]
+    }
+
+    /// Turn towards `heading` the shorter way round, by at most `eighths` of a full circle
+    pub fn turn_towards(self, heading: Self, eighths: u8) -> Self {
+        let clockwise = heading.index() as isize - self.index() as isize;
+        let clockwise = clockwise.rem_euclid(8);
+        let eighths = eighths as isize;
+        if clockwise <= 4 {
+            self.rotate(clockwise.min(eighths))
+        } else {
+            self.rotate(-(8 - clockwise).min(eighths))
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|d| *d == self)
+            .expect("ALL has every direction")
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::North => Self::South,
+            Self::East => Self::West,
+            Self::South => Self::North,
+            Self::West => Self::East,
+            Self::NorthEast => Self::SouthWest,
+            Self::NorthWest => Self::SouthEast,
+            Self::SouthEast => Self::NorthWest,
+            Self::SouthWest => Self::NorthEast,
+        }
+    }
+}
+
+impl Display for PlaneKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Small => "small",
+                Self::Jet => "jet",
+            }
+        )
+    }
+}
+
+impl Display for Exit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (wall, axis) = match self.wall_direction {
+            DirectionGrid::Up => ("top", "column"),
+            DirectionGrid::Down => ("bottom", "column"),
+            DirectionGrid::Left => ("left", "row"),
+            DirectionGrid::Right => ("right", "row"),
+        };
+        write!(
+            f,
+            "{wall} wall at {axis} {}, planes come in heading {}",
+            self.wall_pos, self.plane_out_direction
+        )?;
+        if let Some(height) = self.start_height {
+            write!(f, " at height {height}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for DirectionGrid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Up => "^",
+                Self::Left => "<",
+                Self::Down => "v",
+                Self::Right => ">",
+            }
+        )
+    }
+}
+
+impl Display for DirectionCardinal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::North => "north",
+                Self::East => "east",
+                Self::South => "south",
+                Self::West => "west",
+                Self::NorthEast => "northeast",
+                Self::NorthWest => "northwest",
+                Self::SouthEast => "southeast",
+                Self::SouthWest => "southwest",
+            }
+        )
+    }
+}
+
+/// Parses compass abbreviations like `N` or `sw` and full names like `northeast`, ignoring case
+impl FromStr for DirectionCardinal {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "n" | "north" => Self::North,
+            "e" | "east" => Self::East,
+            "s" | "south" => Self::South,
+            "w" | "west" => Self::West,
+            "ne" | "northeast" => Self::NorthEast,
+            "nw" | "northwest" => Self::NorthWest,
+            "se" | "southeast" => Self::SouthEast,
+            "sw" | "southwest" => Self::SouthWest,
+            _ => return Err(Error::UnknownDirection(s.to_string())),
+        })
+    }
+}
+
+impl TryFrom<&str> for DirectionCardinal {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Display for WorldTile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Empty => ". ".to_string(),
+                Self::Route => "+ ".to_string(),
+                Self::SlowZone => ": ".to_string(),
+                Self::Obstacle => "##".to_string(),
+                Self::Beacon(idx) => format!("b{idx}"),
+                Self::Airport(dir, idx) => format!("{dir}{idx}"),
+            }
+        )
+    }
+}
+
+impl World {
+    /// The world turned by 90 degrees clockwise
+    ///
+    /// Everything is turned with the map: tiles, the walls and directions of exits, the runways
+    /// of airports and the planes in the air. The top wall becomes the right wall.
+    pub fn rotated(&self) -> World {
+        let height = self.y;
+        self.transformed(
+            self.y,
+            self.x,
+            |pos| Pos {
+                x: height - 1 - pos.y,
+                y: pos.x,
+            },
+            |dir| dir.rotate(2),
+            |wall| match wall {
+                DirectionGrid::Up => DirectionGrid::Right,
+                DirectionGrid::Right => DirectionGrid::Down,
+                DirectionGrid::Down => DirectionGrid::Left,
+                DirectionGrid::Left => DirectionGrid::Up,
+            },
+        )
+    }
+
+    /// The world mirrored left to right
+    pub fn mirrored(&self) -> World {
+        let width = self.x;
+        self.transformed(
+            self.x,
+            self.y,
+            |pos| Pos {
+                x: width - 1 - pos.x,
+                y: pos.y,
+            },
+            |dir| {
+                let (dx, dy) = dir.offset();
+                *DirectionCardinal::ALL
+                    .iter()
+                    .find(|d| d.offset() == (-dx, dy))
+                    .expect("every direction has a mirror image")
+            },
+            |wall| match wall {
+                DirectionGrid::Left => DirectionGrid::Right,
+                DirectionGrid::Right => DirectionGrid::Left,
+                other => other,
+            },
+        )
+    }
+
+    /// Build a copy of the world with every position and direction mapped
+    ///
+    /// `width` and `height` are the size of the new world.
+    fn transformed(
+        &self,
+        width: usize,
+        height: usize,
+        map_pos: impl Fn(Pos) -> Pos,
+        map_dir: impl Fn(DirectionCardinal) -> DirectionCardinal,
+        map_wall: impl Fn(DirectionGrid) -> DirectionGrid,
+    ) -> World {
+        let mut tiles = vec![vec![WorldTile::Empty; width]; height];
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let pos = map_pos(Pos { x, y });
+                tiles[pos.y][pos.x] = match *tile {
+                    WorldTile::Airport(runway, aid) => WorldTile::Airport(map_wall(runway), aid),
+                    other => other,
+                };
+            }
+        }
+
+        let exits = self
+            .exits
+            .iter()
+            .map(|(id, exit)| {
+                let wall_direction = map_wall(exit.wall_direction);
+                let pos = map_pos(self.exit_pos(exit));
+                let wall_pos = match wall_direction {
+                    DirectionGrid::Up | DirectionGrid::Down => pos.x,
+                    DirectionGrid::Left | DirectionGrid::Right => pos.y,
+                };
+                let exit = Exit {
+                    wall_direction,
+                    plane_out_direction: map_dir(exit.plane_out_direction),
+                    wall_pos,
+                    ..*exit
+                };
+                (*id, exit)
+            })
+            .collect();
+
+        let planes = self
+            .planes
+            .iter()
+            .map(|(id, plane)| {
+                let mut plane = *plane;
+                plane.pos = map_pos(plane.pos);
+                plane.direction = map_dir(plane.direction);
+                plane.direct_to = plane.direct_to.map(&map_pos);
+                plane.turning_to = plane.turning_to.map(&map_dir);
+                (*id, plane)
+            })
+            .collect();
+
+        let orders = self
+            .orders
+            .iter()
+            .map(|(id, queue)| {
+                let queue = queue
+                    .iter()
+                    .map(|queued| match queued.order {
+                        Order::Heading(dir) => QueuedOrder {
+                            order: Order::Heading(map_dir(dir)),
+                            ..*queued
+                        },
+                        Order::DirectTo(pos) => QueuedOrder {
+                            order: Order::DirectTo(map_pos(pos)),
+                            ..*queued
+                        },
+                        _ => *queued,
+                    })
+                    .collect();
+                (*id, queue)
+            })
+            .collect();
+
+        World {
+            x: width,
+            y: height,
+            tiles,
+            planes,
+            orders,
+            panic_held: self.panic_held.clone(),
+            exits,
+            routes_mandatory: self.routes_mandatory,
+            diagonal_fuel_cost: self.diagonal_fuel_cost,
+            wind: self
+                .wind
+                .map(|wind| Wind::new(map_dir(wind.direction), wind.strength)),
+            movement_mode: self.movement_mode,
+            clearance_required: self.clearance_required,
+            safety_checks: self.safety_checks,
+            beacon_policy: self.beacon_policy,
+            beacon_restrictions: self.beacon_restrictions.clone(),
+            spawn_immunity: self.spawn_immunity,
+            collision_radius: self.collision_radius,
+            proximity_cooldown: self.proximity_cooldown,
+            proximity_warned: self.proximity_warned.clone(),
+            controllers: self.controllers,
+            fuel_unit: self.fuel_unit,
+            specs: self.specs,
+            plane_counter: self.plane_counter,
+            warnings: self.warnings.clone(),
+            stats: self.stats,
+        }
+    }
+}
+
+/// Width of the row labels left of the map when rendering with rulers
+pub const RULER_WIDTH: usize = 3;
+
+/// Column indices to put above the map, aligned with the two character wide tiles
+///
+/// For maps wider than 10 tiles, the first line holds the tens and the second the ones.
+pub fn column_ruler(width: usize) -> Vec<String> {
+    // the row labels and the left wall come before the first tile
+    let indent = " ".repeat(RULER_WIDTH + 2);
+    let mut ones = indent.clone();
+    let mut tens = indent;
+    for x in 0..width {
+        ones.push_str(&format!("{} ", x % 10));
+        if x % 10 == 0 && x > 0 {
+            tens.push_str(&format!("{} ", (x / 10) % 10));
+        } else {
+            tens.push_str("  ");
+        }
+    }
+    let mut lines = Vec::new();
+    if width > 10 {
+        lines.push(tens.trim_end().to_string());
+    }
+    lines.push(ones.trim_end().to_string());
+    lines
+}
+
+/// Row index to put left of the map
+pub fn row_label(y: usize) -> String {
+    format!("{:>width$} ", y % 100, width = RULER_WIDTH - 1)
+}
+
+impl World {
+    /// Render the map with column and row indices outside of the border
+    pub fn render_with_rulers(&self) -> String {
+        let mut lines = column_ruler(self.x);
+        for (i, line) in self.to_string().lines().enumerate() {
+            // the first and last line are the top and bottom walls
+            let label = if i == 0 || i == self.y + 1 {
+                " ".repeat(RULER_WIDTH)
+            } else {
+                row_label(i - 1)
+            };
+            lines.push(format!("{label}{line}"));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Display for World {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut buf = String::new();
+
+        let mut lines = Vec::new();
+
+        // top border
+        buf.push_str("┌─");
+        for x in 0..self.x {
+            buf.push_str(&self.get_wall(x, DirectionGrid::Up));
+        }
+        buf.push('┐');
+        lines.push(buf.clone());
+        buf.clear();
+
+        // inner map
+        for (y, row) in self.tiles.iter().enumerate() {
+            buf.push_str(&self.get_wall(y, DirectionGrid::Left));
+            'tile: for (x, tile) in row.iter().enumerate() {
+                for plane in self.planes.values() {
+                    if plane.pos == [x, y].into() {
+                        buf.push_str(&plane.to_string());
+                        continue 'tile;
+                    }
+                }
+                buf.push_str(&tile.to_string());
+            }
+            buf.push_str(&self.get_wall(y, DirectionGrid::Right));
+            lines.push(buf.clone());
+            buf.clear();
+        }
+
+        // top border
+        buf.push_str("└─");
+        for x in 0..self.x {
+            buf.push_str(&self.get_wall(x, DirectionGrid::Down));
+        }
+        buf.push('┘');
+        lines.push(buf.clone());
+        buf.clear();
+
+        // actual output
+        buf = lines.join("\n");
+
+        write!(f, "{buf}")
+    }
+}
+
+impl Display for Plane {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let id = match self.kind {
+            PlaneKind::Small => self.id.to_ascii_lowercase(),
+            PlaneKind::Jet => self.id.to_ascii_uppercase(),
+        };
+        write!(f, "{id}{}", self.height)
+    }
+}
+
+impl From<[usize; 2]> for Pos {
+    fn from(value: [usize; 2]) -> Self {
+        Pos {
+            x: value[0],
+            y: value[1],
+        }
+    }
+}
+
+impl From<DirectionGrid> for DirectionCardinal {
+    fn from(value: DirectionGrid) -> Self {
+        match value {
+            DirectionGrid::Up => DirectionCardinal::North,
+            DirectionGrid::Down => DirectionCardinal::South,
+            DirectionGrid::Left => DirectionCardinal::West,
+            DirectionGrid::Right => DirectionCardinal::East,
+        }
+    }
+}
+
+/// Only north, east, south and west have a grid direction, the diagonals are rejected with
+/// [`Error::NotAGridDirection`]
+impl TryFrom<DirectionCardinal> for DirectionGrid {
+    type Error = Error;
+
+    fn try_from(value: DirectionCardinal) -> Result<Self, Self::Error> {
+        match value {
+            DirectionCardinal::North => Ok(DirectionGrid::Up),
+            DirectionCardinal::South => Ok(DirectionGrid::Down),
+            DirectionCardinal::West => Ok(DirectionGrid::Left),
+            DirectionCardinal::East => Ok(DirectionGrid::Right),
+            DirectionCardinal::NorthEast
+            | DirectionCardinal::NorthWest
+            | DirectionCardinal::SouthEast
+            | DirectionCardinal::SouthWest => Err(Error::NotAGridDirection(value)),
+        }
+    }
+}
+
+impl TryFrom<(i32, i32)> for Pos {
+    type Error = Error;
+
+    fn try_from(value: (i32, i32)) -> Result<Self, Self::Error> {
+        if value.0 >= 0 && value.1 >= 0 {
+            Ok(Pos {
+                x: value.0 as usize,
+                y: value.1 as usize,
+            })
+        } else {
+            Err(Error::PosFromSigned(value))
+        }
+    }
+}
+
+impl Display for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Ongoing => "The game goes on".to_string(),
+                Self::WrongExit(plane, eid) =>
+                    format!("Plane {} exited at the wrong exit: {eid}", plane.id),
+                Self::PlaneCrash(plane) =>
+                    format!("Plane {} crashed on the ground (height 0)", plane.id),
+                Self::PlaneNoFuel(plane) => format!("Plane {} is out of fuel", plane.id),
+                Self::PlaneHitObstacle(plane) =>
+                    format!("Plane {} crashed into an obstacle", plane.id),
+                Self::WrongAirport(plane, aid) =>
+                    format!("Plane {} landed at the wrong airport: {aid}", plane.id),
+                Self::PlaneCollision(pa, pb) =>
+                    format!("Plane {} collided with Plane {}", pa.id, pb.id),
+                Self::PlaneTouchesWall(plane, _, _) =>
+                    format!("Plane {} did not leave through an exit", plane.id),
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        plane::{Destination, Plane, PlaneSpec, START_HEIGHT},
+        world::WorldTile,
+    };
+
+    use super::*;
+
+    #[test]
+    #[should_panic]
+    fn test_world_place_route_out_of_bounds() {
+        let mut world = World::new(20, 20);
+        world.place_route_in_line([0, 0], [20, 19]).unwrap();
+        for i in 0..19 {
+            assert_eq!(world.tiles[i][i], WorldTile::Route);
+        }
+
+        let mut world = World::new(20, 20);
+        world.place_route_in_line([0, 0], [19, 20]).unwrap();
+        for i in 0..19 {
+            assert_eq!(world.tiles[i][i], WorldTile::Route);
+        }
+    }
+
+    #[test]
+    fn test_world_place_route_0() {
+        let mut world = World::new(20, 20);
+        world.place_route_in_line([0, 0], [19, 19]).unwrap();
+        println!("{}", world);
+        for i in 0..19 {
+            assert_eq!(world.tiles[i][i], WorldTile::Route);
+        }
+    }
+
+    #[test]
+    fn test_world_place_route_1() {
+        let mut world = World::new(20, 20);
+        world.place_route_in_line([19, 19], [0, 0]).unwrap();
+        println!("{}", world);
+        for i in 0..19 {
+            assert_eq!(world.tiles[i][i], WorldTile::Route);
+        }
+    }
+
+    #[test]
+    fn test_world_place_route_2() {
+        let mut world = World::new(20, 20);
+        world.place_route_in_line([19, 0], [0, 19]).unwrap();
+        println!("{}", world);
+        for i in 0..19 {
+            assert_eq!(world.tiles[19 - i][i], WorldTile::Route);
+        }
+    }
+
+    #[test]
+    fn test_world_place_route_single_point() {
+        let mut world = World::new(20, 20);
+        world.place_route_in_line([7, 3], [7, 3]).unwrap();
+        let routes: Vec<Pos> = (0..world.height())
+            .flat_map(|y| (0..world.width()).map(move |x| Pos { x, y }))
+            .filter(|pos| world.tile(*pos) == Some(WorldTile::Route))
+            .collect();
+        assert_eq!(routes, vec![Pos { x: 7, y: 3 }]);
+    }
+
+    #[test]
+    fn test_world_unknown_destination_warning() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap();
+        world
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 1)
+            .unwrap();
+        let plane = Plane::new(
+            [5, 5].into(),
+            DirectionCardinal::South,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(42),
+        );
+        world.planes.insert(plane.id, plane);
+
+        assert!(matches!(world.tick_planes(), State::Ongoing));
+        assert_eq!(
+            world.take_warnings(),
+            vec![Warning::UnknownDestination('a', Destination::Exit(42))]
+        );
+        assert!(world.has_destination(world.planes[&'a'].destination));
+
+        assert!(matches!(world.tick_planes(), State::Ongoing));
+        assert!(world.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_world_spawned_planes_have_destinations() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap();
+        assert!(matches!(
+            world.spawn_plane_at_exit(0, PlaneKind::Jet, &mut rand::rng()),
+            Err(Error::NoDestinations)
+        ));
+
+        world
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 1)
+            .unwrap();
+        for y in 0..10 {
+            let id = world
+                .spawn_plane_at_exit(0, PlaneKind::Jet, &mut rand::rng())
+                .unwrap();
+            // make room for the next plane
+            world.plane_mut(id).unwrap().pos = [10, y].into();
+        }
+        for plane in world.planes.values() {
+            assert_eq!(plane.destination, Destination::Exit(1));
+        }
+    }
+
+    #[test]
+    fn test_world_slow_zone_cadence() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 0)
+            .unwrap();
+        for y in 4..6 {
+            world.place_tile(WorldTile::SlowZone, [5, y]).unwrap();
+        }
+        let plane = Plane::new(
+            [5, 2].into(),
+            DirectionCardinal::South,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        );
+        world.planes.insert(plane.id, plane);
+
+        let mut cadences = Vec::new();
+        for _ in 0..8 {
+            world.tick_planes();
+            let plane = world.planes[&'a'];
+            cadences.push((plane.pos.y, plane.cadence()));
+        }
+        assert_eq!(
+            cadences,
+            vec![
+                (3, 1),
+                (4, 2),
+                (4, 2),
+                (5, 2),
+                (5, 2),
+                (6, 1),
+                (7, 1),
+                (8, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_world_plane_spec_override() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap();
+        world
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 1)
+            .unwrap();
+        world.plane_specs_mut().small.fuel = 3;
+        world.plane_specs_mut().small.start_height = 4;
+
+        let id = world
+            .spawn_plane_at_exit(0, PlaneKind::Small, &mut rand::rng())
+            .unwrap();
+        assert_eq!(world.planes[&id].height, 4);
+        assert_eq!(world.tick_planes(), State::Ongoing);
+        assert_eq!(world.tick_planes(), State::Ongoing);
+        let mut out_of_fuel = world.planes[&id];
+        out_of_fuel.ticks += 1;
+        assert_eq!(world.tick_planes(), State::PlaneNoFuel(out_of_fuel));
+    }
+
+    #[test]
+    fn test_world_exit_start_height() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap();
+        world
+            .place_exit_at_height(DirectionGrid::Down, DirectionCardinal::North, 5, 1, 3)
+            .unwrap();
+
+        world
+            .spawn_plane_at_exit(0, PlaneKind::Jet, &mut rand::rng())
+            .unwrap();
+        world
+            .spawn_plane_at_exit(1, PlaneKind::Jet, &mut rand::rng())
+            .unwrap();
+        let mut heights: Vec<(Destination, u8)> = world
+            .planes
+            .values()
+            .map(|p| (p.destination, p.height))
+            .collect();
+        heights.sort_by_key(|(_, h)| *h);
+        assert_eq!(
+            heights,
+            vec![
+                (Destination::Exit(0), 3),
+                (Destination::Exit(1), START_HEIGHT)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_world_rulers() {
+        assert_eq!(column_ruler(4), vec!["     0 1 2 3"]);
+        assert_eq!(
+            column_ruler(12),
+            vec!["                         1", "     0 1 2 3 4 5 6 7 8 9 0 1"]
+        );
+        assert_eq!(row_label(3), " 3 ");
+        assert_eq!(row_label(13), "13 ");
+
+        let mut world = World::new(12, 3);
+        world.place_tile(WorldTile::Beacon(0), [11, 2]).unwrap();
+        let rendered = world.render_with_rulers();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 2 + 3 + 2);
+        assert!(lines[2].starts_with("   ┌─"));
+        assert!(lines[5].starts_with(" 2 │ "));
+        // the beacon is right below the last column index
+        let beacon_col = lines[5].chars().position(|c| c == 'b').unwrap();
+        assert_eq!(lines[1].chars().nth(beacon_col), Some('1'));
+    }
+
+    fn route_world() -> World {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap();
+        world
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 1)
+            .unwrap();
+        world.place_route_in_line([5, 0], [5, 19]).unwrap();
+        world.set_routes_mandatory(true);
+        world
+    }
+
+    #[test]
+    fn test_world_validate_reachable() {
+        let mut world = route_world();
+        world
+            .place_tile(WorldTile::Airport(DirectionGrid::Right, 0), [5, 10])
+            .unwrap();
+        world.validate().unwrap();
+    }
+
+    #[test]
+    fn test_world_validate_unreachable() {
+        let mut world = route_world();
+        world
+            .place_tile(WorldTile::Airport(DirectionGrid::Right, 3), [12, 10])
+            .unwrap();
+        assert!(matches!(
+            world.validate(),
+            Err(Error::UnreachableDestination(3))
+        ));
+
+        world.set_routes_mandatory(false);
+        world.validate().unwrap();
+    }
+
+    #[test]
+    fn test_world_spawn_diagonal_exits() {
+        use DirectionCardinal as C;
+        use DirectionGrid as G;
+
+        for (wall, heading, wall_pos, pos) in [
+            (G::Right, C::SouthWest, 2, [19, 2]),
+            (G::Right, C::NorthWest, 17, [19, 17]),
+            (G::Left, C::NorthEast, 17, [0, 17]),
+            (G::Left, C::SouthEast, 0, [0, 0]),
+            (G::Up, C::SouthEast, 3, [3, 0]),
+            (G::Up, C::SouthWest, 19, [19, 0]),
+            (G::Down, C::NorthEast, 0, [0, 19]),
+            (G::Down, C::NorthWest, 16, [16, 19]),
+        ] {
+            let mut world = World::new(20, 20);
+            world
+                .place_exit(wall, heading, wall_pos, 0)
+                .unwrap()
+                .place_tile(WorldTile::Airport(DirectionGrid::Up, 0), [10, 10])
+                .unwrap();
+            let id = world
+                .spawn_plane_to(0, PlaneKind::Jet, Destination::Airport(0))
+                .unwrap();
+            let plane = *world.plane(id).unwrap();
+            assert_eq!(plane.pos, pos.into(), "{wall:?} {heading:?}");
+            assert_eq!(plane.direction, heading);
+
+            for _ in 0..2 {
+                assert_eq!(world.tick_planes(), State::Ongoing, "{wall:?} {heading:?}");
+            }
+            let moved = world.plane(id).unwrap().pos;
+            let (dx, dy) = heading.offset();
+            let inward = |from: usize, to: usize, d: isize| to as isize - from as isize == 2 * d;
+            assert!(
+                inward(plane.pos.x, moved.x, dx) && inward(plane.pos.y, moved.y, dy),
+                "{wall:?} {heading:?}: {:?} to {moved:?}",
+                plane.pos
+            );
+        }
+    }
+
+    #[test]
+    fn test_world_exit_ids() {
+        let mut rng = rand::rng();
+        let mut world = World::new(20, 20);
+        assert_eq!(world.random_exit_id(&mut rng), None);
+
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 7)
+            .unwrap()
+            .place_exit(DirectionGrid::Left, DirectionCardinal::East, 5, 2)
+            .unwrap();
+        for _ in 0..20 {
+            let id = world.random_exit_id(&mut rng).unwrap();
+            assert!(world.exit(id).is_some(), "{id}");
+        }
+        world.set_exit_spawn_weight(7, 0).unwrap();
+        for _ in 0..20 {
+            assert_eq!(world.random_exit_id(&mut rng), Some(2));
+        }
+        world.set_exit_spawn_weight(2, 0).unwrap();
+        assert_eq!(world.random_exit_id(&mut rng), None);
+    }
+
+    #[test]
+    fn test_world_validate_no_exits() {
+        let mut world = World::new(20, 20);
+        world
+            .place_tile(WorldTile::Airport(DirectionGrid::Right, 0), [5, 10])
+            .unwrap();
+        assert!(matches!(world.validate(), Err(Error::NoSpawnPoints)));
+
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap()
+            .set_exit_spawn_weight(0, 0)
+            .unwrap();
+        assert!(matches!(world.validate(), Err(Error::NoSpawnPoints)));
+        world.set_exit_spawn_weight(0, 1).unwrap();
+        world.validate().unwrap();
+    }
+
+    #[test]
+    fn test_world_validate_no_destinations() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap();
+        assert!(matches!(world.validate(), Err(Error::NoDestinations)));
+
+        world
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 1)
+            .unwrap();
+        world.validate().unwrap();
+    }
+
+    #[test]
+    fn test_world_queued_orders() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap();
+        world.place_tile(WorldTile::Beacon(0), [5, 4]).unwrap();
+        world.insert_plane(Plane::new(
+            [5, 2].into(),
+            DirectionCardinal::South,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        ));
+        world
+            .queue_order(
+                'a',
+                Trigger::AtBeacon(0),
+                Order::Heading(DirectionCardinal::East),
+            )
+            .unwrap();
+        world
+            .queue_order('a', Trigger::InTicks(3), Order::Height(5))
+            .unwrap();
+        assert!(world
+            .queue_order('z', Trigger::InTicks(1), Order::Height(5))
+            .is_err());
+        assert_eq!(world.orders('a').len(), 2);
+
+        world.tick_planes();
+        assert_eq!(world.planes[&'a'].direction, DirectionCardinal::South);
+        world.tick_planes();
+        assert_eq!(world.planes[&'a'].pos, [5, 4].into());
+        assert_eq!(world.planes[&'a'].direction, DirectionCardinal::East);
+        assert_eq!(world.orders('a').len(), 1);
+        world.tick_planes();
+        assert_eq!(world.planes[&'a'].target_height, 5);
+        assert!(world.orders('a').is_empty());
+    }
+
+    #[test]
+    fn test_world_describe_destination() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Right, DirectionCardinal::SouthWest, 2, 1)
+            .unwrap()
+            .place_tile(WorldTile::Airport(DirectionGrid::Up, 0), [4, 7])
+            .unwrap();
+        assert_eq!(
+            world.describe_destination(Destination::Exit(1)).unwrap(),
+            "exit 1: right wall at row 2, planes come in heading southwest"
+        );
+        assert_eq!(
+            world.describe_destination(Destination::Airport(0)).unwrap(),
+            "airport 0: at 4,7, runway ^"
+        );
+        assert_eq!(world.describe_destination(Destination::Exit(0)), None);
+
+        world
+            .place_exit_at_height(DirectionGrid::Up, DirectionCardinal::South, 12, 0, 5)
+            .unwrap();
+        assert_eq!(
+            world.exit(0).unwrap().to_string(),
+            "top wall at column 12, planes come in heading south at height 5"
+        );
+    }
+
+    #[test]
+    fn test_world_place_tile_if_empty() {
+        let mut world = World::new(20, 20);
+        world
+            .place_tile(WorldTile::Airport(DirectionGrid::Up, 0), [4, 7])
+            .unwrap();
+        assert!(matches!(
+            world.place_tile_if_empty(WorldTile::Beacon(0), [4, 7]),
+            Err(Error::CellOccupied(Pos { x: 4, y: 7 }))
+        ));
+        assert_eq!(world.tiles[7][4], WorldTile::Airport(DirectionGrid::Up, 0));
+
+        world
+            .place_tile_if_empty(WorldTile::Beacon(0), [4, 8])
+            .unwrap();
+        assert_eq!(world.tiles[8][4], WorldTile::Beacon(0));
+        assert!(world
+            .place_tile_if_empty(WorldTile::Beacon(1), [20, 8])
+            .is_err());
+    }
+
+    #[test]
+    fn test_world_direction_parse() {
+        let tokens = ["N", "ne", "E", "se", "s", "SW", "w", "nW"];
+        for (token, dir) in tokens.iter().zip(DirectionCardinal::ALL) {
+            assert_eq!(token.parse::<DirectionCardinal>().unwrap(), dir);
+            assert_eq!(DirectionCardinal::try_from(*token).unwrap(), dir);
+            assert_eq!(dir.to_string().parse::<DirectionCardinal>().unwrap(), dir);
+        }
+        for garbage in ["", "x", "nn", "north east", "en"] {
+            assert!(matches!(
+                garbage.parse::<DirectionCardinal>(),
+                Err(Error::UnknownDirection(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_world_fuel_critical_warning() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap();
+        let mut plane = Plane::new(
+            [5, 5].into(),
+            DirectionCardinal::East,
+            PlaneKind::Small,
+            'a',
+            Destination::Exit(0),
+        );
+        plane.spec.fuel = 3;
+        world.insert_plane(plane);
+
+        world.tick_planes();
+        assert!(world.take_warnings().is_empty());
+        world.tick_planes();
+        let warnings = world.take_warnings();
+        assert!(
+            matches!(warnings[..], [Warning::FuelCritical('A', fuel)] if fuel.left == 1),
+            "{warnings:?}"
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "Plane A runs out of fuel next tick! (1t left)"
+        );
+        assert!(matches!(world.tick_planes(), State::PlaneNoFuel(_)));
+        assert!(world.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_world_spawn_is_logged() {
+        use std::{
+            io::Write,
+            sync::{Arc, Mutex},
+        };
+
+        #[derive(Clone, Default)]
+        struct Buf(Arc<Mutex<Vec<u8>>>);
+        impl Write for Buf {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buf = Buf::default();
+        let writer = buf.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::INFO)
+            .with_ansi(false)
+            .without_time()
+            .with_writer(move || writer.clone())
+            .finish();
+
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap()
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 1)
+            .unwrap();
+        let id = tracing::subscriber::with_default(subscriber, || {
+            world
+                .spawn_plane_at_exit(0, PlaneKind::Jet, &mut rand::rng())
+                .unwrap()
+        });
+
+        let log = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        assert!(log.contains("plane spawned"), "{log}");
+        for field in [
+            format!("plane={id}"),
+            "exit=0".to_string(),
+            "x=5".to_string(),
+            "y=0".to_string(),
+            "destination=exit 1".to_string(),
+        ] {
+            assert!(log.contains(&field), "{field} missing in {log}");
+        }
+    }
+
+    #[test]
+    fn test_world_obstacle_crash() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap()
+            .place_tile(WorldTile::Obstacle, [7, 5])
+            .unwrap();
+        world.insert_plane(Plane::new(
+            [5, 5].into(),
+            DirectionCardinal::East,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        ));
+        assert!(matches!(world.tick_planes(), State::Ongoing));
+        assert!(matches!(
+            world.tick_planes(),
+            State::PlaneHitObstacle(plane) if plane.id == 'a' && plane.pos == [7, 5].into()
+        ));
+    }
+
+    #[test]
+    fn test_world_nothing_on_obstacles() {
+        let mut world = World::new(20, 20);
+        world.place_tile(WorldTile::Obstacle, [4, 4]).unwrap();
+        assert!(matches!(
+            world.place_tile(WorldTile::Airport(DirectionGrid::Up, 0), [4, 4]),
+            Err(Error::CellOccupied(_))
+        ));
+        assert!(matches!(
+            world.place_route_in_line([0, 0], [9, 9]),
+            Err(Error::CellOccupied(Pos { x: 4, y: 4 }))
+        ));
+        // the route was not placed partially
+        assert_eq!(world.tiles[0][0], WorldTile::Empty);
+        assert_eq!(world.tiles[4][4], WorldTile::Obstacle);
+
+        world.place_tile(WorldTile::Empty, [4, 4]).unwrap();
+        world.place_route_in_line([0, 0], [9, 9]).unwrap();
+        assert_eq!(world.tiles[4][4], WorldTile::Route);
+    }
+
+    #[test]
+    fn test_world_clear_planes() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap()
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 1)
+            .unwrap();
+        let empty = world.to_string();
+
+        let first = world
+            .spawn_plane_at_exit(0, PlaneKind::Jet, &mut rand::rng())
+            .unwrap();
+        world
+            .spawn_plane_at_exit(1, PlaneKind::Jet, &mut rand::rng())
+            .unwrap();
+        world
+            .queue_order(first, Trigger::InTicks(3), Order::Height(2))
+            .unwrap();
+        assert_ne!(world.to_string(), empty);
+
+        world.clear_planes();
+        assert_eq!(world.planes().count(), 0);
+        assert!(world.orders(first).is_empty());
+        assert_eq!(world.to_string(), empty);
+        assert_eq!(
+            world
+                .spawn_plane_at_exit(1, PlaneKind::Jet, &mut rand::rng())
+                .unwrap(),
+            first
+        );
+    }
+
+    #[test]
+    fn test_world_wind_fuel() {
+        fn fuel_after(direction: DirectionCardinal, wind: Option<Wind>) -> usize {
+            let mut world = World::new(40, 40);
+            world
+                .place_exit(DirectionGrid::Up, DirectionCardinal::South, 0, 0)
+                .unwrap()
+                .set_wind(wind);
+            world.insert_plane(Plane::new(
+                [20, 20].into(),
+                direction,
+                PlaneKind::Jet,
+                'a',
+                Destination::Exit(0),
+            ));
+            for _ in 0..12 {
+                assert!(matches!(world.tick_planes(), State::Ongoing));
+            }
+            world.plane('a').unwrap().fuel_left()
+        }
+
+        let calm = fuel_after(DirectionCardinal::East, None);
+        assert_eq!(calm, PlaneSpec::default_for(PlaneKind::Jet).fuel - 12);
+        let wind = Some(Wind::new(DirectionCardinal::East, 2));
+        // fuel can not go above a full tank, so only count what the tailwind gave back
+        assert_eq!(fuel_after(DirectionCardinal::East, wind), calm + 3 * 2);
+        assert_eq!(fuel_after(DirectionCardinal::West, wind), calm - 3 * 2);
+        assert_eq!(fuel_after(DirectionCardinal::North, wind), calm);
+    }
+
+    #[test]
+    fn test_world_diagonal_fuel_cost() {
+        fn fuel_after(direction: DirectionCardinal, diagonal_fuel_cost: bool) -> usize {
+            let mut world = World::new(20, 20);
+            world
+                .place_exit(DirectionGrid::Up, DirectionCardinal::South, 0, 0)
+                .unwrap()
+                .set_diagonal_fuel_cost(diagonal_fuel_cost);
+            world.insert_plane(Plane::new(
+                [2, 2].into(),
+                direction,
+                PlaneKind::Jet,
+                'a',
+                Destination::Exit(0),
+            ));
+            for _ in 0..5 {
+                assert!(matches!(world.tick_planes(), State::Ongoing));
+            }
+            world.plane('a').unwrap().fuel_left()
+        }
+
+        let full = PlaneSpec::default_for(PlaneKind::Jet).fuel;
+        assert_eq!(fuel_after(DirectionCardinal::East, false), full - 5);
+        assert_eq!(fuel_after(DirectionCardinal::SouthEast, false), full - 5);
+        assert_eq!(fuel_after(DirectionCardinal::East, true), full - 5);
+        assert_eq!(
+            fuel_after(DirectionCardinal::SouthEast, true),
+            full - 5 - 5 * DIAGONAL_EXTRA_FUEL
+        );
+    }
+
+    #[test]
+    fn test_world_simultaneous_landing() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 0, 0)
+            .unwrap()
+            .place_tile(WorldTile::Airport(DirectionGrid::Up, 0), [5, 10])
+            .unwrap();
+        for id in ['b', 'a'] {
+            let mut plane = Plane::new(
+                [5, 12].into(),
+                DirectionCardinal::North,
+                PlaneKind::Jet,
+                id,
+                Destination::Airport(0),
+            );
+            plane.height = 2;
+            plane.target_height = 0;
+            world.insert_plane(plane);
+        }
+
+        assert!(matches!(world.tick_planes(), State::Ongoing));
+        assert_eq!(
+            world.take_warnings(),
+            vec![
+                Warning::ConvergingLanding('a', 'b', 0),
+                Warning::Proximity('a', 'b')
+            ]
+        );
+
+        assert!(matches!(world.tick_planes(), State::Ongoing));
+        assert_eq!(world.take_warnings(), vec![Warning::GoAround('b', 0)]);
+        assert!(world.plane('a').is_none(), "a landed");
+        assert_eq!(world.stats().landings, 1);
+        assert_eq!(world.plane('b').unwrap().target_height, GO_AROUND_HEIGHT);
+    }
+
+    #[test]
+    fn test_world_crash_on_ground() {
+        fn descend(pos: [usize; 2], direction: DirectionCardinal) -> (World, State) {
+            let mut world = World::new(20, 20);
+            world
+                .place_exit(DirectionGrid::Up, DirectionCardinal::South, 0, 0)
+                .unwrap()
+                .place_tile(WorldTile::Airport(DirectionGrid::Up, 0), [5, 10])
+                .unwrap();
+            let mut plane = Plane::new(
+                pos.into(),
+                direction,
+                PlaneKind::Jet,
+                'a',
+                Destination::Airport(0),
+            );
+            plane.just_spawned = false;
+            plane.height = 1;
+            plane.target_height = 0;
+            world.insert_plane(plane);
+            let state = world.tick_planes();
+            (world, state)
+        }
+
+        let (world, state) = descend([10, 11], DirectionCardinal::North);
+        assert_eq!(world.tile([10, 10].into()), Some(WorldTile::Empty));
+        assert!(matches!(
+            state,
+            State::PlaneCrash(plane) if plane.id == 'a' && plane.pos == [10, 10].into()
+        ));
+        assert_eq!(world.stats().crashes, 1);
+        assert_eq!(
+            state.to_string(),
+            "Plane a crashed on the ground (height 0)"
+        );
+        assert_eq!(State::Ongoing.to_string(), "The game goes on");
+
+        let (world, state) = descend([4, 10], DirectionCardinal::East);
+        assert!(matches!(state, State::PlaneCrash(plane) if plane.pos == [5, 10].into()));
+        assert_eq!(world.stats().landings, 0);
+    }
+
+    #[test]
+    fn test_world_landing() {
+        fn land(destination: u8) -> (World, State) {
+            let mut world = World::new(20, 20);
+            world
+                .place_exit(DirectionGrid::Up, DirectionCardinal::South, 0, 0)
+                .unwrap()
+                .place_tile(WorldTile::Airport(DirectionGrid::Up, 0), [5, 10])
+                .unwrap()
+                .place_tile(WorldTile::Airport(DirectionGrid::Up, 1), [8, 10])
+                .unwrap();
+            let mut plane = Plane::new(
+                [5, 11].into(),
+                DirectionCardinal::North,
+                PlaneKind::Jet,
+                'a',
+                Destination::Airport(destination),
+            );
+            plane.just_spawned = false;
+            plane.height = 1;
+            plane.target_height = 0;
+            world.insert_plane(plane);
+            let state = world.tick_planes();
+            (world, state)
+        }
+
+        let (right, state) = land(0);
+        assert_eq!(state, State::Ongoing);
+        assert!(right.plane('a').is_none());
+        assert_eq!(right.stats().landings, 1);
+
+        let (wrong, state) = land(1);
+        assert!(matches!(
+            state,
+            State::WrongAirport(plane, 0) if plane.id == 'a' && plane.pos == [5, 10].into()
+        ));
+        assert_eq!(wrong.stats().landings, 0);
+        assert_eq!(wrong.stats().wrong_destinations, 1);
+    }
+
+    #[test]
+    fn test_world_proximity_warning_cooldown() {
+        let mut world = World::new(30, 20);
+        world.set_proximity_cooldown(5);
+        for (id, y) in [('a', 5), ('b', 6)] {
+            let mut plane = Plane::new(
+                [2, y].into(),
+                DirectionCardinal::East,
+                PlaneKind::Jet,
+                id,
+                Destination::Exit(0),
+            );
+            plane.just_spawned = false;
+            world.insert_plane(plane);
+        }
+        let proximity_warnings = |world: &mut World| {
+            assert_eq!(world.tick_planes(), State::Ongoing);
+            world
+                .take_warnings()
+                .into_iter()
+                .filter(|w| matches!(w, Warning::Proximity(..)))
+                .count()
+        };
+        let warned: Vec<usize> = (0..11).map(|_| proximity_warnings(&mut world)).collect();
+        assert_eq!(warned, [1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1]);
+
+        // separate the planes and let them come together again, before the cooldown is over
+        world.plane_mut('b').unwrap().pos.y = 12;
+        assert_eq!(proximity_warnings(&mut world), 0);
+        world.plane_mut('b').unwrap().pos.y = 6;
+        assert_eq!(proximity_warnings(&mut world), 1);
+        assert_eq!(proximity_warnings(&mut world), 0);
+    }
+
+    #[test]
+    fn test_world_hex_turns() {
+        use DirectionCardinal::*;
+
+        let mode = MovementMode::Hex;
+        let mut heading = NorthEast;
+        let mut seen = Vec::new();
+        for _ in 0..6 {
+            seen.push(heading);
+            heading = mode.turn(heading, 1);
+        }
+        assert_eq!(heading, NorthEast);
+        assert_eq!(
+            seen,
+            vec![NorthEast, East, SouthEast, SouthWest, West, NorthWest]
+        );
+        assert_eq!(seen, mode.headings());
+        assert_eq!(mode.turn(East, -2), NorthWest);
+        assert_eq!(mode.turn(North, 1), NorthEast);
+        assert_eq!(mode.turn(North, -1), NorthWest);
+        assert_eq!(MovementMode::EightWay.turn(East, 1), SouthEast);
+        assert_eq!(MovementMode::EightWay.turn(North, -1), NorthWest);
+
+        let mut world = World::new(20, 20);
+        world.set_movement_mode(MovementMode::Hex);
+        world.insert_plane(Plane::new(
+            [5, 5].into(),
+            East,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        ));
+        assert!(matches!(
+            world.queue_order('a', Trigger::InTicks(1), Order::Heading(North)),
+            Err(Error::HeadingNotAllowed(North))
+        ));
+        world
+            .queue_order('a', Trigger::InTicks(1), Order::Heading(SouthWest))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_world_rotated() {
+        fn tile_counts(world: &World) -> HashMap<String, usize> {
+            let mut counts = HashMap::new();
+            for tile in world.tiles.iter().flatten() {
+                let key = match tile {
+                    WorldTile::Airport(_, aid) => format!("airport {aid}"),
+                    other => other.to_string(),
+                };
+                *counts.entry(key).or_default() += 1;
+            }
+            counts
+        }
+
+        let world = crate::level::Level::builtin().world().rotated();
+        let original = crate::level::Level::builtin();
+        let original = original.world();
+        assert_eq!((world.x, world.y), (original.y, original.x));
+        assert_eq!(tile_counts(&world), tile_counts(original));
+
+        // exit 0 is on the top wall at x=12, planes fly south
+        let exit = world.exit(0).unwrap();
+        assert_eq!(exit.wall_direction, DirectionGrid::Right);
+        assert_eq!(exit.wall_pos, 12);
+        assert_eq!(exit.plane_out_direction, DirectionCardinal::West);
+        // exit 1 is on the right wall at y=2, planes fly southwest
+        let exit = world.exit(1).unwrap();
+        assert_eq!(exit.wall_direction, DirectionGrid::Down);
+        assert_eq!(exit.wall_pos, original.y - 1 - 2);
+        assert_eq!(exit.plane_out_direction, DirectionCardinal::NorthWest);
+        assert_eq!(
+            world.tiles[5][original.y - 1 - 10],
+            WorldTile::Airport(DirectionGrid::Down, 0)
+        );
+        assert!(world.validate().is_ok());
+
+        let full_turn = world.rotated().rotated().rotated();
+        assert_eq!(full_turn.to_string(), original.to_string());
+    }
+
+    #[test]
+    fn test_world_mirrored() {
+        let level = crate::level::Level::builtin();
+        let original = level.world();
+        let world = original.mirrored();
+        let exit = world.exit(1).unwrap();
+        assert_eq!(exit.wall_direction, DirectionGrid::Left);
+        assert_eq!(exit.wall_pos, 2);
+        assert_eq!(exit.plane_out_direction, DirectionCardinal::SouthEast);
+        assert_eq!(world.exit(0).unwrap().wall_pos, original.x - 1 - 12);
+        assert_eq!(world.mirrored().to_string(), original.to_string());
+    }
+
+    #[test]
+    fn test_world_too_low_small_plane_turns_once() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 0)
+            .unwrap();
+        let mut plane = Plane::new(
+            [5, 18].into(),
+            DirectionCardinal::South,
+            PlaneKind::Small,
+            'a',
+            Destination::Exit(0),
+        );
+        plane.just_spawned = false;
+        plane.height = 3;
+        plane.target_height = 3;
+        // the next tick is a move
+        plane.ticks = 1;
+        world.insert_plane(plane);
+        let id = world.resolve_plane_id('a').unwrap();
+
+        let mut positions = Vec::new();
+        for _ in 0..8 {
+            assert_eq!(world.tick_planes(), State::Ongoing);
+            let plane = world.plane(id).unwrap();
+            positions.push(plane.pos.y);
+            assert_eq!(plane.direction, DirectionCardinal::North);
+        }
+        assert_eq!(positions, vec![19, 19, 18, 18, 17, 17, 16, 16]);
+        assert_eq!(world.take_warnings(), vec![Warning::TooLowToExit(id, 0)]);
+    }
+
+    #[test]
+    fn test_world_clearance() {
+        fn world(cleared: bool) -> World {
+            let mut world = World::new(20, 20);
+            world
+                .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+                .unwrap()
+                .set_clearance_required(true);
+            let mut plane = Plane::new(
+                [5, 1].into(),
+                DirectionCardinal::North,
+                PlaneKind::Jet,
+                'a',
+                Destination::Exit(0),
+            );
+            plane.just_spawned = false;
+            plane.height = plane.spec.exit_height;
+            plane.target_height = plane.spec.exit_height;
+            plane.cleared = cleared;
+            world.insert_plane(plane);
+            world
+        }
+
+        let mut held = world(false);
+        assert!(matches!(held.tick_planes(), State::Ongoing));
+        assert_eq!(held.planes_handled(), 0);
+        assert_eq!(
+            held.take_warnings(),
+            vec![Warning::NotCleared('a', Destination::Exit(0))]
+        );
+        assert_eq!(held.plane('a').unwrap().direction, DirectionCardinal::South);
+
+        let mut cleared = world(true);
+        assert!(matches!(cleared.tick_planes(), State::Ongoing));
+        assert!(cleared.plane('a').is_none());
+        assert_eq!(cleared.planes_handled(), 1);
+    }
+
+    #[test]
+    fn test_world_uncleared_small_plane_turns_once() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap()
+            .set_clearance_required(true);
+        let mut plane = Plane::new(
+            [5, 1].into(),
+            DirectionCardinal::North,
+            PlaneKind::Small,
+            'a',
+            Destination::Exit(0),
+        );
+        plane.just_spawned = false;
+        plane.height = plane.spec.exit_height;
+        plane.target_height = plane.spec.exit_height;
+        // the next tick is a move
+        plane.ticks = 1;
+        world.insert_plane(plane);
+        let id = world.resolve_plane_id('a').unwrap();
+
+        let mut positions = Vec::new();
+        for _ in 0..6 {
+            assert_eq!(world.tick_planes(), State::Ongoing);
+            let plane = world.plane(id).unwrap();
+            positions.push(plane.pos.y);
+            assert_eq!(plane.direction, DirectionCardinal::South);
+        }
+        assert_eq!(positions, vec![0, 0, 1, 1, 2, 2]);
+        assert_eq!(
+            world.take_warnings(),
+            vec![Warning::NotCleared(id, Destination::Exit(0))]
+        );
+    }
+
+    #[test]
+    fn test_world_spawn_on_occupied_exit() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap()
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 1)
+            .unwrap();
+        let first = world
+            .spawn_plane_at_exit(0, PlaneKind::Jet, &mut rand::rng())
+            .unwrap();
+        assert!(matches!(
+            world.spawn_plane_at_exit(0, PlaneKind::Jet, &mut rand::rng()),
+            Err(Error::ExitOccupied(0))
+        ));
+        assert_eq!(world.planes().count(), 1);
+
+        world.plane_mut(first).unwrap().pos = [5, 3].into();
+        world
+            .spawn_plane_at_exit(0, PlaneKind::Jet, &mut rand::rng())
+            .unwrap();
+        assert_eq!(world.planes().count(), 2);
+    }
+
+    #[test]
+    fn test_world_default() {
+        let world = World::default();
+        assert_eq!((world.width(), world.height()), (builtin::X, builtin::Y));
+        assert_eq!(world.planes().count(), 0);
+        assert_eq!(world.exits().count(), 0);
+        assert!((0..builtin::Y)
+            .flat_map(|y| (0..builtin::X).map(move |x| Pos { x, y }))
+            .all(|pos| world.tile(pos) == Some(WorldTile::Empty)));
+    }
+
+    #[test]
+    fn test_world_too_small() {
+        for (x, y) in [(0, 0), (2, 10), (10, 2), (0, 20)] {
+            assert!(matches!(
+                World::try_new(x, y),
+                Err(Error::WorldTooSmall(_, _, MIN_WORLD_SIZE))
+            ));
+        }
+
+        let mut world = World::try_new(MIN_WORLD_SIZE, MIN_WORLD_SIZE).unwrap();
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 1, 0)
+            .unwrap()
+            .place_exit(DirectionGrid::Right, DirectionCardinal::West, 2, 1)
+            .unwrap();
+        assert!(matches!(
+            world.place_exit(DirectionGrid::Down, DirectionCardinal::North, 3, 2),
+            Err(Error::ExitPosOutOfBounds(3, 3))
+        ));
+        world
+            .spawn_plane_at_exit(1, PlaneKind::Jet, &mut rand::rng())
+            .unwrap();
+        assert_eq!(world.to_string().lines().count(), MIN_WORLD_SIZE + 2);
+        assert!(world.render_with_rulers().contains("←1"));
+        assert!(world.validate().is_ok());
+    }
+
+    #[test]
+    fn test_world_beacon_policy() {
+        fn world(policy: BeaconPolicy) -> World {
+            let mut world = World::new(20, 20);
+            world
+                .place_exit(DirectionGrid::Up, DirectionCardinal::South, 0, 0)
+                .unwrap()
+                .place_tile(WorldTile::Beacon(3), [6, 5])
+                .unwrap()
+                .set_beacon_policy(policy);
+            world.insert_plane(Plane::new(
+                [5, 5].into(),
+                DirectionCardinal::East,
+                PlaneKind::Jet,
+                'a',
+                Destination::Exit(0),
+            ));
+            world
+        }
+
+        let mut straight = world(BeaconPolicy::Continue);
+        for _ in 0..3 {
+            straight.tick_planes();
+        }
+        assert_eq!(straight.plane('a').unwrap().pos, [8, 5].into());
+        assert!(straight.take_warnings().is_empty());
+
+        let mut world = world(BeaconPolicy::Hold);
+        for _ in 0..3 {
+            world.tick_planes();
+        }
+        assert_eq!(world.plane('a').unwrap().pos, [6, 5].into());
+        assert_eq!(world.take_warnings(), vec![Warning::Holding('a', 3)]);
+        world
+            .queue_order(
+                'a',
+                Trigger::InTicks(1),
+                Order::Heading(DirectionCardinal::South),
+            )
+            .unwrap();
+        world.tick_planes();
+        world.tick_planes();
+        assert_eq!(world.plane('a').unwrap().pos, [6, 6].into());
+        assert!(!world.plane('a').unwrap().holding);
+    }
+
+    #[test]
+    fn test_world_beacon_release_small() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 0, 0)
+            .unwrap()
+            .place_tile(WorldTile::Beacon(3), [8, 8])
+            .unwrap()
+            .set_beacon_policy(BeaconPolicy::Hold);
+        let mut plane = Plane::new(
+            [8, 8].into(),
+            DirectionCardinal::North,
+            PlaneKind::Small,
+            'a',
+            Destination::Exit(0),
+        );
+        plane.holding = true;
+        world.insert_plane(plane);
+        let id = world.resolve_plane_id('a').unwrap();
+        assert_eq!(world.plane(id).unwrap().ticks, 0);
+
+        world
+            .plane_mut(id)
+            .unwrap()
+            .apply(Order::Heading(DirectionCardinal::East))
+            .unwrap();
+        world.tick_planes();
+        assert_eq!(world.plane(id).unwrap().pos, [8, 8].into());
+        assert!(!world.plane(id).unwrap().holding);
+
+        world.tick_planes();
+        assert_eq!(world.plane(id).unwrap().pos, [9, 8].into());
+        assert!(!world.plane(id).unwrap().holding);
+    }
+
+    #[test]
+    fn test_world_crossing_restriction() {
+        fn cross(height: u8) -> Vec<Warning> {
+            let mut world = World::new(20, 20);
+            world
+                .place_exit(DirectionGrid::Up, DirectionCardinal::South, 0, 0)
+                .unwrap()
+                .place_tile(WorldTile::Beacon(3), [6, 5])
+                .unwrap()
+                .restrict_beacon(3, AltitudeRestriction::AtOrAbove(5))
+                .unwrap();
+            let mut plane = Plane::new(
+                [5, 5].into(),
+                DirectionCardinal::East,
+                PlaneKind::Jet,
+                'a',
+                Destination::Exit(0),
+            );
+            plane.height = height;
+            plane.target_height = height;
+            world.insert_plane(plane);
+            for _ in 0..3 {
+                assert!(matches!(world.tick_planes(), State::Ongoing));
             }
+            world.take_warnings()
         }
 
-        if let Some((plane, exit_id)) = self.planes_take_exits() {
-            return State::WrongExit(plane, exit_id);
-        }
-        if let Some((plane, id_of_wrong_airport)) = self.planes_land() {
-            if let Some(airport_id) = id_of_wrong_airport {
-                return State::WrongAirport(plane, airport_id);
-            } else {
-                return State::PlaneCrash(plane);
+        assert_eq!(
+            cross(3),
+            vec![Warning::CrossingRestriction(
+                'a',
+                3,
+                AltitudeRestriction::AtOrAbove(5)
+            )]
+        );
+        assert!(cross(5).is_empty());
+        assert!(cross(7).is_empty());
+
+        let mut world = World::new(20, 20);
+        assert!(matches!(
+            world.restrict_beacon(1, AltitudeRestriction::At(4)),
+            Err(Error::NoBeaconForID(1))
+        ));
+        assert!(AltitudeRestriction::At(4).allows(4));
+        assert!(!AltitudeRestriction::AtOrBelow(4).allows(5));
+    }
+
+    #[test]
+    fn test_world_plane_collision() {
+        fn world(radius: usize, heights: [u8; 2], gap: usize) -> World {
+            let mut world = World::new(20, 20);
+            world
+                .place_exit(DirectionGrid::Up, DirectionCardinal::South, 0, 0)
+                .unwrap()
+                .set_collision_radius(radius);
+            for (id, x, direction, height) in [
+                ('b', 5, DirectionCardinal::East, heights[0]),
+                ('a', 7 + gap, DirectionCardinal::West, heights[1]),
+            ] {
+                let mut plane = Plane::new(
+                    [x, 5].into(),
+                    direction,
+                    PlaneKind::Jet,
+                    id,
+                    Destination::Exit(0),
+                );
+                plane.just_spawned = false;
+                plane.height = height;
+                plane.target_height = height;
+                world.insert_plane(plane);
             }
+            world
         }
-        if let Some((plane_a, plane_b)) = self.collision_check() {
-            return State::PlaneCollision(plane_a, plane_b);
+
+        let mut same_tile = world(0, [5, 5], 0);
+        assert!(matches!(
+            same_tile.tick_planes(),
+            State::PlaneCollision(a, b) if a.id == 'a' && b.id == 'b' && a.pos == [6, 5].into()
+        ));
+        assert_eq!(same_tile.stats().collisions, 1);
+
+        let mut other_heights = world(0, [5, 6], 0);
+        assert!(matches!(other_heights.tick_planes(), State::Ongoing));
+
+        // one tile apart after the tick
+        let mut apart = world(0, [5, 5], 1);
+        assert!(matches!(apart.tick_planes(), State::Ongoing));
+        let mut adjacent = world(1, [5, 5], 1);
+        assert_eq!(adjacent.collision_radius(), 1);
+        assert!(matches!(
+            adjacent.tick_planes(),
+            State::PlaneCollision(a, b) if a.id == 'a' && b.id == 'b'
+        ));
+
+        let mut spawned = world(0, [5, 5], 0);
+        spawned.plane_mut('a').unwrap().just_spawned = true;
+        spawned.set_spawn_immunity(10);
+        assert!(matches!(spawned.tick_planes(), State::Ongoing));
+
+        // both on the ground at an airport
+        let mut ground = world(0, [0, 0], 0);
+        ground
+            .place_tile(WorldTile::Airport(DirectionGrid::Right, 0), [6, 5])
+            .unwrap();
+        for id in ['a', 'b'] {
+            ground.plane_mut(id).unwrap().pos = [6, 5].into();
         }
-        if let Some((plane, direction, wall_pos)) = self.wall_collision_check() {
-            return State::PlaneTouchesWall(plane, direction, wall_pos);
+        assert!(ground.collision_check().is_none());
+        for id in ['a', 'b'] {
+            ground.plane_mut(id).unwrap().pos = [8, 5].into();
         }
-
-        State::Onging
+        assert!(ground.collision_check().is_some());
     }
-}
 
-impl DirectionCardinal {
-    pub fn opposite(self) -> Self {
-        match self {
-            Self::North => Self::South,
-            Self::East => Self::West,
-            Self::South => Self::North,
-            Self::West => Self::East,
-            Self::NorthEast => Self::SouthWest,
-            Self::NorthWest => Self::SouthEast,
-            Self::SouthEast => Self::NorthWest,
-            Self::SouthWest => Self::NorthEast,
+    #[test]
+    fn test_world_wall_collision() {
+        fn fly(pos: [usize; 2], direction: DirectionCardinal) -> State {
+            let mut world = World::new(20, 20);
+            world
+                .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+                .unwrap();
+            let mut plane = Plane::new(
+                pos.into(),
+                direction,
+                PlaneKind::Jet,
+                'a',
+                Destination::Exit(0),
+            );
+            plane.just_spawned = false;
+            plane.height = 1;
+            plane.target_height = 1;
+            world.insert_plane(plane);
+            world.tick_planes()
         }
+
+        assert!(matches!(
+            fly([8, 1], DirectionCardinal::North),
+            State::PlaneTouchesWall(plane, DirectionGrid::Up, 8) if plane.pos == [8, 0].into()
+        ));
+        assert!(matches!(
+            fly([18, 7], DirectionCardinal::East),
+            State::PlaneTouchesWall(_, DirectionGrid::Right, 7)
+        ));
+        assert!(matches!(
+            fly([3, 18], DirectionCardinal::SouthWest),
+            State::PlaneTouchesWall(_, DirectionGrid::Down, 2)
+        ));
+        // the plane can not move past the corner, it is reported against the wall ahead of it
+        assert!(matches!(
+            fly([0, 0], DirectionCardinal::West),
+            State::PlaneTouchesWall(plane, DirectionGrid::Left, 0) if plane.pos == [0, 0].into()
+        ));
+        assert!(matches!(
+            fly([0, 0], DirectionCardinal::North),
+            State::PlaneTouchesWall(_, DirectionGrid::Up, 0)
+        ));
+        // at the exit, but too low to leave: the plane turns around instead
+        assert!(matches!(
+            fly([5, 1], DirectionCardinal::North),
+            State::Ongoing
+        ));
+        assert!(matches!(
+            fly([10, 10], DirectionCardinal::North),
+            State::Ongoing
+        ));
+
+        // one tile past an exit is outside the map, not at the exit
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap()
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 1)
+            .unwrap();
+        let mut plane = Plane::new(
+            [5, 20].into(),
+            DirectionCardinal::South,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(1),
+        );
+        plane.just_spawned = false;
+        plane.height = 1;
+        plane.target_height = 1;
+        world.insert_plane(plane);
+        // orders of a plane off the map are not looked at
+        world
+            .queue_order(
+                'a',
+                Trigger::InTicks(1),
+                Order::Heading(DirectionCardinal::North),
+            )
+            .unwrap();
+        assert!(matches!(
+            world.tick_planes(),
+            State::PlaneTouchesWall(plane, DirectionGrid::Down, 5) if plane.pos == [5, 21].into()
+        ));
     }
-}
 
-impl Display for DirectionGrid {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::Up => "^",
-                Self::Left => "<",
-                Self::Down => "v",
-                Self::Right => ">",
-            }
-        )
+    #[test]
+    fn test_world_spawn_immunity() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap()
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 1)
+            .unwrap()
+            .place_tile(WorldTile::Obstacle, [5, 1])
+            .unwrap()
+            .set_spawn_immunity(3);
+        // small planes move every second tick, so this one sits on the obstacle for two ticks
+        let id = world
+            .spawn_plane_at_exit(0, PlaneKind::Small, &mut rand::rng())
+            .unwrap();
+
+        assert!(matches!(world.tick_planes(), State::Ongoing));
+        assert!(matches!(world.tick_planes(), State::Ongoing));
+        assert_eq!(world.plane(id).unwrap().pos, [5, 1].into());
+        assert!(world.plane(id).unwrap().just_spawned);
+        assert!(matches!(
+            world.tick_planes(),
+            State::PlaneHitObstacle(plane) if plane.id == id
+        ));
     }
-}
 
-impl Display for WorldTile {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::Empty => ". ".to_string(),
-                Self::Route => "+ ".to_string(),
-                Self::Beacon(idx) => format!("b{idx}"),
-                Self::Airport(dir, idx) => format!("{dir}{idx}"),
-            }
-        )
+    #[test]
+    fn test_world_grid_direction_round_trip() {
+        for wall in [
+            DirectionGrid::Up,
+            DirectionGrid::Down,
+            DirectionGrid::Left,
+            DirectionGrid::Right,
+        ] {
+            let dir: DirectionCardinal = wall.into();
+            assert_eq!(DirectionGrid::try_from(dir).unwrap(), wall);
+        }
+        for dir in [
+            DirectionCardinal::NorthEast,
+            DirectionCardinal::SouthEast,
+            DirectionCardinal::SouthWest,
+            DirectionCardinal::NorthWest,
+        ] {
+            assert!(matches!(
+                DirectionGrid::try_from(dir),
+                Err(Error::NotAGridDirection(d)) if d == dir
+            ));
+        }
     }
-}
 
-impl Display for World {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut buf = String::new();
+    #[test]
+    fn test_world_spawn_at_diagonal_exits() {
+        for (wall, dir) in [
+            (DirectionGrid::Up, DirectionCardinal::SouthEast),
+            (DirectionGrid::Up, DirectionCardinal::SouthWest),
+            (DirectionGrid::Down, DirectionCardinal::NorthEast),
+            (DirectionGrid::Down, DirectionCardinal::NorthWest),
+        ] {
+            let mut world = World::new(20, 20);
+            world
+                .place_exit(wall, dir, 10, 0)
+                .unwrap()
+                .place_exit(DirectionGrid::Left, DirectionCardinal::East, 10, 1)
+                .unwrap();
+            let id = world
+                .spawn_plane_at_exit(0, PlaneKind::Jet, &mut rand::rng())
+                .unwrap();
+            let start = world.plane(id).unwrap().pos;
+            assert!(matches!(world.tick_planes(), State::Ongoing));
+            let plane = world.plane(id).unwrap();
+            assert_eq!(plane.direction, dir);
+            assert_eq!(plane.pos, start.step(dir).unwrap());
 
-        let mut lines = Vec::new();
+            let rotated = world.rotated();
+            assert_eq!(rotated.plane(id).unwrap().direction, dir.rotate(2));
+            let mirrored = world.mirrored();
+            assert_eq!(
+                mirrored.plane(id).unwrap().direction.offset(),
+                (-dir.offset().0, dir.offset().1)
+            );
+            assert_eq!(dir.opposite().rotate(4), dir);
+        }
+    }
 
-        // top border
-        buf.push_str("┌─");
-        for x in 0..self.x {
-            buf.push_str(&self.get_wall(x, DirectionGrid::Up));
+    #[test]
+    fn test_world_planes_near() {
+        let mut world = World::new(20, 20);
+        for (id, pos, height) in [
+            ('a', [10, 10], 5),
+            ('b', [11, 10], 5),
+            ('c', [8, 12], 7),
+            ('d', [13, 10], 5),
+            ('e', [10, 6], 5),
+            ('f', [10, 10], 1),
+        ] {
+            let mut plane = Plane::new(
+                pos.into(),
+                DirectionCardinal::North,
+                PlaneKind::Jet,
+                id,
+                Destination::Exit(0),
+            );
+            plane.height = height;
+            world.insert_plane(plane);
         }
-        buf.push('┐');
-        lines.push(buf.clone());
-        buf.clear();
+        assert_eq!(world.planes_near('a', 0), vec!['f']);
+        assert_eq!(world.planes_near('a', 1), vec!['b', 'f']);
+        assert_eq!(world.planes_near('a', 2), vec!['b', 'c', 'f']);
+        assert_eq!(world.planes_near('a', 3), vec!['b', 'c', 'd', 'f']);
+        assert_eq!(world.planes_near('a', 4), vec!['b', 'c', 'd', 'e', 'f']);
+        assert_eq!(world.planes_near_in_band('a', 4, 0), vec!['b', 'd', 'e']);
+        assert_eq!(world.planes_near_in_band('a', 2, 2), vec!['b', 'c']);
+        assert_eq!(world.planes_near('d', 2), vec!['b']);
+        assert!(world.planes_near('x', 10).is_empty());
+        assert_eq!(world.proximity_conflicts(), vec![('a', 'b'), ('b', 'd')]);
 
-        // inner map
-        for (y, row) in self.tiles.iter().enumerate() {
-            buf.push_str(&self.get_wall(y, DirectionGrid::Left));
-            'tile: for (x, tile) in row.iter().enumerate() {
-                for plane in self.planes.values() {
-                    if plane.pos == [x, y].into() {
-                        buf.push_str(&plane.to_string());
-                        continue 'tile;
-                    }
-                }
-                buf.push_str(&tile.to_string());
+        assert_eq!(
+            world.occupancy_at_altitude(5),
+            BTreeMap::from([
+                ('a', [10, 10].into()),
+                ('b', [11, 10].into()),
+                ('d', [13, 10].into()),
+                ('e', [10, 6].into()),
+            ])
+        );
+        assert_eq!(
+            world.occupancy_at_altitude(7),
+            BTreeMap::from([('c', [8, 12].into())])
+        );
+        assert_eq!(
+            world.occupancy_at_altitude(1),
+            BTreeMap::from([('f', [10, 10].into())])
+        );
+        assert!(world.occupancy_at_altitude(3).is_empty());
+        let total: usize = (0..=MAX_HEIGHT)
+            .map(|height| world.occupancy_at_altitude(height).len())
+            .sum();
+        assert_eq!(total, 6);
+    }
+
+    #[test]
+    fn test_world_many_spawns_keep_ids_unique() {
+        let mut world = World::new(12, 12);
+        world
+            .place_exit(DirectionGrid::Left, DirectionCardinal::East, 4, 0)
+            .unwrap()
+            .place_exit(DirectionGrid::Right, DirectionCardinal::West, 4, 1)
+            .unwrap();
+        let spec = &mut world.plane_specs_mut().jet;
+        spec.exit_height = spec.start_height;
+        let mut held = Plane::new(
+            [5, 8].into(),
+            DirectionCardinal::North,
+            PlaneKind::Jet,
+            'c',
+            Destination::Exit(1),
+        );
+        held.holding = true;
+        held.spec.fuel = 10_000;
+        world.insert_plane(held);
+
+        let mut spawned = 0;
+        for tick in 0..1200 {
+            if tick % 3 == 0 {
+                world
+                    .spawn_plane_to(0, PlaneKind::Jet, Destination::Exit(1))
+                    .unwrap();
+                spawned += 1;
             }
-            buf.push_str(&self.get_wall(y, DirectionGrid::Right));
-            lines.push(buf.clone());
-            buf.clear();
+            assert_eq!(world.tick_planes(), State::Ongoing);
+            let mut ids: Vec<char> = world.planes().map(|p| p.id.to_ascii_lowercase()).collect();
+            ids.sort();
+            ids.dedup();
+            assert_eq!(ids.len(), world.planes().count());
+            assert_eq!(
+                world.planes().count() as u64,
+                spawned + 1 - world.stats().exits
+            );
         }
+        assert!(spawned > 255);
+        assert!(world.plane('c').unwrap().holding);
+        assert!(world.traffic().plane_counter > 255);
+    }
 
-        // top border
-        buf.push_str("└─");
-        for x in 0..self.x {
-            buf.push_str(&self.get_wall(x, DirectionGrid::Down));
+    #[test]
+    fn test_world_fly_via_beacons() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Right, DirectionCardinal::West, 4, 0)
+            .unwrap()
+            .place_tile(WorldTile::Beacon(0), [10, 15])
+            .unwrap()
+            .place_tile(WorldTile::Beacon(1), [6, 6])
+            .unwrap()
+            .set_beacon_policy(BeaconPolicy::Hold);
+        let mut plane = Plane::new(
+            [3, 12].into(),
+            DirectionCardinal::North,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        );
+        plane.height = plane.spec.exit_height;
+        plane.target_height = plane.spec.exit_height;
+        world.insert_plane(plane);
+        world.fly_via('a', &[0, 1]).unwrap();
+        assert!(world.take_warnings().is_empty());
+        let projected = world.projected_positions('a', 40);
+        let visit = |positions: &[Pos], pos: [usize; 2]| {
+            positions.iter().position(|p| *p == pos.into()).unwrap()
+        };
+
+        let mut track = Vec::new();
+        while world.planes().count() > 0 && track.len() < 60 {
+            assert_eq!(world.tick_planes(), State::Ongoing);
+            if let Some(plane) = world.plane('a') {
+                assert!(!plane.holding);
+                track.push(plane.pos);
+            }
         }
-        buf.push('┘');
-        lines.push(buf.clone());
-        buf.clear();
+        assert!(visit(&track, [10, 15]) < visit(&track, [6, 6]));
+        assert_eq!(&projected[..track.len()], track.as_slice());
+        assert_eq!(world.stats().exits, 1);
+        let exit = world.exit(0).unwrap();
+        assert_eq!(projected.last(), Some(&world.exit_pos(exit)));
+    }
 
-        // actual output
-        buf = lines.join("\n");
+    #[test]
+    fn test_world_warns_about_beacon_behind_plane() {
+        let mut world = World::new(20, 20);
+        world
+            .place_tile(WorldTile::Beacon(0), [8, 10])
+            .unwrap()
+            .place_tile(WorldTile::Beacon(1), [3, 10])
+            .unwrap();
+        world.insert_plane(Plane::new(
+            [6, 10].into(),
+            DirectionCardinal::East,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        ));
+        let north = Order::Heading(DirectionCardinal::North);
+        world.queue_order('a', Trigger::AtBeacon(0), north).unwrap();
+        assert!(world.take_warnings().is_empty());
 
-        write!(f, "{buf}")
+        world.queue_order('a', Trigger::AtBeacon(1), north).unwrap();
+        world.queue_order('a', Trigger::AtBeacon(7), north).unwrap();
+        assert_eq!(
+            world.take_warnings(),
+            vec![
+                Warning::BeaconNotAhead('a', 1),
+                Warning::BeaconNotAhead('a', 7)
+            ]
+        );
+        assert_eq!(world.orders('a').len(), 3);
     }
-}
 
-impl Display for Plane {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let id = match self.kind {
-            PlaneKind::Small => self.id.to_ascii_lowercase(),
-            PlaneKind::Jet => self.id.to_ascii_uppercase(),
-        };
-        write!(f, "{id}{}", self.height)
+    #[test]
+    fn test_world_touchdown_pos() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap();
+        let mut plane = Plane::new(
+            [5, 5].into(),
+            DirectionCardinal::East,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        );
+        plane.height = 4;
+        world.insert_plane(plane);
+        let east = DirectionCardinal::East;
+        assert_eq!(world.touchdown_pos('a', east), Some([9, 5].into()));
+        assert_eq!(
+            world.touchdown_pos('a', DirectionCardinal::South),
+            Some([5, 9].into())
+        );
+        assert_eq!(world.plane('a').unwrap().target_height, START_HEIGHT);
+        assert_eq!(world.plane('a').unwrap().direction, east);
+
+        world.plane_mut('a').unwrap().height = 0;
+        assert_eq!(world.touchdown_pos('a', east), Some([5, 5].into()));
+        world.plane_mut('a').unwrap().height = 9;
+        world.plane_mut('a').unwrap().pos = [15, 5].into();
+        assert_eq!(world.touchdown_pos('a', east), None);
+        assert_eq!(world.touchdown_pos('x', east), None);
     }
-}
 
-impl From<[usize; 2]> for Pos {
-    fn from(value: [usize; 2]) -> Self {
-        Pos {
-            x: value[0],
-            y: value[1],
+    #[test]
+    fn test_world_projected_positions() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap()
+            .place_tile(WorldTile::Beacon(0), [8, 10])
+            .unwrap();
+        world.insert_plane(Plane::new(
+            [5, 5].into(),
+            DirectionCardinal::East,
+            PlaneKind::Small,
+            's',
+            Destination::Exit(0),
+        ));
+        assert_eq!(
+            world.projected_positions('S', 6),
+            vec![
+                [5, 5].into(),
+                [6, 5].into(),
+                [6, 5].into(),
+                [7, 5].into(),
+                [7, 5].into(),
+                [8, 5].into(),
+            ]
+        );
+        assert!(world.projected_positions('x', 6).is_empty());
+
+        world.insert_plane(Plane::new(
+            [6, 10].into(),
+            DirectionCardinal::East,
+            PlaneKind::Jet,
+            'a',
+            Destination::Exit(0),
+        ));
+        world
+            .queue_order(
+                'a',
+                Trigger::AtBeacon(0),
+                Order::Heading(DirectionCardinal::South),
+            )
+            .unwrap();
+        let projected = world.projected_positions('a', 4);
+        assert_eq!(
+            projected,
+            vec![
+                [7, 10].into(),
+                [8, 10].into(),
+                [8, 11].into(),
+                [8, 12].into(),
+            ]
+        );
+        let mut actual = Vec::new();
+        for _ in 0..4 {
+            world.tick_planes();
+            actual.push(world.plane('a').unwrap().pos);
         }
+        assert_eq!(actual, projected);
     }
-}
 
-impl From<DirectionGrid> for DirectionCardinal {
-    fn from(value: DirectionGrid) -> Self {
-        match value {
-            DirectionGrid::Up => DirectionCardinal::North,
-            DirectionGrid::Down => DirectionCardinal::South,
-            DirectionGrid::Left => DirectionCardinal::West,
-            DirectionGrid::Right => DirectionCardinal::East,
+    #[test]
+    fn test_world_controllers_take_turns() {
+        let mut world = World::new(20, 20);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap()
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 1)
+            .unwrap()
+            .set_controllers(2);
+        let mut controllers = Vec::new();
+        for _ in 0..3 {
+            let id = world
+                .spawn_plane_at_exit(0, PlaneKind::Jet, &mut rand::rng())
+                .unwrap();
+            controllers.push(world.plane(id).unwrap().controller);
+            world.plane_mut(id).unwrap().pos = [5, 10].into();
         }
+        assert_eq!(controllers, vec![0, 1, 0]);
+        assert_eq!(world.set_controllers(0).controllers(), 1);
     }
-}
 
-impl TryFrom<(i32, i32)> for Pos {
-    type Error = Error;
-
-    fn try_from(value: (i32, i32)) -> Result<Self, Self::Error> {
-        if value.0 >= 0 && value.1 >= 0 {
-            Ok(Pos {
-                x: value.0 as usize,
-                y: value.1 as usize,
-            })
-        } else {
-            Err(Error::PosFromSigned(value))
+    #[test]
+    fn test_world_stats() {
+        fn world(destination: Destination, fuel: usize) -> World {
+            let mut world = World::new(20, 20);
+            world
+                .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+                .unwrap()
+                .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 1)
+                .unwrap();
+            let mut plane = Plane::new(
+                [5, 1].into(),
+                DirectionCardinal::North,
+                PlaneKind::Jet,
+                'a',
+                destination,
+            );
+            plane.just_spawned = false;
+            plane.height = plane.spec.exit_height;
+            plane.target_height = plane.spec.exit_height;
+            plane.spec.fuel = fuel;
+            world.insert_plane(plane);
+            world
         }
-    }
-}
 
-impl Display for State {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::Onging => unreachable!(),
-                Self::WrongExit(plane, eid) =>
-                    format!("Plane {} exited at the wrong exit: {eid}", plane.id),
-                Self::PlaneCrash(plane) =>
-                    format!("Plane {} crashed on the ground (height 0)", plane.id),
-                Self::PlaneNoFuel(plane) => format!("Plane {} is out of fuel", plane.id),
-                Self::WrongAirport(plane, aid) =>
-                    format!("Plane {} landed at the wrong airport: {aid}", plane.id),
-                Self::PlaneCollision(pa, pb) =>
-                    format!("Plane {} collided with Plane {}", pa.id, pb.id),
-                Self::PlaneTouchesWall(plane, _, _) =>
-                    format!("Plane {} did not leave through an exit", plane.id),
+        let mut exits = world(Destination::Exit(0), 100);
+        assert!(matches!(exits.tick_planes(), State::Ongoing));
+        assert_eq!(
+            exits.stats(),
+            Stats {
+                exits: 1,
+                fuel_left: 99,
+                ..Default::default()
             }
-        )
-    }
-}
+        );
+        assert_eq!(exits.stats().handled(), 1);
+        assert_eq!(exits.stats().lost(), 0);
+        exits.reset();
+        assert_eq!(exits.stats(), Stats::default());
 
-#[cfg(test)]
-mod test {
-    use crate::world::WorldTile;
+        let mut wrong = world(Destination::Exit(1), 100);
+        assert!(matches!(wrong.tick_planes(), State::WrongExit(..)));
+        assert_eq!(
+            wrong.stats(),
+            Stats {
+                wrong_destinations: 1,
+                ..Default::default()
+            }
+        );
 
-    use super::World;
+        let mut empty = world(Destination::Exit(0), 1);
+        assert!(matches!(empty.tick_planes(), State::PlaneNoFuel(_)));
+        assert_eq!(
+            empty.stats(),
+            Stats {
+                out_of_fuel: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(empty.stats().lost(), 1);
+    }
 
     #[test]
-    #[should_panic]
-    fn test_world_place_route_out_of_bounds() {
+    fn test_world_panic_hold() {
         let mut world = World::new(20, 20);
-        world.place_route_in_line([0, 0], [20, 19]).unwrap();
-        for i in 0..19 {
-            assert_eq!(world.tiles[i][i], WorldTile::Route);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap();
+        for (id, pos, direction) in [
+            ('a', [5, 5], DirectionCardinal::East),
+            ('b', [10, 10], DirectionCardinal::SouthWest),
+            ('c', [15, 5], DirectionCardinal::North),
+        ] {
+            world.insert_plane(Plane::new(
+                pos.into(),
+                direction,
+                PlaneKind::Jet,
+                id,
+                Destination::Exit(0),
+            ));
         }
+        world.plane_mut('c').unwrap().holding = true;
 
-        let mut world = World::new(20, 20);
-        world.place_route_in_line([0, 0], [19, 20]).unwrap();
-        for i in 0..19 {
-            assert_eq!(world.tiles[i][i], WorldTile::Route);
-        }
+        world.panic_hold();
+        assert!(world.panic_holding());
+        assert!(world.planes().all(|p| p.holding));
+        world.tick_planes();
+        world.tick_planes();
+        assert_eq!(world.plane('a').unwrap().pos, [5, 5].into());
+        assert_eq!(world.plane('b').unwrap().pos, [10, 10].into());
+
+        world.release_panic_hold();
+        assert!(!world.panic_holding());
+        let a = world.plane('a').unwrap();
+        assert!(!a.holding);
+        assert_eq!(a.direction, DirectionCardinal::East);
+        assert_eq!(
+            world.plane('b').unwrap().direction,
+            DirectionCardinal::SouthWest
+        );
+        assert!(world.plane('c').unwrap().holding);
+        world.tick_planes();
+        assert_eq!(world.plane('a').unwrap().pos, [6, 5].into());
     }
 
     #[test]
-    fn test_world_place_route_0() {
+    fn test_world_exit_lanes() {
         let mut world = World::new(20, 20);
-        world.place_route_in_line([0, 0], [19, 19]).unwrap();
-        println!("{}", world);
-        for i in 0..19 {
-            assert_eq!(world.tiles[i][i], WorldTile::Route);
+        world
+            .place_exit_at_height(DirectionGrid::Up, DirectionCardinal::South, 5, 0, 3)
+            .unwrap()
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 5, 1)
+            .unwrap();
+        let mut plane = Plane::new(
+            [5, 0].into(),
+            DirectionCardinal::North,
+            PlaneKind::Jet,
+            'x',
+            Destination::Exit(0),
+        );
+        plane.height = 4;
+        world.insert_plane(plane);
+        assert!(matches!(
+            world.spawn_plane_at_exit(0, PlaneKind::Jet, &mut rand::rng()),
+            Err(Error::ExitOccupied(0))
+        ));
+        assert!(world.set_exit_lanes(7, 2).is_err());
+        world.set_exit_lanes(0, 2).unwrap();
+        assert!(world
+            .spawn_plane_at_exit(0, PlaneKind::Jet, &mut rand::rng())
+            .is_err());
+        world.plane_mut('x').unwrap().height = 7;
+        assert!(world.in_separate_lanes([5, 0].into(), 7, 3));
+        assert!(!world.in_separate_lanes([6, 0].into(), 7, 3));
+        world
+            .spawn_plane_at_exit(0, PlaneKind::Jet, &mut rand::rng())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_world_planes_leave_through_every_wall() {
+        let mut world = World::new(10, 10);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 2, 0)
+            .unwrap()
+            .place_exit(DirectionGrid::Down, DirectionCardinal::North, 2, 1)
+            .unwrap()
+            .place_exit(DirectionGrid::Left, DirectionCardinal::East, 2, 2)
+            .unwrap()
+            .place_exit(DirectionGrid::Right, DirectionCardinal::West, 2, 3)
+            .unwrap();
+        for (id, pos, heading, exit) in [
+            ('a', [2, 1], DirectionCardinal::North, 0),
+            ('b', [2, 8], DirectionCardinal::South, 1),
+            ('c', [1, 2], DirectionCardinal::West, 2),
+            ('d', [8, 2], DirectionCardinal::East, 3),
+        ] {
+            let mut plane = Plane::new(
+                pos.into(),
+                heading,
+                PlaneKind::Jet,
+                id,
+                Destination::Exit(exit),
+            );
+            plane.just_spawned = false;
+            plane.height = plane.spec.exit_height;
+            plane.target_height = plane.spec.exit_height;
+            world.insert_plane(plane);
         }
+        assert_eq!(world.tick_planes(), State::Ongoing);
+        assert_eq!(world.planes().count(), 0);
+        assert_eq!(world.stats().exits, 4);
     }
 
     #[test]
-    fn test_world_place_route_1() {
+    fn test_world_exit_lanes_both_leave() {
         let mut world = World::new(20, 20);
-        world.place_route_in_line([19, 19], [0, 0]).unwrap();
-        println!("{}", world);
-        for i in 0..19 {
-            assert_eq!(world.tiles[i][i], WorldTile::Route);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap()
+            .set_exit_lanes(0, 2)
+            .unwrap();
+        for (id, height) in [('a', 9), ('b', 5)] {
+            let mut plane = Plane::new(
+                [5, 1].into(),
+                DirectionCardinal::North,
+                PlaneKind::Jet,
+                id,
+                Destination::Exit(0),
+            );
+            plane.height = height;
+            plane.target_height = height;
+            plane.spec.exit_height = 5;
+            plane.just_spawned = false;
+            world.insert_plane(plane);
         }
+        assert!(world.in_separate_lanes([5, 0].into(), 9, 5));
+        assert!(matches!(world.tick_planes(), State::Ongoing));
+        assert_eq!(world.planes().count(), 0);
+        assert_eq!(world.stats().exits, 2);
     }
 
     #[test]
-    fn test_world_place_route_2() {
+    fn test_world_safety_checks() {
         let mut world = World::new(20, 20);
-        world.place_route_in_line([19, 0], [0, 19]).unwrap();
-        println!("{}", world);
-        for i in 0..19 {
-            assert_eq!(world.tiles[19 - i][i], WorldTile::Route);
+        world
+            .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+            .unwrap()
+            .place_tile(WorldTile::Obstacle, [10, 11])
+            .unwrap();
+        for (id, pos) in [('a', [1, 1]), ('b', [10, 10]), ('c', [5, 1])] {
+            world.insert_plane(Plane::new(
+                pos.into(),
+                DirectionCardinal::South,
+                PlaneKind::Jet,
+                id,
+                Destination::Exit(0),
+            ));
+        }
+
+        assert!(world
+            .check_heading_safe('a', DirectionCardinal::North)
+            .is_ok());
+        world.set_safety_checks(true);
+        for (id, heading) in [
+            ('a', DirectionCardinal::North),
+            ('a', DirectionCardinal::West),
+            ('a', DirectionCardinal::NorthWest),
+            ('b', DirectionCardinal::South),
+        ] {
+            assert!(matches!(
+                world.check_heading_safe(id, heading),
+                Err(Error::UnsafeHeading(p, h)) if p == id && h == heading
+            ));
+        }
+        for (id, heading) in [
+            ('a', DirectionCardinal::East),
+            ('a', DirectionCardinal::SouthEast),
+            ('b', DirectionCardinal::North),
+            ('c', DirectionCardinal::North),
+        ] {
+            assert!(world.check_heading_safe(id, heading).is_ok());
         }
     }
 }
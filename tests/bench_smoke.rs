@@ -0,0 +1,20 @@
+//! Makes sure the `tick_planes` benchmark keeps working, without measuring anything
+
+#[path = "../benches/load/mod.rs"]
+mod load;
+
+#[test]
+fn test_bench_busy_world_runs() {
+    let mut world = load::busy_world(1337, 200);
+    assert_eq!(world.planes().count(), 200);
+    load::run(&mut world, 5);
+    assert_eq!(world.planes().count(), 200);
+
+    let again = load::busy_world(1337, 200);
+    let pos = |w: &base::world::World| {
+        let mut p: Vec<_> = w.planes().map(|p| (p.id, p.pos)).collect();
+        p.sort_by_key(|(id, _)| *id);
+        p
+    };
+    assert_eq!(pos(&again), pos(&load::busy_world(1337, 200)));
+}
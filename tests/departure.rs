@@ -0,0 +1,86 @@
+//! A plane takes off from an airport, climbs and leaves through its exit
+
+use base::{
+    command::Command,
+    plane::{Destination, EXIT_HEIGHT},
+    world::{DirectionCardinal, DirectionGrid, PlaneKind, State, Warning, World, WorldTile},
+};
+
+fn world() -> World {
+    let mut world = World::new(20, 20);
+    world
+        .place_tile(WorldTile::Airport(DirectionGrid::Up, 0), [5, 10])
+        .unwrap()
+        .place_exit(DirectionGrid::Up, DirectionCardinal::South, 5, 0)
+        .unwrap();
+    world
+}
+
+fn vector(world: &mut World, plane: char, heading: DirectionCardinal, height: u8) {
+    Command::Vector {
+        plane,
+        heading,
+        height,
+    }
+    .apply(world)
+    .unwrap();
+}
+
+fn tick(world: &mut World, ticks: usize) {
+    for _ in 0..ticks {
+        assert_eq!(world.tick_planes(), State::Ongoing);
+    }
+}
+
+#[test]
+fn test_departure_leaves_only_at_exit_height() {
+    let mut world = world();
+    let id = world
+        .spawn_plane_at_airport(0, PlaneKind::Jet, &mut rand::rng())
+        .unwrap();
+    let plane = *world.plane(id).unwrap();
+    assert_eq!(plane.destination, Destination::Exit(0));
+    assert_eq!(plane.direction, DirectionCardinal::North);
+    assert_eq!((plane.height, plane.pos), (0, [5, 10].into()));
+
+    // the plane waits on the runway instead of landing where it took off
+    tick(&mut world, 5);
+    let waiting = world.plane(id).unwrap();
+    assert_eq!((waiting.height, waiting.pos), (0, [5, 10].into()));
+    assert_eq!(world.stats().landings, 0);
+
+    // too low at the exit, the plane turns around
+    vector(&mut world, id, DirectionCardinal::North, 5);
+    tick(&mut world, 10);
+    let low = world.plane(id).unwrap();
+    assert_eq!((low.pos, low.height), ([5, 0].into(), 5));
+    assert_eq!(low.direction, DirectionCardinal::South);
+    assert!(world
+        .take_warnings()
+        .contains(&Warning::TooLowToExit(id, 0)));
+    assert_eq!(world.stats().exits, 0);
+
+    vector(&mut world, id, DirectionCardinal::South, EXIT_HEIGHT);
+    tick(&mut world, 5);
+    vector(&mut world, id, DirectionCardinal::North, EXIT_HEIGHT);
+    tick(&mut world, 4);
+    let high = world.plane(id).unwrap();
+    assert_eq!((high.pos, high.height), ([5, 1].into(), EXIT_HEIGHT));
+    tick(&mut world, 1);
+    assert!(world.plane(id).is_none());
+    assert_eq!(world.stats().exits, 1);
+}
+
+#[test]
+fn test_departure_needs_a_free_airport() {
+    let mut world = world();
+    world
+        .spawn_plane_at_airport(0, PlaneKind::Small, &mut rand::rng())
+        .unwrap();
+    assert!(world
+        .spawn_plane_at_airport(0, PlaneKind::Jet, &mut rand::rng())
+        .is_err());
+    assert!(world
+        .spawn_plane_at_airport(3, PlaneKind::Jet, &mut rand::rng())
+        .is_err());
+}
@@ -0,0 +1,41 @@
+//! The level files in `assets/levels` load and play
+
+use std::path::Path;
+
+use base::{level::Level, world::State};
+
+fn assets() -> Vec<std::path::PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/levels");
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn test_levels_load_and_render() {
+    let paths = assets();
+    assert!(paths.len() >= 2, "{paths:?}");
+    for path in paths {
+        let mut level = Level::load(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+        let render = level.render();
+        assert!(!render.is_empty(), "{}", path.display());
+
+        let reloaded = Level::parse(&level.to_file_string()).unwrap();
+        assert_eq!(reloaded.render(), render, "{}", path.display());
+
+        level.set_seed(3);
+        assert_eq!(level.tick(), State::Ongoing, "{}", path.display());
+    }
+}
+
+#[test]
+fn test_levels_default_is_builtin() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/levels/default.toml");
+    let level = Level::load(path).unwrap();
+    assert_eq!(level.render(), Level::builtin().render());
+    assert_eq!(level.to_file_string(), Level::builtin().to_file_string());
+}
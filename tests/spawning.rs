@@ -0,0 +1,36 @@
+//! Planes come in the same way every time for the same seed
+
+use base::{
+    level::{builtin::DEFAULT_SEED, Level, Spawn},
+    world::State,
+};
+
+/// The planes that came in up to `ticks` or until the game was over
+fn spawns(mut level: Level, ticks: u64) -> Vec<(u64, Spawn)> {
+    let mut spawns = Vec::new();
+    for tick in 1..=ticks {
+        let state = level.tick();
+        spawns.extend(level.take_spawns().into_iter().map(|spawn| (tick, spawn)));
+        if state != State::Ongoing {
+            break;
+        }
+    }
+    spawns
+}
+
+#[test]
+fn test_spawning_same_seed_same_planes() {
+    let first = spawns(Level::with_seed(7), 200);
+    assert!(first.len() >= 2, "{first:?}");
+    assert_eq!(first, spawns(Level::with_seed(7), 200));
+    assert_ne!(first, spawns(Level::with_seed(8), 200));
+}
+
+#[test]
+fn test_spawning_builtin_uses_default_seed() {
+    assert_eq!(Level::builtin().seed(), DEFAULT_SEED);
+    assert_eq!(
+        spawns(Level::builtin(), 200),
+        spawns(Level::with_seed(DEFAULT_SEED), 200)
+    );
+}